@@ -0,0 +1,76 @@
+use crate::*;
+use gloo_console::log;
+use std::collections::BTreeMap;
+
+#[derive(Properties, Clone, PartialEq, serde::Deserialize, Debug)]
+pub struct CommentStruct {
+    pub id: u32,
+    pub parent_id: Option<u32>,
+    pub body: String,
+    pub created_at: String,
+}
+
+/// Orders comments so each one is followed by its replies, along with how deep each one
+/// is nested. Mirrors `thread` in the server's `entities::comment`, where it is tested;
+/// change both together.
+pub fn thread(comments: &[CommentStruct]) -> Vec<(usize, CommentStruct)> {
+    let ids: HashSet<u32> = comments.iter().map(|comment| comment.id).collect();
+    let mut replies: BTreeMap<Option<u32>, Vec<CommentStruct>> = BTreeMap::new();
+    for comment in comments {
+        let parent = comment.parent_id.filter(|parent| ids.contains(parent));
+        replies.entry(parent).or_default().push(comment.clone());
+    }
+
+    let mut threaded = Vec::new();
+    let mut stack: Vec<(usize, CommentStruct)> = replies
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|comment| (0, comment))
+        .collect();
+    while let Some((depth, comment)) = stack.pop() {
+        if let Some(children) = replies.remove(&Some(comment.id)) {
+            stack.extend(children.into_iter().rev().map(|child| (depth + 1, child)));
+        }
+        threaded.push((depth, comment));
+    }
+    threaded
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct CommentsProps {
+    /// Where to fetch the comments from
+    pub url: String,
+}
+
+#[function_component(Comments)]
+pub fn comments(props: &CommentsProps) -> Html {
+    let comments = use_state(Vec::<CommentStruct>::new);
+    {
+        let comments = comments.clone();
+        use_effect_with(props.url.clone(), move |url| {
+            let url = url.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match http::Request::get(&url).send().await {
+                    Ok(response) => match response.json::<Vec<CommentStruct>>().await {
+                        Ok(fetched) => comments.set(fetched),
+                        Err(_) => log!("bad comments"),
+                    },
+                    Err(_) => log!("comments error"),
+                }
+            });
+        });
+    }
+
+    html! {
+        <div class="comments">
+            { for thread(&comments).into_iter().map(|(depth, comment)| html! {
+                <div class="comment" style={format!("margin-left: {}em", depth * 2)}>
+                    <span class="tellee">{comment.body}</span>
+                    <span class="annotation">{format!(" [{}]", comment.created_at)}</span>
+                </div>
+            }) }
+        </div>
+    }
+}
@@ -1,6 +1,8 @@
+mod comment;
 mod finder;
 mod question;
 
+use comment::*;
 use finder::*;
 use gloo_console::log;
 use question::*;
@@ -16,6 +18,12 @@ use yew::prelude::*;
 
 pub type QuestionResult = Result<Vec<QuestionStruct>, gloo_net::Error>;
 
+/// Base URL of the server's API, set with `API_URL` when building
+pub const API_URL: &str = match option_env!("API_URL") {
+    Some(url) => url,
+    None => "http://localhost:3000/api/v1",
+};
+
 struct App {
     question: QuestionResult,
 }
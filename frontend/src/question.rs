@@ -9,16 +9,26 @@ pub struct QuestionStruct {
     pub id: u32,
     pub title: String,
     pub content: String,
+    /// The content rendered to sanitized HTML by the server
+    pub content_html: Option<String>,
     pub tags: Option<HashSet<String>>,
-    pub answer: Option<String>,
+    pub accepted_answer: Option<AnswerStruct>,
+}
+
+#[derive(Properties, Clone, PartialEq, serde::Deserialize, Debug)]
+pub struct AnswerStruct {
+    pub id: u32,
+    pub answer: String,
+    /// The answer rendered to sanitized HTML by the server
+    pub answer_html: Option<String>,
 }
 
 impl QuestionStruct {
     pub async fn get_question(key: Option<String>) -> Msg {
         log!("question");
         let request = match &key {
-            None => "http://localhost:3000/api/v1/questions".to_string(),
-            Some(ref key) => format!("http://localhost:3000/api/v1/questions/{}", key,),
+            None => format!("{API_URL}/questions"),
+            Some(ref key) => format!("{API_URL}/questions/{}", key,),
         };
         log!("q2");
         let response = http::Request::get(&request).send().await;
@@ -35,6 +45,15 @@ impl QuestionStruct {
     }
 }
 
+/// Shows a body the server rendered from Markdown, or the raw text from older servers.
+pub fn markdown(html: &Option<String>, raw: &str) -> Html {
+    match html {
+        // Sanitized on the server before it is stored
+        Some(html) => Html::from_html_unchecked(AttrValue::from(html.clone())),
+        None => html! { {raw.to_string()} },
+    }
+}
+
 pub fn format_tags(tags: &HashSet<String>) -> String {
     let taglist: Vec<&str> = tags.iter().map(String::as_ref).collect();
     taglist.join(", ")
@@ -62,9 +81,13 @@ pub fn question(questions: &QuestionProps) -> Html {
                     <div class="question">
                         <span class="teller">{"Question:"}</span><br/>
                         <span class="tellee">{format!("{}", &question.title)}</span><br/>
-                        <span class="tellee">{format!("{}", &question.content)}</span><br/><br/>
+                        <div class="tellee">{markdown(&question.content_html, &question.content)}</div><br/>
+                        <Comments url={format!("{API_URL}/questions/{}/comments", question.id)}/>
                         <span class="tellee">{"Answer:"}</span><br/>
-                        <span class="tellee">{match &question.answer { Some(res) => { format!("{}.", res) }, None => { "No Answer yet.".to_string() } }}</span><br/>
+                        <div class="tellee">{match &question.accepted_answer { Some(res) => markdown(&res.answer_html, &res.answer), None => html! { {"No Answer yet."} } }}</div><br/>
+                        if let Some(ref res) = question.accepted_answer {
+                            <Comments url={format!("{API_URL}/answers/{}/comments", res.id)}/>
+                        }
                     </div>
                     <span class="annotation">
                         {format!("[id: {}", &question.id)}
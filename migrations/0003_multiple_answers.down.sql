@@ -0,0 +1,8 @@
+-- Rollback migration
+ALTER TABLE answers
+	DROP CONSTRAINT answers_question_id_fkey,
+	ADD CONSTRAINT answers_question_id_fkey
+		FOREIGN KEY (question_id) REFERENCES questions(id);
+DROP INDEX IF EXISTS answers_one_accepted_idx;
+DROP INDEX IF EXISTS answers_question_id_idx;
+ALTER TABLE answers DROP COLUMN IF EXISTS accepted;
@@ -0,0 +1,12 @@
+ALTER TABLE answers ADD COLUMN accepted BOOLEAN NOT NULL DEFAULT false;
+
+/*
+* A question can have many answers, but at most one accepted answer
+*/
+CREATE INDEX answers_question_id_idx ON answers (question_id);
+CREATE UNIQUE INDEX answers_one_accepted_idx ON answers (question_id) WHERE accepted;
+
+ALTER TABLE answers
+	DROP CONSTRAINT answers_question_id_fkey,
+	ADD CONSTRAINT answers_question_id_fkey
+		FOREIGN KEY (question_id) REFERENCES questions(id) ON DELETE CASCADE;
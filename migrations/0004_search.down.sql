@@ -0,0 +1,5 @@
+-- Rollback migration
+DROP INDEX IF EXISTS answers_search_idx;
+ALTER TABLE answers DROP COLUMN IF EXISTS search;
+DROP INDEX IF EXISTS questions_search_idx;
+ALTER TABLE questions DROP COLUMN IF EXISTS search;
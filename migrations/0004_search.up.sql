@@ -0,0 +1,13 @@
+/*
+* Full-text search vectors, kept up to date by Postgres on every write
+*/
+ALTER TABLE questions ADD COLUMN search tsvector GENERATED ALWAYS AS (
+	setweight(to_tsvector('english', title), 'A') ||
+	setweight(to_tsvector('english', content), 'B')
+) STORED;
+CREATE INDEX questions_search_idx ON questions USING GIN (search);
+
+ALTER TABLE answers ADD COLUMN search tsvector GENERATED ALWAYS AS (
+	to_tsvector('english', answer)
+) STORED;
+CREATE INDEX answers_search_idx ON answers USING GIN (search);
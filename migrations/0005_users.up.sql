@@ -0,0 +1,20 @@
+CREATE TABLE users (
+	id integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	username TEXT NOT NULL UNIQUE,
+	password_hash TEXT NOT NULL,
+	is_admin BOOLEAN NOT NULL DEFAULT false
+);
+
+/*
+* Login sessions, only a hash of the cookie's token is stored
+*/
+CREATE TABLE sessions (
+	token_hash TEXT PRIMARY KEY,
+	user_id integer NOT NULL,
+	expires_at TIMESTAMPTZ NOT NULL,
+	FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
+
+ALTER TABLE questions ADD COLUMN author_id integer REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE answers ADD COLUMN author_id integer REFERENCES users(id) ON DELETE SET NULL;
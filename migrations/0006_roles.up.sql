@@ -0,0 +1,7 @@
+/*
+* Replace the admin flag with a role: reader < member < moderator < admin
+*/
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'member'
+	CHECK (role IN ('reader', 'member', 'moderator', 'admin'));
+UPDATE users SET role = 'admin' WHERE is_admin;
+ALTER TABLE users DROP COLUMN is_admin;
@@ -0,0 +1,15 @@
+/*
+* Personal API tokens, only a hash of the token is stored
+*/
+CREATE TABLE api_tokens (
+	id integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id integer NOT NULL,
+	name TEXT NOT NULL,
+	token_hash TEXT NOT NULL UNIQUE,
+	scopes TEXT[] NOT NULL CHECK (scopes <@ ARRAY['read', 'write', 'admin']),
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	expires_at TIMESTAMPTZ,
+	last_used_at TIMESTAMPTZ,
+	FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX api_tokens_user_id_idx ON api_tokens (user_id);
@@ -0,0 +1,29 @@
+/*
+* One vote per user per question or answer, the sum is kept in `score`
+*/
+ALTER TABLE questions ADD COLUMN score integer NOT NULL DEFAULT 0;
+ALTER TABLE answers ADD COLUMN score integer NOT NULL DEFAULT 0;
+
+CREATE TABLE question_votes (
+	question_id integer NOT NULL,
+	user_id integer NOT NULL,
+	value SMALLINT NOT NULL CHECK (value IN (-1, 1)),
+	PRIMARY KEY (question_id, user_id),
+	FOREIGN KEY (question_id) REFERENCES questions(id) ON DELETE CASCADE,
+	FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX question_votes_user_id_idx ON question_votes (user_id);
+
+CREATE TABLE answer_votes (
+	answer_id integer NOT NULL,
+	user_id integer NOT NULL,
+	value SMALLINT NOT NULL CHECK (value IN (-1, 1)),
+	PRIMARY KEY (answer_id, user_id),
+	FOREIGN KEY (answer_id) REFERENCES answers(id) ON DELETE CASCADE,
+	FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX answer_votes_user_id_idx ON answer_votes (user_id);
+
+-- Keyset pagination by score
+CREATE INDEX questions_score_idx ON questions (score DESC, id);
+CREATE INDEX answers_question_id_score_idx ON answers (question_id, score DESC, id);
@@ -0,0 +1,29 @@
+/*
+* Full snapshots of every version of a question or answer, numbered from 1 per item
+*/
+CREATE TABLE revisions (
+	id integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	question_id integer REFERENCES questions(id) ON DELETE CASCADE,
+	answer_id integer REFERENCES answers(id) ON DELETE CASCADE,
+	revision integer NOT NULL,
+	author_id integer REFERENCES users(id) ON DELETE SET NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	title TEXT,
+	content TEXT NOT NULL,
+	tags TEXT[],
+	CHECK ((question_id IS NULL) <> (answer_id IS NULL)),
+	UNIQUE (question_id, revision),
+	UNIQUE (answer_id, revision)
+);
+
+-- What exists so far becomes the first revision
+INSERT INTO revisions (question_id, revision, author_id, title, content, tags)
+SELECT q.id, 1, q.author_id, q.title, q.content,
+	(SELECT ARRAY_AGG(t.name ORDER BY t.name)
+	 FROM question_tags qt JOIN tags t ON qt.tag_id = t.id
+	 WHERE qt.question_id = q.id)
+FROM questions q;
+
+INSERT INTO revisions (answer_id, revision, author_id, content)
+SELECT a.id, 1, a.author_id, a.answer
+FROM answers a;
@@ -0,0 +1,2 @@
+-- Rollback migration, merged tags stay merged
+ALTER TABLE tags DROP CONSTRAINT IF EXISTS tags_name_key;
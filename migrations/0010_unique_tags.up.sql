@@ -0,0 +1,25 @@
+/*
+* One row per tag: names are stored trimmed and lowercased, and questions
+* pointing at duplicates are moved to the oldest row with the same name
+*/
+DELETE FROM tags WHERE btrim(name) = '';
+
+CREATE TEMPORARY TABLE tag_merges AS
+SELECT id AS old_id,
+	first_value(id) OVER (PARTITION BY lower(btrim(name)) ORDER BY id) AS new_id
+FROM tags;
+
+INSERT INTO question_tags (question_id, tag_id)
+SELECT qt.question_id, m.new_id
+FROM question_tags qt
+JOIN tag_merges m ON qt.tag_id = m.old_id
+WHERE m.old_id <> m.new_id
+ON CONFLICT DO NOTHING;
+
+DELETE FROM tags
+WHERE id IN (SELECT old_id FROM tag_merges WHERE old_id <> new_id);
+
+DROP TABLE tag_merges;
+
+UPDATE tags SET name = lower(btrim(name));
+ALTER TABLE tags ADD CONSTRAINT tags_name_key UNIQUE (name);
@@ -0,0 +1,3 @@
+-- Rollback migration
+DROP TABLE IF EXISTS tag_synonyms;
+ALTER TABLE tags DROP COLUMN IF EXISTS description;
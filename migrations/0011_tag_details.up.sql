@@ -0,0 +1,14 @@
+/*
+* Tag descriptions and synonyms, names that are replaced by their tag on write
+*/
+ALTER TABLE tags ADD COLUMN description TEXT;
+
+CREATE TABLE tag_synonyms (
+	name TEXT PRIMARY KEY,
+	tag_id integer NOT NULL REFERENCES tags(id) ON DELETE CASCADE
+);
+CREATE INDEX tag_synonyms_tag_id_idx ON tag_synonyms (tag_id);
+
+-- Tags no question uses are removed from now on
+DELETE FROM tags t
+WHERE NOT EXISTS (SELECT 1 FROM question_tags qt WHERE qt.tag_id = t.id);
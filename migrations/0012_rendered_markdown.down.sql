@@ -0,0 +1,3 @@
+-- Rollback migration
+ALTER TABLE answers DROP COLUMN IF EXISTS answer_html;
+ALTER TABLE questions DROP COLUMN IF EXISTS content_html;
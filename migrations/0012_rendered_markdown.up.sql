@@ -0,0 +1,6 @@
+/*
+* HTML rendered from the Markdown bodies, cached so reads don't render it again.
+* Rows written before this migration are rendered when the server starts.
+*/
+ALTER TABLE questions ADD COLUMN content_html TEXT;
+ALTER TABLE answers ADD COLUMN answer_html TEXT;
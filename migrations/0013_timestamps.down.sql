@@ -0,0 +1,9 @@
+-- Rollback migration
+DROP INDEX IF EXISTS questions_last_activity_at_idx;
+DROP INDEX IF EXISTS questions_updated_at_idx;
+ALTER TABLE tags DROP COLUMN IF EXISTS updated_at, DROP COLUMN IF EXISTS created_at;
+ALTER TABLE answers DROP COLUMN IF EXISTS updated_at, DROP COLUMN IF EXISTS created_at;
+ALTER TABLE questions
+	DROP COLUMN IF EXISTS last_activity_at,
+	DROP COLUMN IF EXISTS updated_at,
+	DROP COLUMN IF EXISTS created_at;
@@ -0,0 +1,49 @@
+/*
+* When questions, answers and tags were created and last changed, and when a question or
+* one of its answers was last written to
+*/
+ALTER TABLE questions
+	ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	ADD COLUMN last_activity_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE answers
+	ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE tags
+	ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+-- The revision history tells when existing questions and answers were written
+UPDATE questions q
+SET created_at = r.first, updated_at = r.last
+FROM (
+	SELECT question_id, MIN(created_at) AS first, MAX(created_at) AS last
+	FROM revisions WHERE question_id IS NOT NULL
+	GROUP BY question_id
+) r
+WHERE r.question_id = q.id;
+
+UPDATE answers a
+SET created_at = r.first, updated_at = r.last
+FROM (
+	SELECT answer_id, MIN(created_at) AS first, MAX(created_at) AS last
+	FROM revisions WHERE answer_id IS NOT NULL
+	GROUP BY answer_id
+) r
+WHERE r.answer_id = a.id;
+
+UPDATE questions q
+SET last_activity_at = GREATEST(q.updated_at,
+	(SELECT MAX(a.updated_at) FROM answers a WHERE a.question_id = q.id));
+
+-- A tag appeared with the first question using it
+UPDATE tags t
+SET created_at = COALESCE(
+	(SELECT MIN(q.created_at) FROM question_tags qt
+	 JOIN questions q ON qt.question_id = q.id
+	 WHERE qt.tag_id = t.id),
+	t.created_at);
+UPDATE tags SET updated_at = created_at;
+
+CREATE INDEX questions_updated_at_idx ON questions (updated_at);
+CREATE INDEX questions_last_activity_at_idx ON questions (last_activity_at);
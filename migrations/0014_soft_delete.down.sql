@@ -0,0 +1,5 @@
+-- Rollback migration
+DROP INDEX IF EXISTS answers_deleted_at_idx;
+DROP INDEX IF EXISTS questions_deleted_at_idx;
+ALTER TABLE answers DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE questions DROP COLUMN IF EXISTS deleted_at;
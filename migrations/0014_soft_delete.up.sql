@@ -0,0 +1,9 @@
+/*
+* Deleted questions and answers stay in the trash, hidden from every read, until they are
+* restored or purged
+*/
+ALTER TABLE questions ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE answers ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX questions_deleted_at_idx ON questions (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX answers_deleted_at_idx ON answers (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,17 @@
+/*
+* Short comments on questions and answers, threaded through replies to other comments
+*/
+CREATE TABLE comments (
+	id integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	question_id integer REFERENCES questions(id) ON DELETE CASCADE,
+	answer_id integer REFERENCES answers(id) ON DELETE CASCADE,
+	parent_id integer REFERENCES comments(id) ON DELETE CASCADE,
+	author_id integer REFERENCES users(id) ON DELETE SET NULL,
+	body TEXT NOT NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+
+CREATE INDEX comments_question_id_idx ON comments (question_id);
+CREATE INDEX comments_answer_id_idx ON comments (answer_id);
@@ -0,0 +1,8 @@
+-- Rollback migration
+DROP TABLE IF EXISTS question_closures;
+ALTER TABLE questions
+	DROP COLUMN IF EXISTS closed_by,
+	DROP COLUMN IF EXISTS duplicate_of,
+	DROP COLUMN IF EXISTS close_note,
+	DROP COLUMN IF EXISTS close_reason,
+	DROP COLUMN IF EXISTS closed_at;
@@ -0,0 +1,23 @@
+/*
+* Moderators close questions as duplicates, off-topic or resolved, and reopen them. Every close
+* and reopen is kept in question_closures.
+*/
+ALTER TABLE questions
+	ADD COLUMN closed_at TIMESTAMPTZ,
+	ADD COLUMN close_reason TEXT CHECK (close_reason IN ('duplicate', 'off_topic', 'resolved')),
+	ADD COLUMN close_note TEXT,
+	ADD COLUMN duplicate_of integer REFERENCES questions(id) ON DELETE SET NULL,
+	ADD COLUMN closed_by integer REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE TABLE question_closures (
+	id integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	question_id integer NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+	action TEXT NOT NULL CHECK (action IN ('close', 'reopen')),
+	reason TEXT CHECK (reason IN ('duplicate', 'off_topic', 'resolved')),
+	note TEXT,
+	duplicate_of integer REFERENCES questions(id) ON DELETE SET NULL,
+	user_id integer REFERENCES users(id) ON DELETE SET NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX question_closures_question_id_idx ON question_closures (question_id);
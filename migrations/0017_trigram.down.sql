@@ -0,0 +1,2 @@
+-- Rollback migration
+DROP INDEX IF EXISTS questions_title_trgm;
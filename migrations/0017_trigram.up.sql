@@ -0,0 +1,6 @@
+/*
+* Similar titles are found with pg_trgm trigram similarity, the index speeds up the `%`
+* operator on titles.
+*/
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE INDEX IF NOT EXISTS questions_title_trgm ON questions USING GIN (title gin_trgm_ops);
@@ -0,0 +1,3 @@
+-- Rollback migration
+DROP TABLE IF EXISTS quiz_items;
+DROP TABLE IF EXISTS quiz_sessions;
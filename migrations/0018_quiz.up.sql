@@ -0,0 +1,24 @@
+/*
+* Quiz sessions serve a user random answered questions, optionally drawn from some tags, and
+* grade what they answer against the question's accepted or best answer.
+*/
+CREATE TABLE quiz_sessions (
+	id integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+	user_id integer NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	tags TEXT[] NOT NULL DEFAULT '{}',
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE quiz_items (
+	session_id integer NOT NULL REFERENCES quiz_sessions(id) ON DELETE CASCADE,
+	question_id integer NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+	answer TEXT,
+	correct BOOLEAN,
+	similarity REAL,
+	served_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	answered_at TIMESTAMPTZ,
+	PRIMARY KEY (session_id, question_id)
+);
+
+CREATE INDEX quiz_sessions_user_id_idx ON quiz_sessions (user_id);
+CREATE INDEX quiz_items_question_id_idx ON quiz_items (question_id);
@@ -0,0 +1,5 @@
+-- Rollback migration
+ALTER TABLE answers
+	DROP COLUMN IF EXISTS key;
+ALTER TABLE questions
+	DROP COLUMN IF EXISTS kind;
@@ -0,0 +1,8 @@
+/*
+* Questions are free text, multiple choice, true/false or numeric. The kind, with its choices or
+* unit, is kept on the question and the structured answer to grade against on each answer.
+*/
+ALTER TABLE questions
+	ADD COLUMN kind JSONB NOT NULL DEFAULT '{"type": "free_text"}';
+ALTER TABLE answers
+	ADD COLUMN key JSONB;
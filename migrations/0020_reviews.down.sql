@@ -0,0 +1,2 @@
+-- Rollback migration
+DROP TABLE IF EXISTS reviews;
@@ -0,0 +1,17 @@
+/*
+* Spaced repetition: every question a user answered in a quiz is a card, rescheduled after
+* each graded attempt with the SM-2 algorithm.
+*/
+CREATE TABLE reviews (
+	user_id integer NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	question_id integer NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+	ease REAL NOT NULL,
+	interval_days integer NOT NULL,
+	repetitions integer NOT NULL,
+	reviewed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	due_at TIMESTAMPTZ NOT NULL,
+	PRIMARY KEY (user_id, question_id)
+);
+
+CREATE INDEX reviews_user_id_due_at_idx ON reviews (user_id, due_at);
+CREATE INDEX reviews_question_id_idx ON reviews (question_id);
@@ -0,0 +1,2 @@
+-- Rollback migration
+DROP INDEX IF EXISTS tags_name_normalized_idx;
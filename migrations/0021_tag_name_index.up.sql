@@ -0,0 +1,5 @@
+/*
+* Tag names are unique however they are cased or padded, even if a write
+* skips normalizing them
+*/
+CREATE UNIQUE INDEX tags_name_normalized_idx ON tags (lower(btrim(name)));
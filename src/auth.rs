@@ -0,0 +1,332 @@
+use crate::{
+    config::QuestionBank,
+    entities::{
+        token::Scope,
+        user::{Role, User},
+    },
+    models::errors::{QuestionBankErr, QuestionBankError},
+};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    response::Response,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::{error::Error, marker::PhantomData, ops::Deref, sync::Arc};
+use tokio::sync::RwLock;
+
+/// Name of the cookie holding the session token
+pub const SESSION_COOKIE: &str = "session";
+
+/// How long a login session stays valid
+pub const SESSION_TTL: time::Duration = time::Duration::days(7);
+
+/// Prefix of API tokens, so they are easy to spot in scripts and secret scanners
+pub const API_TOKEN_PREFIX: &str = "qb_";
+
+/// Hashes a password with argon2 and a random salt.
+///
+/// # Returns
+///
+/// The PHC string to store, which includes the salt and the argon2 parameters.
+pub fn hash_password(password: &str) -> Result<String, Box<dyn Error>> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| e.to_string())?;
+
+    Ok(hash.to_string())
+}
+
+/// Checks a password against a stored argon2 PHC string.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Generates 32 random bytes, base64url encoded.
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Generates a new random session token.
+///
+/// # Returns
+///
+/// The token to send in the cookie and its hash, which is what gets stored.
+pub fn new_session_token() -> (String, String) {
+    let token = random_token();
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+/// Generates a new random API token.
+///
+/// # Returns
+///
+/// The token to show to the user once and its hash, which is what gets stored.
+pub fn new_api_token() -> (String, String) {
+    let token = format!("{API_TOKEN_PREFIX}{}", random_token());
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+/// Hashes a session or API token, so a leaked table can't be used to log in.
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Derives the token page forms must post back from a session token. Another site can't
+/// read the session cookie, so it can't forge a form that passes the check.
+pub fn csrf_token(session_token: &str) -> String {
+    hash_token(&format!("csrf:{session_token}"))
+}
+
+/// Builds the HTTP-only cookie carrying a session token.
+pub fn session_cookie(token: String) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE, token))
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .path("/")
+        .max_age(SESSION_TTL)
+        .build()
+}
+
+/// How a request was authenticated
+#[derive(Debug, Clone, PartialEq)]
+pub enum Access {
+    /// With the session cookie, which can do anything the user can. Page forms must
+    /// post back `csrf`, see [`csrf_token`].
+    Session { csrf: String },
+    /// With an `Authorization: Bearer` API token limited to these scopes
+    Token(Vec<Scope>),
+}
+
+/// The logged in user making the request, resolved from an `Authorization: Bearer`
+/// API token or else from the session cookie.
+///
+/// Handlers that take a `CurrentUser` reject anonymous requests with 401;
+/// `Option<CurrentUser>` accepts both. API tokens need the `read` scope for `GET`
+/// requests and the `write` scope for anything else.
+#[derive(Debug, Clone)]
+pub struct CurrentUser(pub User, pub Access);
+
+impl CurrentUser {
+    /// Whether the request may do what `scope` allows. Sessions can do anything,
+    /// and each token scope includes the ones before it.
+    pub fn allows(&self, scope: Scope) -> bool {
+        match &self.1 {
+            Access::Session { .. } => true,
+            Access::Token(scopes) => scopes.iter().any(|granted| *granted >= scope),
+        }
+    }
+
+    /// The 403 response for a token lacking `scope`.
+    fn missing_scope(scope: Scope) -> Response {
+        QuestionBankError::response(
+            StatusCode::FORBIDDEN,
+            Box::new(QuestionBankErr::Forbidden(format!(
+                "the API token needs the {scope} scope"
+            ))),
+        )
+    }
+
+    /// Whether the user may edit or delete content written by `author_id`:
+    /// its author, or a moderator.
+    pub fn can_edit(&self, author_id: Option<i32>) -> bool {
+        self.0.role >= Role::Moderator || author_id == Some(self.0.id)
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<RwLock<QuestionBank>>> for CurrentUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<RwLock<QuestionBank>>,
+    ) -> Result<Self, Self::Rejection> {
+        let unauthorized = || {
+            QuestionBankError::response(
+                StatusCode::UNAUTHORIZED,
+                Box::new(QuestionBankErr::Unauthorized),
+            )
+        };
+
+        let read_lock = state.read().await;
+        let user = if let Some(header) = parts.headers.get(AUTHORIZATION) {
+            let invalid_token = || {
+                QuestionBankError::response(
+                    StatusCode::UNAUTHORIZED,
+                    Box::new(QuestionBankErr::InvalidToken),
+                )
+            };
+            let token = header
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .ok_or_else(invalid_token)?;
+            match read_lock.tokens.token_user(&hash_token(token.trim())).await {
+                Ok(Some((user, scopes))) => CurrentUser(user, Access::Token(scopes)),
+                Ok(None) => return Err(invalid_token()),
+                Err(e) => {
+                    return Err(QuestionBankError::response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        e,
+                    ))
+                }
+            }
+        } else {
+            let jar = CookieJar::from_headers(&parts.headers);
+            let token = jar.get(SESSION_COOKIE).ok_or_else(unauthorized)?;
+            match read_lock
+                .users
+                .session_user(&hash_token(token.value()))
+                .await
+            {
+                Ok(Some(user)) => CurrentUser(
+                    user,
+                    Access::Session {
+                        csrf: csrf_token(token.value()),
+                    },
+                ),
+                Ok(None) => return Err(unauthorized()),
+                Err(e) => {
+                    return Err(QuestionBankError::response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        e,
+                    ))
+                }
+            }
+        };
+
+        let scope = if parts.method.is_safe() {
+            Scope::Read
+        } else {
+            Scope::Write
+        };
+        if !user.allows(scope) {
+            return Err(CurrentUser::missing_scope(scope));
+        }
+
+        Ok(user)
+    }
+}
+
+/// A role a route requires, see [`Authorized`].
+pub trait RequiredRole {
+    const ROLE: Role;
+    /// The scope an API token needs on top of the one implied by the request method
+    const SCOPE: Scope;
+}
+
+/// Requires any logged in user, and the admin scope from API tokens
+#[derive(Debug, Clone)]
+pub struct AccountRole;
+
+impl RequiredRole for AccountRole {
+    const ROLE: Role = Role::Reader;
+    const SCOPE: Scope = Scope::Admin;
+}
+
+/// Requires the member role or above
+#[derive(Debug, Clone)]
+pub struct MemberRole;
+
+impl RequiredRole for MemberRole {
+    const ROLE: Role = Role::Member;
+    const SCOPE: Scope = Scope::Write;
+}
+
+/// Requires the moderator role or above
+#[derive(Debug, Clone)]
+pub struct ModeratorRole;
+
+impl RequiredRole for ModeratorRole {
+    const ROLE: Role = Role::Moderator;
+    const SCOPE: Scope = Scope::Write;
+}
+
+/// Requires the admin role
+#[derive(Debug, Clone)]
+pub struct AdminRole;
+
+impl RequiredRole for AdminRole {
+    const ROLE: Role = Role::Admin;
+    const SCOPE: Scope = Scope::Admin;
+}
+
+/// The logged in user, if they have at least the role `R` and, when using an API token,
+/// the scope `R` needs.
+///
+/// Anonymous requests are rejected with 401 and users with a lesser role or token scope
+/// with 403, so handlers taking one of the aliases below don't have to check roles
+/// themselves.
+#[derive(Debug, Clone)]
+pub struct Authorized<R> {
+    user: CurrentUser,
+    role: PhantomData<R>,
+}
+
+/// Any logged in user managing their own account
+pub type Account = Authorized<AccountRole>;
+/// A logged in member, moderator or admin
+pub type Member = Authorized<MemberRole>;
+/// A logged in moderator or admin
+pub type Moderator = Authorized<ModeratorRole>;
+/// A logged in admin
+pub type Admin = Authorized<AdminRole>;
+
+impl<R> Deref for Authorized<R> {
+    type Target = CurrentUser;
+
+    fn deref(&self) -> &CurrentUser {
+        &self.user
+    }
+}
+
+#[async_trait]
+impl<R: RequiredRole> FromRequestParts<Arc<RwLock<QuestionBank>>> for Authorized<R> {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<RwLock<QuestionBank>>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = CurrentUser::from_request_parts(parts, state).await?;
+        if user.0.role < R::ROLE {
+            return Err(QuestionBankError::response(
+                StatusCode::FORBIDDEN,
+                Box::new(QuestionBankErr::Forbidden(format!(
+                    "requires the {} role",
+                    R::ROLE
+                ))),
+            ));
+        }
+        if !user.allows(R::SCOPE) {
+            return Err(CurrentUser::missing_scope(R::SCOPE));
+        }
+
+        Ok(Authorized {
+            user,
+            role: PhantomData,
+        })
+    }
+}
@@ -1,26 +1,125 @@
 use crate::db_config::*;
-use sqlx::{Pool, Postgres};
-use std::error::Error;
+use crate::repositories::{
+    answer_repo::AnswerRepository, bulk_repo::BulkRepository, closure_repo::ClosureRepository,
+    comment_repo::CommentRepository, lib::*, question_repo::QuestionRepository,
+    quiz_repo::QuizRepository, review_repo::ReviewRepository, revision_repo::RevisionRepository,
+    search_repo::SearchRepository, tag_repo::TagRepository, token_repo::TokenRepository,
+    trash_repo::TrashRepository, user_repo::UserRepository, vote_repo::VoteRepository,
+};
+use std::sync::Arc;
+
+/// Environment variable used to pick the storage backend at startup.
+pub const BACKEND_VAR: &str = "QUESTION_BANK_BACKEND";
+
+/// The storage backends a `QuestionBank` can run on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Postgres,
+    Memory,
+}
+
+impl Backend {
+    /// Reads the backend from `QUESTION_BANK_BACKEND`, defaulting to Postgres
+    /// when the variable is unset.
+    ///
+    /// # Returns
+    ///
+    /// The selected `Backend`, or an error if the value is not recognized.
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        match std::env::var(BACKEND_VAR) {
+            Ok(value) => match value.trim().to_lowercase().as_str() {
+                "postgres" | "pg" => Ok(Backend::Postgres),
+                "memory" | "in-memory" => Ok(Backend::Memory),
+                other => Err(format!("Unknown {BACKEND_VAR} value: {other}").into()),
+            },
+            Err(_) => Ok(Backend::default()),
+        }
+    }
+}
 
 /// A question bank that stores and manages questions and their answers
 #[derive(Debug)]
 pub struct QuestionBank {
-    pub question_db: Pool<Postgres>,
+    pub questions: Arc<dyn QuestionRepository>,
+    pub quizzes: Arc<dyn QuizRepository>,
+    pub reviews: Arc<dyn ReviewRepository>,
+    pub answers: Arc<dyn AnswerRepository>,
+    pub bulk: Arc<dyn BulkRepository>,
+    pub closures: Arc<dyn ClosureRepository>,
+    pub comments: Arc<dyn CommentRepository>,
+    pub revisions: Arc<dyn RevisionRepository>,
+    pub search: Arc<dyn SearchRepository>,
+    pub tags: Arc<dyn TagRepository>,
+    pub tokens: Arc<dyn TokenRepository>,
+    pub trash: Arc<dyn TrashRepository>,
+    pub users: Arc<dyn UserRepository>,
+    pub votes: Arc<dyn VoteRepository>,
 }
 
 impl QuestionBank {
-    /// Creates a new `QuestionBank` instance.
-    ///
-    /// # Parameters
-    ///
-    /// * `db_path`: The path to the file that will store the questions.
+    /// Creates a new `QuestionBank` instance using the backend selected by
+    /// `QUESTION_BANK_BACKEND`.
     ///
     /// # Returns
     ///
     /// A new `QuestionBank` instance, or an error if the database cannot be initialized
     pub async fn new() -> Result<Self, Box<dyn Error>> {
-        let question_db = db_setup().await?;
+        match Backend::from_env()? {
+            Backend::Postgres => {
+                let question_db = db_setup().await?;
+                Ok(Self::postgres(PostgresRepo::new(question_db)))
+            }
+            Backend::Memory => {
+                tracing::info!("Using the in-memory backend, nothing will be persisted");
+                Ok(Self::in_memory())
+            }
+        }
+    }
+
+    /// Creates a `QuestionBank` backed by Postgres.
+    ///
+    /// # Parameters
+    ///
+    /// * `repo`: The Postgres repository to store questions and answers in.
+    pub fn postgres(repo: PostgresRepo) -> Self {
+        let repo = Arc::new(repo);
+        Self {
+            questions: repo.clone(),
+            quizzes: repo.clone(),
+            reviews: repo.clone(),
+            answers: repo.clone(),
+            bulk: repo.clone(),
+            closures: repo.clone(),
+            comments: repo.clone(),
+            revisions: repo.clone(),
+            search: repo.clone(),
+            tags: repo.clone(),
+            tokens: repo.clone(),
+            trash: repo.clone(),
+            users: repo.clone(),
+            votes: repo,
+        }
+    }
 
-        Ok(Self { question_db })
+    /// Creates a `QuestionBank` that keeps everything in memory.
+    pub fn in_memory() -> Self {
+        let repo = Arc::new(MemoryRepo::new());
+        Self {
+            questions: repo.clone(),
+            quizzes: repo.clone(),
+            reviews: repo.clone(),
+            answers: repo.clone(),
+            bulk: repo.clone(),
+            closures: repo.clone(),
+            comments: repo.clone(),
+            revisions: repo.clone(),
+            search: repo.clone(),
+            tags: repo.clone(),
+            tokens: repo.clone(),
+            trash: repo.clone(),
+            users: repo.clone(),
+            votes: repo,
+        }
     }
 }
@@ -1,22 +1,56 @@
 use crate::{
-    controllers::lib::*,
-    entities::answer::Answer,
-    models::{answer_model::*, errors::QuestionBankError},
+    auth::{CurrentUser, Member},
+    controllers::{
+        auth_controller::AuthSchemes,
+        closure_controller, comment_controller,
+        lib::*,
+        revision_controller, trash_controller,
+        vote_controller::{self, fill_answer_votes},
+    },
+    entities::{
+        answer::Answer,
+        comment::{Comment, CommentRequest},
+        kind::{AnswerKey, QuestionKind},
+        revision::{DiffLine, DiffOp, Revision, RevisionDiff},
+        vote::Vote,
+    },
+    models::{answer_model, errors::*},
+    negotiate::{Accept, Responder, ITEM_FORMATS, LIST_FORMATS},
+    pagination::{AnswerFilter, AnswerSort, Pagination},
     QuestionBank,
 };
+use std::error::Error;
 
 // From utoipa/examples/{simple-axum, axum-todo}.
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        get_answer,
+        get_answers,
         post_answer,
+        get_answer,
         delete_answer,
         update_answer,
+        accept_answer,
+        unaccept_answer,
+        vote_controller::upvote_answer,
+        vote_controller::downvote_answer,
+        vote_controller::unvote_answer,
+        revision_controller::get_answer_revisions,
+        revision_controller::diff_answer_revisions,
+        revision_controller::rollback_answer,
+        trash_controller::get_trashed_answers,
+        trash_controller::restore_answer,
+        comment_controller::get_answer_comments,
+        comment_controller::post_answer_comment,
+        comment_controller::get_answer_comment,
+        comment_controller::update_answer_comment,
+        comment_controller::delete_answer_comment,
     ),
     components(
-        schemas(Answer, QuestionBankError)
+        schemas(Answer, AnswerSort, Vote, Revision, RevisionDiff, DiffLine, DiffOp, Comment,
+            CommentRequest, AnswerKey, QuestionBankError)
     ),
+    modifiers(&AuthSchemes),
     tags(
         (name = "Question's Answers API", description = "Question's Answer API")
     )
@@ -24,64 +58,242 @@ use crate::{
 // TODO figure out a better way of combining answers/questions openapi
 pub struct ApiDoc2;
 
+/// Whose permission an answer operation needs
+enum Owner {
+    /// The author of the answer
+    Answer,
+    /// The author of the question it answers
+    Question,
+}
+
+/// Checks that the user may change an answer, see [`CurrentUser::can_edit`].
+///
+/// # Returns
+///
+/// The error response to send if the answer does not exist or the user is not allowed.
+async fn check_author(
+    bank: &QuestionBank,
+    user: &CurrentUser,
+    answer_id: i32,
+    owner: Owner,
+) -> Result<(), Response> {
+    let answer = match bank.answers.get(answer_id).await {
+        Ok(answer) => answer,
+        Err(e) => return Err(QuestionBankError::response(StatusCode::NOT_FOUND, e)),
+    };
+
+    let author_id = match owner {
+        Owner::Answer => answer.author_id,
+        Owner::Question => match bank
+            .questions
+            .get(answer.question_id.unwrap_or_default())
+            .await
+        {
+            Ok(question) => question.first().and_then(|question| question.author_id),
+            Err(e) => return Err(QuestionBankError::response(StatusCode::NOT_FOUND, e)),
+        },
+    };
+
+    if user.can_edit(author_id) {
+        Ok(())
+    } else {
+        let who = match owner {
+            Owner::Answer => "answer",
+            Owner::Question => "question",
+        };
+        Err(QuestionBankError::response(
+            StatusCode::FORBIDDEN,
+            Box::new(QuestionBankErr::Forbidden(format!(
+                "only the author of the {who} or a moderator can change answer {answer_id}"
+            ))),
+        ))
+    }
+}
+
+/// Checks the key of an answer being written against the kind of its question.
+///
+/// # Parameters
+///
+/// * `question_id`: The question answered.
+/// * `answer`: The answer as written.
+/// * `editing`: Whether an existing answer is edited, whose key is kept if left out.
+///
+/// # Returns
+///
+/// The answer with its key cleaned up, or a `QuestionBankErr` error if the key doesn't fit the
+/// question or a new answer to a question that isn't free text has none.
+pub async fn check_key(
+    bank: &QuestionBank,
+    question_id: i32,
+    mut answer: Answer,
+    editing: bool,
+) -> Result<Answer, Box<dyn Error>> {
+    let kind = bank
+        .questions
+        .get(question_id)
+        .await?
+        .remove(0)
+        .kind
+        .unwrap_or_default();
+    answer.key = match answer.key.take() {
+        Some(key) => Some(key.validate(&kind).map_err(QuestionBankErr::InvalidInput)?),
+        None if editing || kind == QuestionKind::FreeText => None,
+        None => {
+            return Err(Box::new(QuestionBankErr::InvalidInput(format!(
+                "an answer to a {} question needs a key",
+                kind.name()
+            ))))
+        }
+    };
+
+    Ok(answer)
+}
+
 #[utoipa::path(
     get,
-    path = "/api/v1/questions/{id}/answer",
+    path = "/api/v1/questions/{id}/answers",
+    params(Pagination, AnswerFilter),
     responses(
-        (status = 200, description = "Return specified answer", body = Answer),
-        (status = 404, description = "No answer with this question", body = QuestionBankError),
+        (status = 200, description = "List the question's answers, by default the accepted \
+            answer first, paginated like the question list. Also available as CSV or NDJSON",
+            body = [Answer]),
+        (status = 400, description = "Invalid pagination", body = QuestionBankError),
+        (status = 404, description = "No question with this id", body = QuestionBankError),
+        (status = 406, description = "None of JSON, CSV or NDJSON is accepted",
+            body = QuestionBankError),
     )
 )]
-pub async fn get_answer(
+pub async fn get_answers(
     State(answers): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    accept: Accept,
+    OriginalUri(uri): OriginalUri,
     Path(question_id): Path<i32>,
+    Query(params): Query<Pagination>,
+    Query(filter): Query<AnswerFilter>,
 ) -> Response {
+    let respond = match Responder::negotiate(&accept, LIST_FORMATS, &user) {
+        Ok(respond) => respond,
+        Err(response) => return *response,
+    };
+    let page = match params.validate(&answer_model::order(filter.sort)) {
+        Ok(page) => page,
+        Err(e) => return respond.error(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+
     let read_guard = answers.read().await;
-    match get(&read_guard.question_db, question_id).await {
-        Ok(answer) => answer.into_response(),
-        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    if let Err(e) = read_guard.questions.get(question_id).await {
+        return respond.error(StatusCode::NOT_FOUND, e);
+    }
+
+    let mut res = match read_guard
+        .answers
+        .get_all(question_id, &page, filter.sort)
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return respond.error(StatusCode::NOT_FOUND, e),
+    };
+    match fill_answer_votes(&read_guard, &user, &mut res.items).await {
+        Ok(()) => respond.page(res, &uri),
+        Err(e) => respond.error(StatusCode::INTERNAL_SERVER_ERROR, e),
     }
 }
 
 #[utoipa::path(
     post,
-    path = "/api/v1/questions/{id}/answer",
+    path = "/api/v1/questions/{id}/answers",
+    security(("session" = ["member"]), ("token" = ["write"])),
     request_body(
         content = inline(Answer),
         description = "Answer to add"
     ),
     responses(
         (status = 201, description = "Added answer", body = ()),
-        (status = 400, description = "Bad request", body = QuestionBankError)
+        (status = 400, description = "Bad request", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't post", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+        (status = 409, description = "Question closed", body = QuestionBankError),
     )
 )]
 pub async fn post_answer(
     State(answers): State<Arc<RwLock<QuestionBank>>>,
-    Json(answer): Json<Answer>,
+    user: Member,
+    Path(question_id): Path<i32>,
+    Json(mut answer): Json<Answer>,
 ) -> Response {
     let write_guard = answers.write().await;
     tracing::info!("post answer");
-    match add(&write_guard.question_db, answer).await {
+    if let Err(e) = closure_controller::check_open(&write_guard, question_id).await {
+        return QuestionBankError::response(closure_controller::error_status(e.as_ref()), e);
+    }
+    answer.question_id = Some(question_id);
+    answer.author_id = Some(user.0.id);
+    let answer = match check_key(&write_guard, question_id, answer, false).await {
+        Ok(answer) => answer,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    match write_guard.answers.add(answer).await {
         Ok(_) => StatusCode::CREATED.into_response(),
         Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/answers/{answer_id}",
+    responses(
+        (status = 200, description = "Return specified answer", body = Answer),
+        (status = 404, description = "No answer with this id", body = QuestionBankError),
+        (status = 406, description = "Neither JSON nor NDJSON is accepted",
+            body = QuestionBankError),
+    )
+)]
+pub async fn get_answer(
+    State(answers): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    accept: Accept,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    let respond = match Responder::negotiate(&accept, ITEM_FORMATS, &user) {
+        Ok(respond) => respond,
+        Err(response) => return *response,
+    };
+    let read_guard = answers.read().await;
+    let mut answer = match read_guard.answers.get(answer_id).await {
+        Ok(answer) => answer,
+        Err(e) => return respond.error(StatusCode::NOT_FOUND, e),
+    };
+    match fill_answer_votes(&read_guard, &user, std::slice::from_mut(&mut answer)).await {
+        Ok(()) => respond.item(&answer),
+        Err(e) => respond.error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
 #[utoipa::path(
     delete,
-    path = "/api/v1/questions/{id}/answer",
+    path = "/api/v1/answers/{answer_id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
     responses(
         (status = 200, description = "Deleted answer", body = ()),
         (status = 400, description = "Bad request", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
     )
 )]
 pub async fn delete_answer(
     State(answers): State<Arc<RwLock<QuestionBank>>>,
-    Path(question_id): Path<i32>,
+    user: Member,
+    Path(answer_id): Path<i32>,
 ) -> Response {
     tracing::info!("delete answer");
     let write_guard = answers.write().await;
-    match delete(&write_guard.question_db, question_id).await {
+    if let Err(response) = check_author(&write_guard, &user, answer_id, Owner::Answer).await {
+        return response;
+    }
+    match write_guard.answers.delete(answer_id).await {
         Ok(()) => StatusCode::OK.into_response(),
         Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
     }
@@ -89,14 +301,17 @@ pub async fn delete_answer(
 
 #[utoipa::path(
     put,
-    path = "/api/v1/questions/{id}/answer",
+    path = "/api/v1/answers/{answer_id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
     request_body(
         content = inline(Answer),
-        description = "Question to update"
+        description = "Answer to update"
     ),
     responses(
         (status = 200, description = "Updated answer", body = ()),
         (status = 400, description = "Bad request", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
         (status = 404, description = "Answer not found", body = QuestionBankError),
         (status = 422, description = "Unprocessable entity", body = QuestionBankError),
     )
@@ -104,13 +319,94 @@ pub async fn delete_answer(
 #[debug_handler]
 pub async fn update_answer(
     State(answers): State<Arc<RwLock<QuestionBank>>>,
-    Path(question_id): Path<i32>,
+    user: Member,
+    Path(answer_id): Path<i32>,
     Json(answer): Json<Answer>,
 ) -> Response {
-    println!("delete");
+    tracing::info!("update answer");
     let write_guard = answers.write().await;
-    match update(&write_guard.question_db, question_id, answer).await {
+    if let Err(response) = check_author(&write_guard, &user, answer_id, Owner::Answer).await {
+        return response;
+    }
+    let question_id = match write_guard.answers.get(answer_id).await {
+        Ok(current) => current.question_id.unwrap_or_default(),
+        Err(e) => return QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    };
+    let answer = match check_key(&write_guard, question_id, answer, true).await {
+        Ok(answer) => answer,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    match write_guard
+        .answers
+        .update(answer_id, answer, Some(user.0.id))
+        .await
+    {
         Ok(_) => StatusCode::OK.into_response(),
         Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
     }
 }
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/answers/{answer_id}/accept",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Marked the answer as accepted. Only the question's author \
+            or a moderator can accept answers", body = Answer),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn accept_answer(
+    State(answers): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    accept: Accept,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    tracing::info!("accept answer");
+    let respond = match Responder::negotiate(&accept, ITEM_FORMATS, &Some((*user).clone())) {
+        Ok(respond) => respond,
+        Err(response) => return *response,
+    };
+    let write_guard = answers.write().await;
+    if let Err(response) = check_author(&write_guard, &user, answer_id, Owner::Question).await {
+        return response;
+    }
+    match write_guard.answers.accept(answer_id).await {
+        Ok(answer) => respond.item(&answer),
+        Err(e) => respond.error(StatusCode::NOT_FOUND, e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/answers/{answer_id}/accept",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Removed the accepted mark from the answer", body = Answer),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn unaccept_answer(
+    State(answers): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    accept: Accept,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    tracing::info!("unaccept answer");
+    let respond = match Responder::negotiate(&accept, ITEM_FORMATS, &Some((*user).clone())) {
+        Ok(respond) => respond,
+        Err(response) => return *response,
+    };
+    let write_guard = answers.write().await;
+    if let Err(response) = check_author(&write_guard, &user, answer_id, Owner::Question).await {
+        return response;
+    }
+    match write_guard.answers.unaccept(answer_id).await {
+        Ok(answer) => respond.item(&answer),
+        Err(e) => respond.error(StatusCode::NOT_FOUND, e),
+    }
+}
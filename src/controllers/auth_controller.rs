@@ -0,0 +1,275 @@
+use crate::{
+    auth::*,
+    controllers::lib::*,
+    entities::user::{Credentials, Role, User},
+    models::errors::*,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify,
+};
+
+/// Adds the `session` cookie and `token` bearer security schemes to the OpenAPI docs.
+///
+/// Routes list the least role they require as the `session` scope (`member`,
+/// `moderator` or `admin`) and the API token scope they need as the `token` scope.
+pub struct AuthSchemes;
+
+impl Modify for AuthSchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "session",
+                SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::with_description(
+                    SESSION_COOKIE,
+                    "Session cookie set by /api/v1/auth/login",
+                ))),
+            );
+            components.add_security_scheme(
+                "token",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .description(Some("Personal API token created with /api/v1/tokens"))
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
+/// Shortest accepted password
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Checks that a username is 3 to 32 letters, digits, `_` or `-`, and that the
+/// password is long enough.
+fn validate(credentials: &Credentials) -> Result<(), QuestionBankErr> {
+    let username = &credentials.username;
+    if !(3..=32).contains(&username.chars().count())
+        || !username
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(QuestionBankErr::InvalidInput(
+            "username must be 3 to 32 letters, digits, '_' or '-'".to_string(),
+        ));
+    }
+    if credentials.password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(QuestionBankErr::InvalidInput(format!(
+            "password must be at least {MIN_PASSWORD_LENGTH} characters"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Environment variable naming the user made an admin at startup
+pub const ADMIN_USERNAME_VAR: &str = "ADMIN_USERNAME";
+
+/// Environment variable with the password the admin is registered with if they don't exist yet
+pub const ADMIN_PASSWORD_VAR: &str = "ADMIN_PASSWORD";
+
+/// Makes the user named by `ADMIN_USERNAME` an admin, registering them with `ADMIN_PASSWORD`
+/// first if they don't exist yet. Does nothing when `ADMIN_USERNAME` is unset.
+///
+/// # Returns
+///
+/// An error if the admin doesn't exist and can't be registered.
+pub async fn seed_admin_from_env(bank: &QuestionBank) -> Result<(), Box<dyn std::error::Error>> {
+    let Ok(username) = std::env::var(ADMIN_USERNAME_VAR) else {
+        return Ok(());
+    };
+    let user = match bank.users.get_credentials(&username).await? {
+        Some((user, _)) => user,
+        None => {
+            let Ok(password) = std::env::var(ADMIN_PASSWORD_VAR) else {
+                return Err(format!(
+                    "{ADMIN_USERNAME_VAR} names user {username}, who doesn't exist, and \
+                    {ADMIN_PASSWORD_VAR} is unset"
+                )
+                .into());
+            };
+            let credentials = Credentials { username, password };
+            validate(&credentials).map_err(|e| format!("Invalid {ADMIN_USERNAME_VAR}: {e}"))?;
+            bank.users
+                .create(
+                    &credentials.username,
+                    &hash_password(&credentials.password)?,
+                )
+                .await?
+        }
+    };
+    if user.role != Role::Admin {
+        bank.users.set_role(user.id, Role::Admin).await?;
+        tracing::info!("Made {} an admin", user.username);
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    request_body(
+        content = Credentials,
+        description = "Name and password of the new user"
+    ),
+    responses(
+        (status = 201, description = "Registered user", body = User),
+        (status = 400, description = "Invalid username or password", body = QuestionBankError),
+        (status = 409, description = "Username taken", body = QuestionBankError),
+    )
+)]
+pub async fn register(
+    State(users): State<Arc<RwLock<QuestionBank>>>,
+    Json(credentials): Json<Credentials>,
+) -> Response {
+    tracing::info!("register {:?}", credentials);
+    if let Err(e) = validate(&credentials) {
+        return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e));
+    }
+    let password_hash = match hash_password(&credentials.password) {
+        Ok(hash) => hash,
+        Err(e) => return QuestionBankError::response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let write_lock = users.write().await;
+    match write_lock
+        .users
+        .create(&credentials.username, &password_hash)
+        .await
+    {
+        Ok(user) => (StatusCode::CREATED, Json(user)).into_response(),
+        Err(e) => {
+            let status = match e.downcast_ref::<QuestionBankErr>() {
+                Some(QuestionBankErr::AlreadyExists(_)) => StatusCode::CONFLICT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            QuestionBankError::response(status, e)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    request_body(
+        content = Credentials,
+        description = "Name and password of the user"
+    ),
+    responses(
+        (status = 200, description = "Logged in, the session is in the `session` cookie",
+            body = User),
+        (status = 401, description = "Wrong username or password", body = QuestionBankError),
+    )
+)]
+pub async fn login(
+    State(users): State<Arc<RwLock<QuestionBank>>>,
+    jar: CookieJar,
+    Json(credentials): Json<Credentials>,
+) -> Response {
+    tracing::info!("login {:?}", credentials);
+    match start_session(&users, &credentials).await {
+        Ok((user, cookie)) => (jar.add(cookie), Json(user)).into_response(),
+        Err((status, e)) => QuestionBankError::response(status, e),
+    }
+}
+
+/// Checks a user's credentials and starts a session for them.
+///
+/// The password is checked on the blocking pool without holding the lock, since argon2
+/// is slow on purpose; only storing the session takes the write lock.
+///
+/// # Returns
+///
+/// The user and their session cookie, or the status and error to respond with if the
+/// credentials are wrong.
+pub async fn start_session(
+    bank: &RwLock<QuestionBank>,
+    credentials: &Credentials,
+) -> Result<(User, Cookie<'static>), (StatusCode, Box<dyn std::error::Error>)> {
+    let invalid = || {
+        (
+            StatusCode::UNAUTHORIZED,
+            Box::new(QuestionBankErr::InvalidCredentials) as Box<dyn std::error::Error>,
+        )
+    };
+    let (user, hash) = match bank
+        .read()
+        .await
+        .users
+        .get_credentials(&credentials.username)
+        .await
+    {
+        Ok(Some(found)) => found,
+        Ok(None) => return Err(invalid()),
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+    };
+    let password = credentials.password.clone();
+    match tokio::task::spawn_blocking(move || verify_password(&password, &hash)).await {
+        Ok(true) => {}
+        Ok(false) => return Err(invalid()),
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Box::new(e))),
+    }
+
+    let (token, token_hash) = new_session_token();
+    let expires_at = time::OffsetDateTime::now_utc() + SESSION_TTL;
+    match bank
+        .write()
+        .await
+        .users
+        .create_session(user.id, &token_hash, expires_at)
+        .await
+    {
+        Ok(()) => Ok((user, session_cookie(token))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    responses(
+        (status = 200, description = "Logged out and cleared the session cookie", body = ()),
+    )
+)]
+pub async fn logout(State(users): State<Arc<RwLock<QuestionBank>>>, jar: CookieJar) -> Response {
+    let write_lock = users.write().await;
+    match end_session(&write_lock, jar).await {
+        Ok(jar) => (jar, StatusCode::OK).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+/// Ends the session in the `session` cookie, if any.
+///
+/// # Returns
+///
+/// The cookies with the session cookie removed.
+pub async fn end_session(
+    bank: &QuestionBank,
+    jar: CookieJar,
+) -> Result<CookieJar, Box<dyn std::error::Error>> {
+    if let Some(token) = jar.get(SESSION_COOKIE) {
+        bank.users
+            .delete_session(&hash_token(token.value()))
+            .await?;
+    }
+
+    let removal = Cookie::build(SESSION_COOKIE).path("/");
+    Ok(jar.remove(removal))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/me",
+    security(("session" = []), ("token" = ["read"])),
+    responses(
+        (status = 200, description = "The logged in user", body = User),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+    )
+)]
+pub async fn me(CurrentUser(user, _): CurrentUser) -> Response {
+    Json(user).into_response()
+}
@@ -0,0 +1,439 @@
+use crate::{
+    auth::Member,
+    controllers::lib::*,
+    entities::bulk::{BulkAnswer, BulkQuestion, BulkSummary},
+    models::errors::*,
+    pagination::{AnswerSort, Cursor, PageQuery, Position, QuestionFilter, MAX_LIMIT},
+};
+use axum::{
+    body::Body,
+    http::{header, HeaderValue},
+};
+use futures_util::{stream, StreamExt};
+use std::io;
+
+/// Questions written per transaction
+pub const BATCH_SIZE: usize = 100;
+
+/// Largest JSON array, or NDJSON line, an import reads. Bigger imports are sent as NDJSON,
+/// which is written batch by batch as it arrives.
+pub const MAX_IMPORT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Writes imported questions in batches, keeping count of what was written.
+struct Importer<'a> {
+    bank: &'a Arc<RwLock<QuestionBank>>,
+    author_id: i32,
+    batch: Vec<BulkQuestion>,
+    summary: BulkSummary,
+}
+
+impl Importer<'_> {
+    /// The 400 response for an item that can't be imported, noting what was imported before.
+    fn reject(&self, item: &str, error: impl std::fmt::Display) -> Response {
+        let imported = match self.summary.questions {
+            0 => String::new(),
+            1 => ", the question before it was imported".to_string(),
+            n => format!(", the {n} questions before it were imported"),
+        };
+        QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(format!(
+                "{item}: {error}{imported}"
+            ))),
+        )
+    }
+
+    /// Validates a question and queues it, writing the batch once it is full.
+    async fn push(&mut self, item: &str, question: BulkQuestion) -> Result<(), Response> {
+        match question.validate() {
+            Ok(question) => self.batch.push(question),
+            Err(error) => {
+                // Everything before the invalid item is kept
+                self.flush().await?;
+                return Err(self.reject(item, error));
+            }
+        }
+        if self.batch.len() >= BATCH_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Writes the queued questions in one transaction.
+    async fn flush(&mut self) -> Result<(), Response> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let write_lock = self.bank.write().await;
+        match write_lock.bulk.insert(self.author_id, &self.batch).await {
+            Ok(question_ids) => {
+                self.summary.questions += question_ids.len();
+                self.summary.answers += self
+                    .batch
+                    .iter()
+                    .map(|question| question.answers.len())
+                    .sum::<usize>();
+                self.summary.batches += 1;
+                self.batch.clear();
+                Ok(())
+            }
+            Err(e) => Err(QuestionBankError::response(StatusCode::BAD_REQUEST, e)),
+        }
+    }
+
+    /// Reads a line of NDJSON, skipping blank ones.
+    async fn line(&mut self, number: usize, line: &[u8]) -> Result<(), Response> {
+        if line.trim_ascii().is_empty() {
+            return Ok(());
+        }
+        let item = format!("line {number}");
+        match serde_json::from_slice::<BulkQuestion>(line) {
+            Ok(question) => self.push(&item, question).await,
+            Err(e) => {
+                self.flush().await?;
+                Err(self.reject(&item, e))
+            }
+        }
+    }
+
+    /// Reads a JSON array, checking every question before writing any.
+    async fn array(&mut self, body: &[u8]) -> Result<(), Response> {
+        let questions = serde_json::from_slice::<Vec<BulkQuestion>>(body)
+            .map_err(|e| self.reject("the array", e))?;
+        let mut valid = Vec::with_capacity(questions.len());
+        for (index, question) in questions.into_iter().enumerate() {
+            valid.push(
+                question
+                    .validate()
+                    .map_err(|error| self.reject(&format!("item {}", index + 1), error))?,
+            );
+        }
+        for question in valid {
+            self.batch.push(question);
+            if self.batch.len() >= BATCH_SIZE {
+                self.flush().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether an import body is a JSON array rather than NDJSON, known from its first non-blank
+/// byte. `None` while the body read so far is blank.
+fn is_array(body: &[u8]) -> Option<bool> {
+    body.iter()
+        .find(|byte| !byte.is_ascii_whitespace())
+        .map(|byte| *byte == b'[')
+}
+
+/// The body of an import as it arrives, split into NDJSON lines. Each byte is searched for a
+/// newline once, however the body is chunked.
+#[derive(Debug, Default)]
+struct Lines {
+    buffer: Vec<u8>,
+    /// Where the line being read starts in `buffer`
+    start: usize,
+    /// How far `buffer` was searched for the end of that line
+    scanned: usize,
+}
+
+impl Lines {
+    /// Appends a chunk of the body, dropping the lines already read.
+    fn push(&mut self, chunk: &[u8]) {
+        self.buffer.drain(..self.start);
+        self.scanned -= self.start;
+        self.start = 0;
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// The next complete line, with its newline.
+    fn next_line(&mut self) -> Option<&[u8]> {
+        match self.buffer[self.scanned..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+        {
+            Some(newline) => {
+                let start = self.start;
+                self.start = self.scanned + newline + 1;
+                self.scanned = self.start;
+                Some(&self.buffer[start..self.start])
+            }
+            None => {
+                self.scanned = self.buffer.len();
+                None
+            }
+        }
+    }
+
+    /// What is left after the complete lines: the end of the body once it is all read, or
+    /// all of it for a JSON array.
+    fn rest(&self) -> &[u8] {
+        &self.buffer[self.start..]
+    }
+}
+
+/// The 413 response for an import too large to read at once.
+fn too_large() -> Response {
+    QuestionBankError::response(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Box::new(QuestionBankErr::InvalidInput(format!(
+            "JSON arrays and NDJSON lines are limited to {MAX_IMPORT_BYTES} bytes, send larger \
+            imports as NDJSON"
+        ))),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/import",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    request_body(
+        content = [BulkQuestion],
+        description = "A JSON array of questions with their answers, or the same questions as \
+            NDJSON, one per line. An array is checked whole before anything is written; NDJSON \
+            is written as it arrives, up to the first invalid line"
+    ),
+    responses(
+        (status = 201, description = "What was imported", body = BulkSummary),
+        (status = 400, description = "Malformed or invalid question, naming the item or line \
+            and how many questions were imported before it", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't post", body = QuestionBankError),
+        (status = 413, description = "JSON array or NDJSON line too large",
+            body = QuestionBankError),
+    )
+)]
+pub async fn import_questions(
+    State(questions): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    body: Body,
+) -> Response {
+    tracing::info!("import questions");
+    let mut importer = Importer {
+        bank: &questions,
+        author_id: user.0.id,
+        batch: Vec::new(),
+        summary: BulkSummary::default(),
+    };
+    let mut chunks = body.into_data_stream();
+    let mut body = Lines::default();
+    let mut array: Option<bool> = None;
+    let mut lines = 0;
+    while let Some(chunk) = chunks.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+        };
+        body.push(&chunk);
+        if array.is_none() {
+            array = is_array(body.rest());
+        }
+        if array == Some(false) {
+            while let Some(line) = body.next_line() {
+                lines += 1;
+                if let Err(response) = importer.line(lines, line).await {
+                    return response;
+                }
+            }
+        }
+        if body.rest().len() > MAX_IMPORT_BYTES {
+            return match importer.flush().await {
+                Ok(()) => too_large(),
+                Err(response) => response,
+            };
+        }
+    }
+
+    let read = match array {
+        Some(true) => importer.array(body.rest()).await,
+        Some(false) => importer.line(lines + 1, body.rest()).await,
+        None => Err(QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(
+                "there are no questions to import".to_string(),
+            )),
+        )),
+    };
+    if let Err(response) = read {
+        return response;
+    }
+    match importer.flush().await {
+        Ok(()) => (StatusCode::CREATED, Json(importer.summary)).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Writes a page of the filtered questions as NDJSON, each with its answers.
+///
+/// # Returns
+///
+/// The lines and the cursor of the next page, if there is one.
+async fn export_page(
+    bank: &RwLock<QuestionBank>,
+    filter: &QuestionFilter,
+    page: &PageQuery,
+) -> Result<(Vec<u8>, Option<Cursor>), io::Error> {
+    let read_lock = bank.read().await;
+    let res = read_lock
+        .questions
+        .paginated_get(page, filter)
+        .await
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+    let mut body = Vec::new();
+    for question in res.items {
+        let Some(question_id) = question.id else {
+            continue;
+        };
+        let mut answers = Vec::new();
+        let mut answer_page = PageQuery {
+            position: Position::Offset(0),
+            limit: MAX_LIMIT as i64,
+        };
+        loop {
+            let res = read_lock
+                .answers
+                .get_all(question_id, &answer_page, AnswerSort::default())
+                .await
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            answers.extend(res.items.into_iter().map(|answer| BulkAnswer {
+                answer: answer.answer,
+                accepted: answer.accepted,
+                key: answer.key,
+            }));
+            match res.next {
+                Some(cursor) => answer_page.position = Position::After(cursor),
+                None => break,
+            }
+        }
+        serde_json::to_writer(
+            &mut body,
+            &BulkQuestion {
+                id: Some(question_id),
+                title: question.title,
+                content: question.content,
+                tags: question.tags.unwrap_or_default(),
+                kind: question.kind,
+                answers,
+            },
+        )?;
+        body.push(b'\n');
+    }
+
+    Ok((body, res.next))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/export",
+    params(QuestionFilter),
+    responses(
+        (status = 200, description = "The filtered questions with their answers as NDJSON, one \
+            question per line, streamed page by page. The lines can be imported again",
+            body = BulkQuestion, content_type = "application/x-ndjson"),
+        (status = 400, description = "Invalid filter", body = QuestionBankError),
+    )
+)]
+pub async fn export_questions(
+    State(questions): State<Arc<RwLock<QuestionBank>>>,
+    Query(filter): Query<QuestionFilter>,
+) -> Response {
+    tracing::info!("export questions");
+    let filter = match filter.canonical(&*questions.read().await.tags).await {
+        Ok(filter) => filter,
+        Err((status, e)) => return QuestionBankError::response(status, e),
+    };
+
+    let first = PageQuery {
+        position: Position::Offset(0),
+        limit: MAX_LIMIT as i64,
+    };
+    let pages = stream::unfold(Some(first), move |page| {
+        let questions = questions.clone();
+        let filter = filter.clone();
+        async move {
+            let page = page?;
+            Some(match export_page(&questions, &filter, &page).await {
+                Ok((body, next)) => (
+                    Ok(body),
+                    next.map(|cursor| PageQuery {
+                        position: Position::After(cursor),
+                        limit: page.limit,
+                    }),
+                ),
+                Err(e) => (Err(e), None),
+            })
+        }
+    });
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-ndjson"),
+        )],
+        Body::from_stream(pages),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits `body` sent in `chunks` of the given sizes into its lines and what is left.
+    fn split(body: &str, chunks: &[usize]) -> (Vec<String>, String) {
+        let mut lines = Lines::default();
+        let mut read = Vec::new();
+        let mut rest = body.as_bytes();
+        for &size in chunks {
+            let (chunk, tail) = rest.split_at(size.min(rest.len()));
+            rest = tail;
+            lines.push(chunk);
+            while let Some(line) = lines.next_line() {
+                read.push(String::from_utf8(line.to_vec()).unwrap());
+            }
+        }
+        assert!(rest.is_empty(), "the chunks must cover the body");
+        (read, String::from_utf8(lines.rest().to_vec()).unwrap())
+    }
+
+    #[test]
+    fn detects_arrays_from_the_first_non_blank_byte() {
+        assert_eq!(is_array(b""), None);
+        assert_eq!(is_array(b" \r\n\t"), None);
+        assert_eq!(is_array(b"\n  [{\"title\": \"t\"}]"), Some(true));
+        assert_eq!(is_array(b"{\"title\": \"[\"}"), Some(false));
+        assert_eq!(is_array(b"  nonsense"), Some(false));
+    }
+
+    #[test]
+    fn splits_lines_however_the_body_is_chunked() {
+        let body = "{\"a\":1}\n\n{\"b\":2}\r\n{\"c\":3}";
+        let expected = (
+            vec![
+                "{\"a\":1}\n".to_string(),
+                "\n".to_string(),
+                "{\"b\":2}\r\n".to_string(),
+            ],
+            "{\"c\":3}".to_string(),
+        );
+        assert_eq!(split(body, &[body.len()]), expected);
+        assert_eq!(split(body, &[1; 32]), expected);
+        assert_eq!(split(body, &[3, 5, 0, 7, 20]), expected);
+        assert_eq!(split(body, &[8, 1, 9, 7]), expected);
+    }
+
+    #[test]
+    fn keeps_the_whole_body_without_newlines() {
+        let body = "[{\"title\": \"t\", \"content\": \"c\"}]";
+        assert_eq!(split(body, &[4, 4, 100]), (vec![], body.to_string()));
+        assert_eq!(split("", &[]), (vec![], String::new()));
+    }
+
+    #[test]
+    fn a_trailing_newline_leaves_nothing() {
+        let (lines, rest) = split("{}\n{}\n", &[2, 2, 2]);
+        assert_eq!(lines, ["{}\n", "{}\n"]);
+        assert!(rest.is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{auth::Moderator, controllers::lib::*, entities::closure::*, models::errors::*};
+use std::error::Error;
+
+/// Longest accepted close or reopen note, after trimming
+const MAX_NOTE_LENGTH: usize = 500;
+
+/// The status to answer a failed close or reopen with.
+pub fn error_status(e: &(dyn Error + 'static)) -> StatusCode {
+    match e.downcast_ref::<QuestionBankErr>() {
+        Some(QuestionBankErr::DoesNotExist(_)) => StatusCode::NOT_FOUND,
+        Some(QuestionBankErr::Closed(_) | QuestionBankErr::NotClosed(_)) => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Trims a note, dropping it if it's empty.
+///
+/// # Returns
+///
+/// The note, or a `QuestionBankErr` error if it is too long.
+fn clean_note(note: Option<String>) -> Result<Option<String>, QuestionBankErr> {
+    let note = note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH)
+    {
+        return Err(QuestionBankErr::InvalidInput(format!(
+            "a note must be at most {MAX_NOTE_LENGTH} characters"
+        )));
+    }
+
+    Ok(note)
+}
+
+/// Checks that a question takes answers.
+///
+/// # Returns
+///
+/// A `QuestionBankErr` error if the question does not exist or is closed, see
+/// [`error_status`].
+pub async fn check_open(bank: &QuestionBank, question_id: i32) -> Result<(), Box<dyn Error>> {
+    let question = bank.questions.get(question_id).await?;
+    if question.iter().any(|question| question.closed.is_some()) {
+        return Err(Box::new(QuestionBankErr::Closed(format!(
+            "Question {question_id}"
+        ))));
+    }
+
+    Ok(())
+}
+
+/// Checks a close request against the question it closes.
+///
+/// # Returns
+///
+/// The request with its note cleaned up, or a `QuestionBankErr` error if a duplicate doesn't
+/// name an existing other question or another reason names one.
+async fn validate(
+    bank: &QuestionBank,
+    question_id: i32,
+    mut request: CloseRequest,
+) -> Result<CloseRequest, QuestionBankErr> {
+    request.note = clean_note(request.note)?;
+    match (request.reason, request.duplicate_of) {
+        (CloseReason::Duplicate, None) => Err(QuestionBankErr::InvalidInput(
+            "a duplicate needs duplicate_of".to_string(),
+        )),
+        (CloseReason::Duplicate, Some(original)) if original == question_id => Err(
+            QuestionBankErr::InvalidInput("a question can't duplicate itself".to_string()),
+        ),
+        (CloseReason::Duplicate, Some(original)) => match bank.questions.get(original).await {
+            Ok(_) => Ok(request),
+            Err(_) => Err(QuestionBankErr::InvalidInput(format!(
+                "question {original} doesn't exist"
+            ))),
+        },
+        (_, Some(_)) => Err(QuestionBankErr::InvalidInput(
+            "only duplicates can have duplicate_of".to_string(),
+        )),
+        (_, None) => Ok(request),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/{id}/close",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    request_body(
+        content = CloseRequest,
+        description = "Why the question is closed and, for duplicates, the original question"
+    ),
+    responses(
+        (status = 200, description = "Closed the question, which no longer takes answers",
+            body = Question),
+        (status = 400, description = "Invalid reason, note or duplicate_of",
+            body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+        (status = 409, description = "Question already closed", body = QuestionBankError),
+    )
+)]
+pub async fn close_question(
+    State(closures): State<Arc<RwLock<QuestionBank>>>,
+    moderator: Moderator,
+    Path(question_id): Path<i32>,
+    Json(request): Json<CloseRequest>,
+) -> Response {
+    tracing::info!("close question");
+    let write_lock = closures.write().await;
+    let request = match validate(&write_lock, question_id, request).await {
+        Ok(request) => request,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+    match write_lock
+        .closures
+        .close(question_id, &request, Some(moderator.0.id))
+        .await
+    {
+        Ok(question) => Json(question).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/{id}/reopen",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    request_body(
+        content = Option<ReopenRequest>,
+        description = "Why the question is reopened"
+    ),
+    responses(
+        (status = 200, description = "Reopened the question", body = Question),
+        (status = 400, description = "Note too long", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+        (status = 409, description = "Question isn't closed", body = QuestionBankError),
+    )
+)]
+pub async fn reopen_question(
+    State(closures): State<Arc<RwLock<QuestionBank>>>,
+    moderator: Moderator,
+    Path(question_id): Path<i32>,
+    request: Option<Json<ReopenRequest>>,
+) -> Response {
+    tracing::info!("reopen question");
+    let Json(request) = request.unwrap_or_default();
+    let note = match clean_note(request.note) {
+        Ok(note) => note,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+    let write_lock = closures.write().await;
+    match write_lock
+        .closures
+        .reopen(question_id, note, Some(moderator.0.id))
+        .await
+    {
+        Ok(question) => Json(question).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/questions/{id}/closures",
+    responses(
+        (status = 200, description = "Every close and reopen of the question, oldest first",
+            body = [ClosureEvent]),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_question_closures(
+    State(closures): State<Arc<RwLock<QuestionBank>>>,
+    Path(question_id): Path<i32>,
+) -> Response {
+    tracing::info!("get question closures");
+    let read_lock = closures.read().await;
+    if let Err(e) = read_lock.questions.get(question_id).await {
+        return QuestionBankError::response(StatusCode::NOT_FOUND, e);
+    }
+    match read_lock.closures.get_history(question_id).await {
+        Ok(history) => Json(history).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
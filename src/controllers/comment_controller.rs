@@ -0,0 +1,438 @@
+use crate::{
+    auth::{CurrentUser, Member},
+    controllers::lib::*,
+    entities::comment::*,
+    models::errors::*,
+};
+use std::error::Error;
+
+/// Shortest accepted comment, after trimming
+pub const MIN_BODY_LENGTH: usize = 2;
+
+/// Longest accepted comment, after trimming
+pub const MAX_BODY_LENGTH: usize = 600;
+
+/// Checks that a comment is neither too short nor too long.
+pub fn validate(body: &str) -> Result<(), QuestionBankErr> {
+    let length = body.trim().chars().count();
+    if !(MIN_BODY_LENGTH..=MAX_BODY_LENGTH).contains(&length) {
+        return Err(QuestionBankErr::InvalidInput(format!(
+            "a comment must be {MIN_BODY_LENGTH} to {MAX_BODY_LENGTH} characters"
+        )));
+    }
+
+    Ok(())
+}
+
+/// The status to answer a failed comment operation with.
+fn error_status(e: &(dyn Error + 'static)) -> StatusCode {
+    match e.downcast_ref::<QuestionBankErr>() {
+        Some(
+            QuestionBankErr::DoesNotExist(_)
+            | QuestionBankErr::AnswerDoesNotExist(_)
+            | QuestionBankErr::CommentDoesNotExist(_),
+        ) => StatusCode::NOT_FOUND,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Checks that the commented question or answer exists and isn't in the trash.
+///
+/// # Returns
+///
+/// The 404 response to send if it doesn't.
+async fn check_target(
+    bank: &QuestionBank,
+    target: CommentTarget,
+    target_id: i32,
+) -> Result<(), Response> {
+    let result = match target {
+        CommentTarget::Question => bank.questions.get(target_id).await.map(|_| ()),
+        CommentTarget::Answer => bank.answers.get(target_id).await.map(|_| ()),
+    };
+    result.map_err(|e| QuestionBankError::response(StatusCode::NOT_FOUND, e))
+}
+
+/// Lists the comments on a question or answer.
+async fn list(bank: &QuestionBank, target: CommentTarget, target_id: i32) -> Response {
+    if let Err(response) = check_target(bank, target, target_id).await {
+        return response;
+    }
+    match bank.comments.get_all(target, target_id).await {
+        Ok(comments) => Json(comments).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+/// Returns one comment on a question or answer.
+async fn show(bank: &QuestionBank, target: CommentTarget, target_id: i32, index: i32) -> Response {
+    if let Err(response) = check_target(bank, target, target_id).await {
+        return response;
+    }
+    match bank.comments.get(target, target_id, index).await {
+        Ok(comment) => Json(comment).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+/// Adds a comment by `user` to a question or answer.
+async fn add(
+    bank: &QuestionBank,
+    user: &CurrentUser,
+    target: CommentTarget,
+    target_id: i32,
+    comment: CommentRequest,
+) -> Response {
+    if let Err(e) = validate(&comment.body) {
+        return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e));
+    }
+    if let Err(response) = check_target(bank, target, target_id).await {
+        return response;
+    }
+    match bank
+        .comments
+        .add(target, target_id, Some(user.0.id), &comment)
+        .await
+    {
+        Ok(comment) => (StatusCode::CREATED, Json(comment)).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+/// Checks that the user may edit or delete a comment, see [`CurrentUser::can_edit`].
+///
+/// # Returns
+///
+/// The error response to send if the comment does not exist or the user is not allowed.
+async fn check_author(
+    bank: &QuestionBank,
+    user: &CurrentUser,
+    target: CommentTarget,
+    target_id: i32,
+    index: i32,
+) -> Result<(), Response> {
+    check_target(bank, target, target_id).await?;
+    let comment = match bank.comments.get(target, target_id, index).await {
+        Ok(comment) => comment,
+        Err(e) => return Err(QuestionBankError::response(StatusCode::NOT_FOUND, e)),
+    };
+
+    if user.can_edit(comment.author_id) {
+        Ok(())
+    } else {
+        Err(QuestionBankError::response(
+            StatusCode::FORBIDDEN,
+            Box::new(QuestionBankErr::Forbidden(format!(
+                "only the author of the comment or a moderator can change comment {index}"
+            ))),
+        ))
+    }
+}
+
+/// Changes the text of a comment on a question or answer.
+async fn update(
+    bank: &QuestionBank,
+    user: &CurrentUser,
+    target: CommentTarget,
+    target_id: i32,
+    index: i32,
+    comment: CommentRequest,
+) -> Response {
+    if let Err(e) = validate(&comment.body) {
+        return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e));
+    }
+    if let Err(response) = check_author(bank, user, target, target_id, index).await {
+        return response;
+    }
+    match bank
+        .comments
+        .update(target, target_id, index, &comment.body)
+        .await
+    {
+        Ok(comment) => Json(comment).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+/// Deletes a comment on a question or answer along with its replies.
+async fn remove(
+    bank: &QuestionBank,
+    user: &CurrentUser,
+    target: CommentTarget,
+    target_id: i32,
+    index: i32,
+) -> Response {
+    if let Err(response) = check_author(bank, user, target, target_id, index).await {
+        return response;
+    }
+    match bank.comments.delete(target, target_id, index).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/questions/{id}/comments",
+    responses(
+        (status = 200, description = "Every comment on the question, oldest first",
+            body = [Comment]),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_question_comments(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    Path(question_id): Path<i32>,
+) -> Response {
+    tracing::info!("get question comments");
+    let read_lock = comments.read().await;
+    list(&read_lock, CommentTarget::Question, question_id).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/{id}/comments",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    request_body(
+        content = CommentRequest,
+        description = "Comment to add, optionally replying to another comment on the question"
+    ),
+    responses(
+        (status = 201, description = "Added comment", body = Comment),
+        (status = 400, description = "Comment too short or too long, or replying to a comment \
+            on something else", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't comment", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+    )
+)]
+pub async fn post_question_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(question_id): Path<i32>,
+    Json(comment): Json<CommentRequest>,
+) -> Response {
+    tracing::info!("post question comment");
+    let write_lock = comments.write().await;
+    add(
+        &write_lock,
+        &user,
+        CommentTarget::Question,
+        question_id,
+        comment,
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/questions/{id}/comments/{comment_id}",
+    responses(
+        (status = 200, description = "Return specified comment", body = Comment),
+        (status = 404, description = "Question or comment not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_question_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    Path((question_id, comment_id)): Path<(i32, i32)>,
+) -> Response {
+    tracing::info!("get question comment");
+    let read_lock = comments.read().await;
+    show(&read_lock, CommentTarget::Question, question_id, comment_id).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/questions/{id}/comments/{comment_id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    request_body(
+        content = CommentRequest,
+        description = "The comment's new text"
+    ),
+    responses(
+        (status = 200, description = "Edited comment", body = Comment),
+        (status = 400, description = "Comment too short or too long", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Question or comment not found", body = QuestionBankError),
+    )
+)]
+pub async fn update_question_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path((question_id, comment_id)): Path<(i32, i32)>,
+    Json(comment): Json<CommentRequest>,
+) -> Response {
+    tracing::info!("update question comment");
+    let write_lock = comments.write().await;
+    update(
+        &write_lock,
+        &user,
+        CommentTarget::Question,
+        question_id,
+        comment_id,
+        comment,
+    )
+    .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/questions/{id}/comments/{comment_id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Deleted the comment and the replies to it", body = ()),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Question or comment not found", body = QuestionBankError),
+    )
+)]
+pub async fn delete_question_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path((question_id, comment_id)): Path<(i32, i32)>,
+) -> Response {
+    tracing::info!("delete question comment");
+    let write_lock = comments.write().await;
+    remove(
+        &write_lock,
+        &user,
+        CommentTarget::Question,
+        question_id,
+        comment_id,
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/answers/{answer_id}/comments",
+    responses(
+        (status = 200, description = "Every comment on the answer, oldest first",
+            body = [Comment]),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_answer_comments(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    tracing::info!("get answer comments");
+    let read_lock = comments.read().await;
+    list(&read_lock, CommentTarget::Answer, answer_id).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/answers/{answer_id}/comments",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    request_body(
+        content = CommentRequest,
+        description = "Comment to add, optionally replying to another comment on the answer"
+    ),
+    responses(
+        (status = 201, description = "Added comment", body = Comment),
+        (status = 400, description = "Comment too short or too long, or replying to a comment \
+            on something else", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't comment", body = QuestionBankError),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn post_answer_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(answer_id): Path<i32>,
+    Json(comment): Json<CommentRequest>,
+) -> Response {
+    tracing::info!("post answer comment");
+    let write_lock = comments.write().await;
+    add(
+        &write_lock,
+        &user,
+        CommentTarget::Answer,
+        answer_id,
+        comment,
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/answers/{answer_id}/comments/{comment_id}",
+    responses(
+        (status = 200, description = "Return specified comment", body = Comment),
+        (status = 404, description = "Answer or comment not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_answer_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    Path((answer_id, comment_id)): Path<(i32, i32)>,
+) -> Response {
+    tracing::info!("get answer comment");
+    let read_lock = comments.read().await;
+    show(&read_lock, CommentTarget::Answer, answer_id, comment_id).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/answers/{answer_id}/comments/{comment_id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    request_body(
+        content = CommentRequest,
+        description = "The comment's new text"
+    ),
+    responses(
+        (status = 200, description = "Edited comment", body = Comment),
+        (status = 400, description = "Comment too short or too long", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Answer or comment not found", body = QuestionBankError),
+    )
+)]
+pub async fn update_answer_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path((answer_id, comment_id)): Path<(i32, i32)>,
+    Json(comment): Json<CommentRequest>,
+) -> Response {
+    tracing::info!("update answer comment");
+    let write_lock = comments.write().await;
+    update(
+        &write_lock,
+        &user,
+        CommentTarget::Answer,
+        answer_id,
+        comment_id,
+        comment,
+    )
+    .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/answers/{answer_id}/comments/{comment_id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Deleted the comment and the replies to it", body = ()),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Answer or comment not found", body = QuestionBankError),
+    )
+)]
+pub async fn delete_answer_comment(
+    State(comments): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path((answer_id, comment_id)): Path<(i32, i32)>,
+) -> Response {
+    tracing::info!("delete answer comment");
+    let write_lock = comments.write().await;
+    remove(
+        &write_lock,
+        &user,
+        CommentTarget::Answer,
+        answer_id,
+        comment_id,
+    )
+    .await
+}
@@ -0,0 +1,267 @@
+use crate::{
+    auth::Member,
+    controllers::{lib::*, question_controller::STRICT_SIMILARITY, search_controller},
+    deck,
+    entities::{
+        bulk::{BulkAnswer, BulkQuestion},
+        deck::{
+            DeckCard, DeckFormat, ExportQuery, ImportQuery, ImportReport, ImportRow, RowStatus,
+        },
+    },
+    models::errors::*,
+    pagination::{Cursor, PageQuery, Position, QuestionFilter, MAX_LIMIT},
+};
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue},
+};
+use futures_util::stream;
+use std::{collections::HashMap, error::Error, io};
+
+/// Adds a card as a question by `author_id`, along with its answer if it has one, both or
+/// neither.
+///
+/// # Returns
+///
+/// The ID of the new question.
+async fn add_card(
+    bank: &QuestionBank,
+    author_id: i32,
+    card: DeckCard,
+) -> Result<i32, Box<dyn Error>> {
+    let question = BulkQuestion {
+        id: None,
+        title: card.title,
+        content: card.content,
+        tags: card.tags,
+        kind: None,
+        answers: card
+            .answer
+            .map(|answer| BulkAnswer {
+                answer,
+                accepted: false,
+                key: None,
+            })
+            .into_iter()
+            .collect(),
+    };
+    let question_ids = bank.bulk.insert(author_id, &[question]).await?;
+
+    Ok(question_ids.first().copied().unwrap_or_default())
+}
+
+/// Writes a page of the filtered questions as deck rows, each with its accepted answer or
+/// else its best scored one, starting with the header on the first page.
+///
+/// # Returns
+///
+/// The rows and the cursor of the next page, if there is one.
+async fn export_page(
+    bank: &RwLock<QuestionBank>,
+    format: DeckFormat,
+    filter: &QuestionFilter,
+    page: &PageQuery,
+) -> Result<(Vec<u8>, Option<Cursor>), io::Error> {
+    let read_lock = bank.read().await;
+    let res = read_lock
+        .questions
+        .paginated_get(page, filter)
+        .await
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let question_ids: Vec<i32> = res
+        .items
+        .iter()
+        .filter_map(|question| question.id)
+        .collect();
+    let mut answers = read_lock
+        .quizzes
+        .correct_answers(&question_ids)
+        .await
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    drop(read_lock);
+
+    let cards: Vec<DeckCard> = res
+        .items
+        .into_iter()
+        .map(|question| DeckCard {
+            answer: question
+                .id
+                .and_then(|id| answers.remove(&id))
+                .map(|answer| answer.answer),
+            title: question.title,
+            content: question.content,
+            tags: question.tags.unwrap_or_default(),
+        })
+        .collect();
+    let first = matches!(page.position, Position::Offset(0));
+    let body = deck::write(format, &cards, first).map_err(|e| io::Error::other(e.to_string()))?;
+
+    Ok((body, res.next))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/deck/import",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    params(ImportQuery),
+    request_body(
+        content = String,
+        description = "The deck file, in the format named by `format`",
+        content_type = "text/plain"
+    ),
+    responses(
+        (status = 200, description = "What became of each row: imported, or ready to in a dry \
+            run, skipped as a duplicate of an existing title or an earlier row, or skipped as \
+            invalid", body = ImportReport),
+        (status = 400, description = "Unreadable file or missing title column",
+            body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't post", body = QuestionBankError),
+    )
+)]
+pub async fn import_deck(
+    State(questions): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Response {
+    tracing::info!("import deck");
+    let rows = match deck::parse(query.format, &body) {
+        Ok(rows) => rows,
+        Err(message) => {
+            return QuestionBankError::response(
+                StatusCode::BAD_REQUEST,
+                Box::new(QuestionBankErr::InvalidInput(message)),
+            )
+        }
+    };
+
+    let mut report = ImportReport {
+        dry_run: query.dry_run,
+        imported: 0,
+        duplicates: 0,
+        invalid: 0,
+        rows: Vec::with_capacity(rows.len()),
+    };
+    // Lines of the rows read so far, by lowercased title
+    let mut seen: HashMap<String, u64> = HashMap::new();
+    for row in rows {
+        let mut outcome = ImportRow {
+            line: row.line,
+            status: RowStatus::Invalid,
+            title: None,
+            question_id: None,
+            duplicates: Vec::new(),
+            error: None,
+        };
+        let card = match row.card {
+            Ok(card) => card,
+            Err(error) => {
+                outcome.error = Some(error);
+                report.push(outcome);
+                continue;
+            }
+        };
+        outcome.title = Some(card.title.clone());
+        if let Some(line) = seen.get(&card.title.to_lowercase()) {
+            outcome.status = RowStatus::Duplicate;
+            outcome.error = Some(format!("the title repeats line {line}"));
+            report.push(outcome);
+            continue;
+        }
+        seen.insert(card.title.to_lowercase(), row.line);
+
+        // Only writing a card needs the write lock, so reads go on between rows
+        outcome.duplicates = match questions
+            .read()
+            .await
+            .search
+            .similar(&card.title, search_controller::MAX_SIMILAR_LIMIT)
+            .await
+        {
+            Ok(similar) => similar
+                .iter()
+                .filter(|question| question.similarity >= STRICT_SIMILARITY)
+                .map(|question| question.id)
+                .collect(),
+            Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+        };
+        if !outcome.duplicates.is_empty() {
+            outcome.status = RowStatus::Duplicate;
+        } else if query.dry_run {
+            outcome.status = RowStatus::Ready;
+        } else {
+            match add_card(&*questions.write().await, user.0.id, card).await {
+                Ok(question_id) => {
+                    outcome.status = RowStatus::Imported;
+                    outcome.question_id = Some(question_id);
+                }
+                Err(e) => outcome.error = Some(e.to_string()),
+            }
+        }
+        report.push(outcome);
+    }
+
+    Json(report).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/deck/export",
+    params(ExportQuery, QuestionFilter),
+    responses(
+        (status = 200, description = "The filtered questions as a deck file, each with its \
+            accepted answer or else its best scored one, streamed page by page", body = String,
+            content_type = "text/plain"),
+        (status = 400, description = "Invalid filter", body = QuestionBankError),
+    )
+)]
+pub async fn export_deck(
+    State(questions): State<Arc<RwLock<QuestionBank>>>,
+    Query(query): Query<ExportQuery>,
+    Query(filter): Query<QuestionFilter>,
+) -> Response {
+    tracing::info!("export deck");
+    let filter = match filter.canonical(&*questions.read().await.tags).await {
+        Ok(filter) => filter,
+        Err((status, e)) => return QuestionBankError::response(status, e),
+    };
+
+    let format = query.format;
+    let first = PageQuery {
+        position: Position::Offset(0),
+        limit: MAX_LIMIT as i64,
+    };
+    let pages = stream::unfold(Some(first), move |page| {
+        let questions = questions.clone();
+        let filter = filter.clone();
+        async move {
+            let page = page?;
+            Some(
+                match export_page(&questions, format, &filter, &page).await {
+                    Ok((body, next)) => (
+                        Ok(body),
+                        next.map(|cursor| PageQuery {
+                            position: Position::After(cursor),
+                            limit: page.limit,
+                        }),
+                    ),
+                    Err(e) => (Err(e), None),
+                },
+            )
+        }
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(query.format.media_type()),
+    );
+    if let Ok(value) = HeaderValue::from_str(&format!(
+        r#"attachment; filename="questions.{}""#,
+        query.format.extension()
+    )) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    (StatusCode::OK, headers, Body::from_stream(pages)).into_response()
+}
@@ -2,7 +2,7 @@ pub use crate::config::*;
 pub use askama_axum::IntoResponse;
 pub use axum::{
     debug_handler,
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, Query, State},
     http::StatusCode,
     response::Response,
     Json,
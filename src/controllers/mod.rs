@@ -1,3 +1,18 @@
 pub mod answer_controller;
+pub mod auth_controller;
+pub mod bulk_controller;
+pub mod closure_controller;
+pub mod comment_controller;
+pub mod deck_controller;
 pub mod lib;
+pub mod page_controller;
 pub mod question_controller;
+pub mod quiz_controller;
+pub mod review_controller;
+pub mod revision_controller;
+pub mod search_controller;
+pub mod tag_controller;
+pub mod token_controller;
+pub mod trash_controller;
+pub mod user_controller;
+pub mod vote_controller;
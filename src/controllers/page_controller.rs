@@ -0,0 +1,620 @@
+use crate::{
+    auth::{Access, CurrentUser},
+    controllers::{
+        answer_controller,
+        auth_controller::{end_session, start_session},
+        closure_controller, comment_controller,
+        lib::*,
+    },
+    entities::{
+        answer::Answer,
+        comment::{thread, CommentRequest, CommentTarget},
+        question::Question,
+        tag::normalize_tag,
+        user::{Credentials, Role},
+    },
+    markdown,
+    models::{answer_model, errors::*, question_model, tag_model},
+    pagination::{AnswerSort, Page, Pagination, QuestionFilter, TagFilter},
+    views::pages::*,
+};
+use axum::{
+    http::{header, Uri},
+    response::Redirect,
+    Form,
+};
+use axum_extra::extract::CookieJar;
+use std::error::Error;
+
+/// The logged in user, shown in the page header.
+fn account(user: &Option<CurrentUser>) -> Option<Account> {
+    user.as_ref().map(Account::from)
+}
+
+/// Renders the error page in place of a page that failed.
+fn error_page(status: StatusCode, user: &Option<CurrentUser>, e: Box<dyn Error>) -> Response {
+    (status, ErrorPage::new(status, account(user), e.as_ref())).into_response()
+}
+
+/// Checks that a form posted with the session cookie carries its CSRF token, see
+/// [`csrf_token`](crate::auth::csrf_token). API tokens can't be sent by another site,
+/// so their requests pass.
+///
+/// # Returns
+///
+/// The error page to send if the token is missing or wrong.
+fn check_csrf(user: &Option<CurrentUser>, csrf: &str) -> Result<(), Box<Response>> {
+    match user {
+        Some(CurrentUser(_, Access::Session { csrf: expected })) if expected != csrf => {
+            Err(Box::new(error_page(
+                StatusCode::FORBIDDEN,
+                user,
+                Box::new(QuestionBankErr::Forbidden(
+                    "the form has expired, reload the page and try again".to_string(),
+                )),
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the user may post a form, like [`member`] does, and that the form carries
+/// its CSRF token.
+fn poster<'a>(user: &'a Option<CurrentUser>, csrf: &str) -> Result<&'a CurrentUser, Box<Response>> {
+    let current = member(user)?;
+    check_csrf(user, csrf)?;
+    Ok(current)
+}
+
+/// Checks that the user may post, like the `Member` extractor does for the API.
+///
+/// # Returns
+///
+/// The user, or the response to send: the login page for anonymous users and an
+/// error page for readers.
+fn member(user: &Option<CurrentUser>) -> Result<&CurrentUser, Box<Response>> {
+    match user {
+        None => Err(Box::new(Redirect::to("/login").into_response())),
+        Some(current) if current.0.role >= Role::Member => Ok(current),
+        Some(_) => Err(Box::new(error_page(
+            StatusCode::FORBIDDEN,
+            user,
+            Box::new(QuestionBankErr::Forbidden(
+                "requires the member role".to_string(),
+            )),
+        ))),
+    }
+}
+
+/// The error page for a user who may not edit something.
+fn not_author(user: &Option<CurrentUser>, what: &str) -> Response {
+    error_page(
+        StatusCode::FORBIDDEN,
+        user,
+        Box::new(QuestionBankErr::Forbidden(format!(
+            "only the author or a moderator can edit this {what}"
+        ))),
+    )
+}
+
+/// The stylesheet of highlighted code in questions and answers.
+pub async fn highlight_css() -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/css")],
+        markdown::highlight_css(),
+    )
+        .into_response()
+}
+
+pub async fn home() -> Redirect {
+    Redirect::to("/questions")
+}
+
+/// Renders a page of the question list, see [`questions`](crate::controllers::question_controller::questions).
+///
+/// # Parameters
+///
+/// * `user`: The logged in user.
+/// * `res`: The page of questions.
+/// * `uri`: The URI of the request, used as the base of the page links.
+pub fn questions_view(user: &Option<CurrentUser>, res: Page<Question>, uri: &Uri) -> Response {
+    QuestionsPage {
+        user: account(user),
+        title: "Questions".to_string(),
+        next: res.next_link(uri),
+        prev: res.prev_link(uri),
+        total: res.total,
+        questions: res.items,
+    }
+    .into_response()
+}
+
+/// Renders a question with a page of its answers and their comments, see [`get_question`](crate::controllers::question_controller::get_question).
+///
+/// # Parameters
+///
+/// * `bank`: The question bank, to fetch the answers and comments from.
+/// * `user`: The logged in user.
+/// * `question`: The question to show.
+/// * `params`: Which page of answers to show, accepted answers first.
+/// * `uri`: The URI of the request, used as the base of the answer page links.
+pub async fn question_view(
+    bank: &QuestionBank,
+    user: &Option<CurrentUser>,
+    question: Question,
+    params: &Pagination,
+    uri: &Uri,
+) -> Response {
+    let question_id = question.id.unwrap_or_default();
+    let page = match params.validate(&answer_model::order(AnswerSort::Accepted)) {
+        Ok(page) => page,
+        Err(e) => return error_page(StatusCode::BAD_REQUEST, user, Box::new(e)),
+    };
+    let res = match bank
+        .answers
+        .get_all(question_id, &page, AnswerSort::Accepted)
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, user, e),
+    };
+    let (next, prev) = (res.next_link(uri), res.prev_link(uri));
+    let answers = res.items;
+
+    let comments = match bank
+        .comments
+        .get_all(CommentTarget::Question, question_id)
+        .await
+    {
+        Ok(comments) => thread(comments),
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, user, e),
+    };
+
+    let answer_ids: Vec<i32> = answers.iter().filter_map(|answer| answer.id).collect();
+    let mut answer_comments = match bank.comments.get_for_answers(&answer_ids).await {
+        Ok(comments) => comments,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, user, e),
+    };
+
+    let can_edit = |author_id| user.as_ref().is_some_and(|user| user.can_edit(author_id));
+    let mut answer_views = Vec::new();
+    for answer in answers {
+        let comments = answer
+            .id
+            .and_then(|answer_id| answer_comments.remove(&answer_id))
+            .unwrap_or_default();
+        let editable = can_edit(answer.author_id);
+        answer_views.push((answer, editable, thread(comments)));
+    }
+
+    QuestionPage {
+        user: account(user),
+        title: question.title.clone(),
+        question_id,
+        can_edit: can_edit(question.author_id),
+        can_answer: member(user).is_ok(),
+        comments,
+        answers: answer_views,
+        answer_total: res.total,
+        next,
+        prev,
+        question,
+    }
+    .into_response()
+}
+
+/// Renders the question form.
+fn question_form(
+    user: &Option<CurrentUser>,
+    title: &str,
+    action: String,
+    form: QuestionForm,
+    error: Option<String>,
+) -> QuestionFormPage {
+    QuestionFormPage {
+        user: account(user),
+        title: title.to_string(),
+        action,
+        form,
+        error,
+    }
+}
+
+pub async fn new_question_page(user: Option<CurrentUser>) -> Response {
+    if let Err(response) = member(&user) {
+        return *response;
+    }
+    question_form(
+        &user,
+        "Ask a question",
+        "/questions/new".to_string(),
+        QuestionForm::default(),
+        None,
+    )
+    .into_response()
+}
+
+pub async fn create_question(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Form(form): Form<QuestionForm>,
+) -> Response {
+    let author = match poster(&user, &form.csrf) {
+        Ok(author) => author,
+        Err(response) => return *response,
+    };
+
+    let mut question = form.clone().into_question();
+    question.author_id = Some(author.0.id);
+    let write_lock = bank.write().await;
+    match write_lock.questions.add(question).await {
+        Ok(_) => Redirect::to("/questions?sort=newest").into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            question_form(
+                &user,
+                "Ask a question",
+                "/questions/new".to_string(),
+                form,
+                Some(e.to_string()),
+            ),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn edit_question_page(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Path(question_id): Path<i32>,
+) -> Response {
+    let editor = match member(&user) {
+        Ok(editor) => editor,
+        Err(response) => return *response,
+    };
+
+    let read_lock = bank.read().await;
+    let question = match read_lock.questions.get(question_id).await {
+        Ok(questions) => questions.into_iter().next(),
+        Err(e) => return error_page(StatusCode::NOT_FOUND, &user, e),
+    };
+    match question {
+        Some(question) if editor.can_edit(question.author_id) => question_form(
+            &user,
+            "Edit question",
+            format!("/questions/{question_id}/edit"),
+            QuestionForm::from_question(&question),
+            None,
+        )
+        .into_response(),
+        _ => not_author(&user, "question"),
+    }
+}
+
+pub async fn update_question_form(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Path(question_id): Path<i32>,
+    Form(form): Form<QuestionForm>,
+) -> Response {
+    let editor = match poster(&user, &form.csrf) {
+        Ok(editor) => editor,
+        Err(response) => return *response,
+    };
+
+    let write_lock = bank.write().await;
+    match write_lock.questions.get(question_id).await {
+        Ok(questions) if questions.iter().all(|q| editor.can_edit(q.author_id)) => {}
+        Ok(_) => return not_author(&user, "question"),
+        Err(e) => return error_page(StatusCode::NOT_FOUND, &user, e),
+    }
+    match write_lock
+        .questions
+        .update(question_id, form.clone().into_question(), Some(editor.0.id))
+        .await
+    {
+        Ok(_) => Redirect::to(&format!("/questions/{question_id}")).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            question_form(
+                &user,
+                "Edit question",
+                format!("/questions/{question_id}/edit"),
+                form,
+                Some(e.to_string()),
+            ),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn create_answer(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Path(question_id): Path<i32>,
+    Form(form): Form<AnswerForm>,
+) -> Response {
+    let author = match poster(&user, &form.csrf) {
+        Ok(author) => author,
+        Err(response) => return *response,
+    };
+
+    let mut answer = Answer::new(None, &form.answer, Some(question_id));
+    answer.author_id = Some(author.0.id);
+    let write_lock = bank.write().await;
+    if let Err(e) = closure_controller::check_open(&write_lock, question_id).await {
+        return error_page(closure_controller::error_status(e.as_ref()), &user, e);
+    }
+    let answer = match answer_controller::check_key(&write_lock, question_id, answer, false).await {
+        Ok(answer) => answer,
+        Err(e) => return error_page(StatusCode::BAD_REQUEST, &user, e),
+    };
+    match write_lock.answers.add(answer).await {
+        Ok(_) => Redirect::to(&format!("/questions/{question_id}")).into_response(),
+        Err(e) => error_page(StatusCode::BAD_REQUEST, &user, e),
+    }
+}
+
+/// Adds a comment by the logged in member, then goes back to the question.
+///
+/// # Parameters
+///
+/// * `question_id`: The question to go back to.
+/// * `target`, `target_id`: What is commented on.
+async fn add_comment(
+    bank: &QuestionBank,
+    user: &Option<CurrentUser>,
+    question_id: i32,
+    target: CommentTarget,
+    target_id: i32,
+    form: CommentForm,
+) -> Response {
+    let author = match poster(user, &form.csrf) {
+        Ok(author) => author,
+        Err(response) => return *response,
+    };
+    if let Err(e) = comment_controller::validate(&form.body) {
+        return error_page(StatusCode::BAD_REQUEST, user, Box::new(e));
+    }
+
+    let comment = CommentRequest {
+        body: form.body,
+        parent_id: form.parent_id,
+    };
+    match bank
+        .comments
+        .add(target, target_id, Some(author.0.id), &comment)
+        .await
+    {
+        Ok(comment) => Redirect::to(&format!("/questions/{question_id}#comment-{}", comment.id))
+            .into_response(),
+        Err(e) => error_page(StatusCode::BAD_REQUEST, user, e),
+    }
+}
+
+pub async fn create_question_comment(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Path(question_id): Path<i32>,
+    Form(form): Form<CommentForm>,
+) -> Response {
+    let write_lock = bank.write().await;
+    if let Err(e) = write_lock.questions.get(question_id).await {
+        return error_page(StatusCode::NOT_FOUND, &user, e);
+    }
+    add_comment(
+        &write_lock,
+        &user,
+        question_id,
+        CommentTarget::Question,
+        question_id,
+        form,
+    )
+    .await
+}
+
+pub async fn create_answer_comment(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Path(answer_id): Path<i32>,
+    Form(form): Form<CommentForm>,
+) -> Response {
+    let write_lock = bank.write().await;
+    let answer = match write_lock.answers.get(answer_id).await {
+        Ok(answer) => answer,
+        Err(e) => return error_page(StatusCode::NOT_FOUND, &user, e),
+    };
+    add_comment(
+        &write_lock,
+        &user,
+        answer.question_id.unwrap_or_default(),
+        CommentTarget::Answer,
+        answer_id,
+        form,
+    )
+    .await
+}
+
+pub async fn edit_answer_page(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    let editor = match member(&user) {
+        Ok(editor) => editor,
+        Err(response) => return *response,
+    };
+
+    let read_lock = bank.read().await;
+    let answer = match read_lock.answers.get(answer_id).await {
+        Ok(answer) => answer,
+        Err(e) => return error_page(StatusCode::NOT_FOUND, &user, e),
+    };
+    if !editor.can_edit(answer.author_id) {
+        return not_author(&user, "answer");
+    }
+
+    AnswerFormPage {
+        user: account(&user),
+        title: "Edit answer".to_string(),
+        answer_id,
+        question_id: answer.question_id.unwrap_or_default(),
+        form: AnswerForm {
+            answer: answer.answer,
+            ..AnswerForm::default()
+        },
+        error: None,
+    }
+    .into_response()
+}
+
+pub async fn update_answer_form(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    Path(answer_id): Path<i32>,
+    Form(form): Form<AnswerForm>,
+) -> Response {
+    let editor = match poster(&user, &form.csrf) {
+        Ok(editor) => editor,
+        Err(response) => return *response,
+    };
+
+    let write_lock = bank.write().await;
+    let answer = match write_lock.answers.get(answer_id).await {
+        Ok(answer) => answer,
+        Err(e) => return error_page(StatusCode::NOT_FOUND, &user, e),
+    };
+    if !editor.can_edit(answer.author_id) {
+        return not_author(&user, "answer");
+    }
+
+    let question_id = answer.question_id.unwrap_or_default();
+    let update = Answer::new(None, &form.answer, answer.question_id);
+    match write_lock
+        .answers
+        .update(answer_id, update, Some(editor.0.id))
+        .await
+    {
+        Ok(_) => Redirect::to(&format!("/questions/{question_id}")).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            AnswerFormPage {
+                user: account(&user),
+                title: "Edit answer".to_string(),
+                answer_id,
+                question_id,
+                form,
+                error: Some(e.to_string()),
+            },
+        )
+            .into_response(),
+    }
+}
+
+pub async fn tags_page(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<Pagination>,
+    Query(filter): Query<TagFilter>,
+) -> Response {
+    let page = match params.validate(&tag_model::order(filter.sort)) {
+        Ok(page) => page,
+        Err(e) => return error_page(StatusCode::BAD_REQUEST, &user, Box::new(e)),
+    };
+
+    let read_lock = bank.read().await;
+    match read_lock.tags.get_all(&page, filter.sort).await {
+        Ok(res) => TagsPage {
+            user: account(&user),
+            title: "Tags".to_string(),
+            next: res.next_link(&uri),
+            prev: res.prev_link(&uri),
+            total: res.total,
+            tags: res.items,
+        }
+        .into_response(),
+        Err(e) => error_page(StatusCode::BAD_REQUEST, &user, e),
+    }
+}
+
+pub async fn tag_page(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    OriginalUri(uri): OriginalUri,
+    Path(name): Path<String>,
+    Query(params): Query<Pagination>,
+) -> Response {
+    let read_lock = bank.read().await;
+    let tag = match read_lock.tags.get(&normalize_tag(&name)).await {
+        Ok(tag) => tag,
+        Err(e) => return error_page(StatusCode::NOT_FOUND, &user, e),
+    };
+
+    let filter = QuestionFilter {
+        tags: vec![tag.name.clone()],
+        ..QuestionFilter::default()
+    };
+    let page = match params.validate(&question_model::order(filter.sort)) {
+        Ok(page) => page,
+        Err(e) => return error_page(StatusCode::BAD_REQUEST, &user, Box::new(e)),
+    };
+    match read_lock.questions.paginated_get(&page, &filter).await {
+        Ok(res) => TagPage {
+            user: account(&user),
+            title: format!("Questions tagged {}", tag.name),
+            next: res.next_link(&uri),
+            prev: res.prev_link(&uri),
+            total: res.total,
+            questions: res.items,
+            tag,
+        }
+        .into_response(),
+        Err(e) => error_page(StatusCode::BAD_REQUEST, &user, e),
+    }
+}
+
+pub async fn login_page(user: Option<CurrentUser>) -> Response {
+    LoginPage {
+        user: account(&user),
+        title: "Log in".to_string(),
+        error: None,
+    }
+    .into_response()
+}
+
+pub async fn login_form(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    jar: CookieJar,
+    Form(credentials): Form<Credentials>,
+) -> Response {
+    match start_session(&bank, &credentials).await {
+        Ok((_, cookie)) => (jar.add(cookie), Redirect::to("/questions")).into_response(),
+        Err((status, e)) => (
+            status,
+            LoginPage {
+                user: None,
+                title: "Log in".to_string(),
+                error: Some(e.to_string()),
+            },
+        )
+            .into_response(),
+    }
+}
+
+pub async fn logout_form(
+    State(bank): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    jar: CookieJar,
+    Form(form): Form<LogoutForm>,
+) -> Response {
+    if let Err(response) = check_csrf(&user, &form.csrf) {
+        return *response;
+    }
+
+    let write_lock = bank.write().await;
+    match end_session(&write_lock, jar).await {
+        Ok(jar) => (jar, Redirect::to("/questions")).into_response(),
+        Err(e) => error_page(StatusCode::INTERNAL_SERVER_ERROR, &user, e),
+    }
+}
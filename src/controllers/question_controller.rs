@@ -1,9 +1,35 @@
 use crate::{
-    controllers::lib::*,
-    entities::question::Question,
-    models::{errors::*, question_model::*},
-    pagination::Pagination,
+    auth::{CurrentUser, Member},
+    controllers::{
+        auth_controller::{self, AuthSchemes},
+        bulk_controller, closure_controller, comment_controller, deck_controller,
+        lib::*,
+        page_controller, quiz_controller, review_controller, revision_controller,
+        search_controller, tag_controller, token_controller, trash_controller, user_controller,
+        vote_controller::{self, fill_question_votes},
+    },
+    entities::{
+        answer::Answer,
+        bulk::{BulkAnswer, BulkQuestion, BulkSummary},
+        closure::{CloseReason, CloseRequest, Closure, ClosureAction, ClosureEvent, ReopenRequest},
+        comment::{Comment, CommentRequest},
+        deck::{DeckFormat, ImportReport, ImportRow, RowStatus},
+        kind::{AnswerKey, QuestionKind, SubmittedAnswer},
+        question::{AddQuestionQuery, Question},
+        quiz::{QuizGrade, QuizQuestion, QuizRequest, QuizSession, QuizSubmission},
+        review::{DueCard, Review},
+        revision::{DiffLine, DiffOp, Revision, RevisionDiff},
+        search::{DuplicateTitle, SearchQuery, SearchResult, SimilarQuery, SimilarQuestion},
+        tag::{Synonym, Tag, TagMerge, TagUpdate},
+        token::{ApiToken, NewApiToken, Scope, TokenRequest},
+        user::{Credentials, Role, RoleChange, User},
+        vote::Vote,
+    },
+    models::{errors::*, question_model::order},
+    negotiate::{Accept, Format, Responder, PAGE_FORMATS, PAGE_LIST_FORMATS},
+    pagination::{Pagination, QuestionFilter, QuestionSort, TagMatch, TagSort},
 };
+use std::error::Error;
 // From utoipa/examples/{simple-axum, axum-todo}.
 
 #[derive(OpenApi)]
@@ -14,45 +40,147 @@ use crate::{
         post_question,
         delete_question,
         update_question,
+        search_controller::search,
+        search_controller::similar_questions,
+        auth_controller::register,
+        auth_controller::login,
+        auth_controller::logout,
+        auth_controller::me,
+        user_controller::get_users,
+        user_controller::set_user_role,
+        user_controller::delete_user,
+        tag_controller::get_tags,
+        tag_controller::get_tag,
+        tag_controller::update_tag,
+        tag_controller::add_synonym,
+        tag_controller::delete_synonym,
+        tag_controller::merge_tag,
+        tag_controller::delete_tag,
+        token_controller::create_token,
+        token_controller::get_tokens,
+        token_controller::revoke_token,
+        vote_controller::upvote_question,
+        vote_controller::downvote_question,
+        vote_controller::unvote_question,
+        revision_controller::get_question_revisions,
+        revision_controller::diff_question_revisions,
+        revision_controller::rollback_question,
+        trash_controller::get_trashed_questions,
+        trash_controller::restore_question,
+        comment_controller::get_question_comments,
+        comment_controller::post_question_comment,
+        comment_controller::get_question_comment,
+        comment_controller::update_question_comment,
+        comment_controller::delete_question_comment,
+        closure_controller::close_question,
+        closure_controller::reopen_question,
+        closure_controller::get_question_closures,
+        quiz_controller::start_quiz,
+        quiz_controller::get_quiz_session,
+        quiz_controller::next_quiz_question,
+        quiz_controller::answer_quiz_question,
+        review_controller::due_reviews,
+        deck_controller::import_deck,
+        deck_controller::export_deck,
+        bulk_controller::import_questions,
+        bulk_controller::export_questions,
     ),
     components(
-        schemas(Question, QuestionBankError)
+        schemas(Question, Answer, SearchQuery, SearchResult, SimilarQuery, SimilarQuestion,
+            DuplicateTitle, TagMatch, QuestionSort, User, Role,
+            RoleChange, Credentials, ApiToken, NewApiToken, Scope, TokenRequest, Vote, Revision, RevisionDiff, DiffLine, DiffOp, Tag, TagUpdate, Synonym, TagMerge, TagSort, Comment, CommentRequest,
+            Closure, CloseReason, CloseRequest, ReopenRequest, ClosureAction, ClosureEvent,
+            QuizRequest, QuizSession, QuizQuestion, QuizSubmission, QuizGrade,
+            QuestionKind, AnswerKey, SubmittedAnswer, Review, DueCard, DeckFormat, RowStatus, ImportRow, ImportReport,
+            BulkQuestion, BulkAnswer, BulkSummary, QuestionBankError)
     ),
+    modifiers(&AuthSchemes),
     tags(
         (name = "Questions Server API", description = "Questions Server API")
     )
 )]
 pub struct ApiDoc;
 
+/// Checks that the user may edit or delete a question, see [`CurrentUser::can_edit`].
+///
+/// # Returns
+///
+/// The error response to send if the question does not exist or the user is not allowed.
+async fn check_author(
+    bank: &QuestionBank,
+    user: &CurrentUser,
+    question_id: i32,
+) -> Result<(), Response> {
+    let question = match bank.questions.get(question_id).await {
+        Ok(question) => question,
+        Err(e) => return Err(QuestionBankError::response(StatusCode::NOT_FOUND, e)),
+    };
+
+    if question
+        .iter()
+        .all(|question| user.can_edit(question.author_id))
+    {
+        Ok(())
+    } else {
+        Err(QuestionBankError::response(
+            StatusCode::FORBIDDEN,
+            Box::new(QuestionBankErr::Forbidden(format!(
+                "only the author or a moderator can change question {question_id}"
+            ))),
+        ))
+    }
+}
+
 #[utoipa::path(
     get,
-    path = "/api/v1/questions?page={page}&limit={limit}",
+    path = "/api/v1/questions",
     params(
-        ("page" = u64, description = "Page", minimum = 1),
-        ("limit" = u64, description = "Limit", minimum = 1)
+        Pagination,
+        QuestionFilter
     ),
     responses(
-        (status = 200, description = "List questions", body = [Question]),
-        (status = 404, description = "No questions in that range")
+        (status = 200, description = "List questions, as JSON, HTML, CSV or NDJSON depending on \
+            the `Accept` header. The total is in `X-Total-Count`, the \
+            cursors of the neighbouring pages in `X-Next-Cursor`/`X-Prev-Cursor` and a `Link` header",
+            body = [Question]),
+        (status = 400, description = "Invalid pagination or filter", body = QuestionBankError),
+        (status = 406, description = "None of JSON, HTML, CSV or NDJSON is accepted",
+            body = QuestionBankError),
     )
 )]
 pub async fn questions(
     State(questions): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    accept: Accept,
+    OriginalUri(uri): OriginalUri,
     Query(params): Query<Pagination>,
+    Query(filter): Query<QuestionFilter>,
 ) -> Response {
-    let page = params.page;
-    let limit = params.limit;
-
+    let respond = match Responder::negotiate(&accept, PAGE_LIST_FORMATS, &user) {
+        Ok(respond) => respond,
+        Err(response) => return *response,
+    };
     let read_lock = questions.read().await;
-    match paginated_get(&read_lock.question_db, page, limit).await {
-        Ok(res) => {
-            tracing::info!("{:?}", &res);
-            Json(res).into_response()
-        }
-        Err(e) => QuestionBankError::response(
-            StatusCode::NOT_FOUND,
-            Box::new(QuestionBankErr::DoesNotExist(e.to_string())),
-        ),
+    let filter = match filter.canonical(&*read_lock.tags).await {
+        Ok(filter) => filter,
+        Err((status, e)) => return respond.error(status, e),
+    };
+    let page = match params.validate(&order(filter.sort)) {
+        Ok(page) => page,
+        Err(e) => return respond.error(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+    let mut res = match read_lock.questions.paginated_get(&page, &filter).await {
+        Ok(res) => res,
+        Err(e) => return respond.error(StatusCode::BAD_REQUEST, e),
+    };
+    if let Err(e) = fill_question_votes(&read_lock, &user, &mut res.items).await {
+        return respond.error(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    tracing::info!("{:?}", &res);
+    match respond.format {
+        Format::Html => page_controller::questions_view(&user, res, &uri),
+        _ => respond.page(res, &uri),
     }
 }
 
@@ -60,60 +188,189 @@ pub async fn questions(
     get,
     path = "/api/v1/questions/{id}",
     responses(
-        (status = 200, description = "Return specified question", body = Question),
+        (status = 200, description = "Return specified question, as JSON, HTML or NDJSON \
+            depending on the `Accept` header", body = Question),
         (status = 404, description = "No question with this id", body = QuestionBankError),
+        (status = 406, description = "None of JSON, HTML or NDJSON is accepted",
+            body = QuestionBankError),
     )
 )]
 pub async fn get_question(
     State(questions): State<Arc<RwLock<QuestionBank>>>,
+    user: Option<CurrentUser>,
+    accept: Accept,
+    OriginalUri(uri): OriginalUri,
     Path(question_id): Path<i32>,
+    Query(params): Query<Pagination>,
 ) -> Response {
+    let respond = match Responder::negotiate(&accept, PAGE_FORMATS, &user) {
+        Ok(respond) => respond,
+        Err(response) => return *response,
+    };
     let read_lock = questions.read().await;
-    match get(&read_lock.question_db, question_id).await {
-        Ok(question) => Json(question).into_response(),
-        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    let mut question = match read_lock.questions.get(question_id).await {
+        Ok(question) => question,
+        Err(e) => return respond.error(StatusCode::NOT_FOUND, e),
+    };
+    if let Err(e) = fill_question_votes(&read_lock, &user, &mut question).await {
+        return respond.error(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    match respond.format {
+        Format::Html => match question.pop() {
+            Some(question) => {
+                page_controller::question_view(&read_lock, &user, question, &params, &uri).await
+            }
+            None => respond.error(
+                StatusCode::NOT_FOUND,
+                Box::new(QuestionBankErr::DoesNotExist(question_id.to_string())),
+            ),
+        },
+        _ => respond.items(question),
+    }
+}
+
+/// Checks the kind of a question being asked or edited.
+///
+/// # Parameters
+///
+/// * `question_id`: The question being edited, `None` when asking one.
+/// * `question`: The question as written.
+///
+/// # Returns
+///
+/// The question with its kind cleaned up, or a `QuestionBankErr` error if the kind is invalid
+/// or would change on a question that has an answer.
+pub async fn check_kind(
+    bank: &QuestionBank,
+    question_id: Option<i32>,
+    mut question: Question,
+) -> Result<Question, Box<dyn Error>> {
+    let Some(kind) = question.kind.take() else {
+        return Ok(question);
+    };
+    let kind = kind.validate().map_err(QuestionBankErr::InvalidInput)?;
+    if let Some(question_id) = question_id {
+        let current = bank.questions.get(question_id).await?.remove(0).kind;
+        if current.as_ref() != Some(&kind) && bank.answers.has_answers(question_id).await? {
+            return Err(Box::new(QuestionBankErr::InvalidInput(
+                "the kind of a question with answers can't change".to_string(),
+            )));
+        }
     }
+    question.kind = Some(kind);
+
+    Ok(question)
 }
 
+/// Least title similarity at which strict mode rejects a question as a duplicate
+pub const STRICT_SIMILARITY: f32 = 0.9;
+
 #[utoipa::path(
     post,
     path = "/api/v1/questions/add",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    params(AddQuestionQuery),
     request_body(
         content = inline(Question),
         description = "Question to add"
     ),
     responses(
-        (status = 201, description = "Added question", body = ()),
-        (status = 400, description = "Bad request", body = QuestionBankError)
+        (status = 201, description = "Added question. The body lists existing questions with a \
+            similar title, and is an empty array when there are none", body = [SimilarQuestion]),
+        (status = 400, description = "Bad request", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't post", body = QuestionBankError),
+        (status = 409, description = "Strict mode: the title is nearly the same as existing \
+            questions'", body = DuplicateTitle),
     )
 )]
 pub async fn post_question(
     State(questions): State<Arc<RwLock<QuestionBank>>>,
-    Json(question): Json<Question>,
+    user: Member,
+    Query(query): Query<AddQuestionQuery>,
+    Json(mut question): Json<Question>,
 ) -> Response {
     tracing::info!("post question!");
+    question.author_id = Some(user.0.id);
     let write_lock = questions.write().await;
-    match add(&write_lock.question_db, question).await {
-        Ok(_) => StatusCode::CREATED.into_response(),
+    let question = match check_kind(&write_lock, None, question).await {
+        Ok(question) => question,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    let similar = match write_lock
+        .search
+        .similar(&question.title, search_controller::MAX_SIMILAR_LIMIT)
+        .await
+    {
+        Ok(similar) => similar,
+        Err(e) if query.strict => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+        Err(e) => {
+            tracing::warn!("similar questions: {e}");
+            Vec::new()
+        }
+    };
+    if query.strict {
+        let duplicates: Vec<i32> = similar
+            .iter()
+            .filter(|question| question.similarity >= STRICT_SIMILARITY)
+            .map(|question| question.id)
+            .collect();
+        if !duplicates.is_empty() {
+            return duplicate_title(duplicates);
+        }
+    }
+
+    match write_lock.questions.add(question).await {
+        Ok(_) => (StatusCode::CREATED, Json(similar)).into_response(),
         Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
     }
 }
 
+/// Answers a strict add with a 409 naming the questions the title duplicates.
+fn duplicate_title(duplicates: Vec<i32>) -> Response {
+    let ids = duplicates
+        .iter()
+        .map(i32::to_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+    let noun = if duplicates.len() == 1 {
+        "question"
+    } else {
+        "questions"
+    };
+    let status = StatusCode::CONFLICT;
+    let error = DuplicateTitle {
+        status: status.to_string(),
+        error: QuestionBankErr::DuplicateTitle(format!("{noun} {ids}")).to_string(),
+        duplicates,
+    };
+    (status, Json(error)).into_response()
+}
+
 #[utoipa::path(
     delete,
     path = "/api/v1/questions/{id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
     responses(
         (status = 200, description = "Deleted question", body = ()),
         (status = 400, description = "Bad request", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
     )
 )]
 pub async fn delete_question(
     State(questions): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
     Path(question_id): Path<i32>,
 ) -> Response {
     tracing::info!("delete question");
     let write_lock = questions.write().await;
-    match delete(&write_lock.question_db, question_id).await {
+    if let Err(response) = check_author(&write_lock, &user, question_id).await {
+        return response;
+    }
+    match write_lock.questions.delete(question_id).await {
         Ok(()) => StatusCode::OK.into_response(),
         Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
     }
@@ -122,6 +379,7 @@ pub async fn delete_question(
 #[utoipa::path(
     put,
     path = "/api/v1/questions/{id}",
+    security(("session" = ["member"]), ("token" = ["write"])),
     request_body(
         content = inline(Question),
         description = "Question to update"
@@ -129,6 +387,8 @@ pub async fn delete_question(
     responses(
         (status = 200, description = "Updated question", body = ()),
         (status = 400, description = "Bad request", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not the author or a moderator", body = QuestionBankError),
         (status = 404, description = "Question not found", body = QuestionBankError),
         (status = 422, description = "Unprocessable entity", body = QuestionBankError),
     )
@@ -136,11 +396,23 @@ pub async fn delete_question(
 #[debug_handler]
 pub async fn update_question(
     State(questions): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
     Path(question_id): Path<i32>,
     Json(question): Json<Question>,
 ) -> Response {
     let write_lock = questions.write().await;
-    match update(&write_lock.question_db, question_id, question).await {
+    if let Err(response) = check_author(&write_lock, &user, question_id).await {
+        return response;
+    }
+    let question = match check_kind(&write_lock, Some(question_id), question).await {
+        Ok(question) => question,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    match write_lock
+        .questions
+        .update(question_id, question, Some(user.0.id))
+        .await
+    {
         Ok(_) => StatusCode::OK.into_response(),
         Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
     }
@@ -0,0 +1,271 @@
+use crate::{
+    auth::CurrentUser,
+    controllers::lib::*,
+    entities::{kind::SubmittedAnswer, quiz::*},
+    grading,
+    models::errors::*,
+    scheduling,
+};
+use std::error::Error;
+
+/// The status to answer a failed quiz operation with.
+fn error_status(e: &(dyn Error + 'static)) -> StatusCode {
+    match e.downcast_ref::<QuestionBankErr>() {
+        Some(
+            QuestionBankErr::DoesNotExist(_)
+            | QuestionBankErr::AnswerDoesNotExist(_)
+            | QuestionBankErr::QuizSessionDoesNotExist(_),
+        ) => StatusCode::NOT_FOUND,
+        Some(QuestionBankErr::AlreadyExists(_)) => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Loads a quiz session of the logged in user.
+///
+/// # Returns
+///
+/// The session, or the 404 or 403 response to send if it doesn't exist or belongs to
+/// someone else.
+async fn own_session(
+    bank: &QuestionBank,
+    user: &CurrentUser,
+    session_id: i32,
+) -> Result<QuizSession, Response> {
+    let session = bank
+        .quizzes
+        .get(session_id)
+        .await
+        .map_err(|e| QuestionBankError::response(error_status(e.as_ref()), e))?;
+    if session.user_id != user.0.id {
+        return Err(QuestionBankError::response(
+            StatusCode::FORBIDDEN,
+            Box::new(QuestionBankErr::Forbidden(
+                "only the user who started a quiz session can use it".to_string(),
+            )),
+        ));
+    }
+
+    Ok(session)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/quiz/sessions",
+    security(("session" = []), ("token" = ["write"])),
+    request_body(
+        content = Option<QuizRequest>,
+        description = "The tags to draw questions from, any answered question if left out"
+    ),
+    responses(
+        (status = 201, description = "Started a quiz session", body = QuizSession),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+    )
+)]
+pub async fn start_quiz(
+    State(quizzes): State<Arc<RwLock<QuestionBank>>>,
+    user: CurrentUser,
+    request: Option<Json<QuizRequest>>,
+) -> Response {
+    tracing::info!("start quiz");
+    let Json(request) = request.unwrap_or_default();
+    let write_lock = quizzes.write().await;
+    // Synonyms draw from their tag, like they are stored on questions
+    let tags = match write_lock.tags.canonical(Some(request.tags)).await {
+        Ok(tags) => tags.unwrap_or_default(),
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    match write_lock.quizzes.start(user.0.id, &tags).await {
+        Ok(session) => (StatusCode::CREATED, Json(session)).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/quiz/sessions/{id}",
+    security(("session" = []), ("token" = ["read"])),
+    responses(
+        (status = 200, description = "The quiz session and its score", body = QuizSession),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Someone else's session", body = QuestionBankError),
+        (status = 404, description = "Session not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_quiz_session(
+    State(quizzes): State<Arc<RwLock<QuestionBank>>>,
+    user: CurrentUser,
+    Path(session_id): Path<i32>,
+) -> Response {
+    tracing::info!("get quiz session");
+    let read_lock = quizzes.read().await;
+    match own_session(&read_lock, &user, session_id).await {
+        Ok(session) => Json(session).into_response(),
+        Err(response) => response,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/quiz/sessions/{id}/next",
+    security(("session" = []), ("token" = ["read"])),
+    responses(
+        (status = 200, description = "A random question the session hasn't served yet, without \
+            its answers", body = QuizQuestion),
+        (status = 204, description = "Every question was served", body = ()),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Someone else's session", body = QuestionBankError),
+        (status = 404, description = "Session not found", body = QuestionBankError),
+    )
+)]
+pub async fn next_quiz_question(
+    State(quizzes): State<Arc<RwLock<QuestionBank>>>,
+    user: CurrentUser,
+    Path(session_id): Path<i32>,
+) -> Response {
+    tracing::info!("next quiz question");
+    let write_lock = quizzes.write().await;
+    if let Err(response) = own_session(&write_lock, &user, session_id).await {
+        return response;
+    }
+    let unseen = match write_lock.quizzes.unseen(session_id).await {
+        Ok(unseen) => unseen,
+        Err(e) => return QuestionBankError::response(error_status(e.as_ref()), e),
+    };
+    let Some(question_id) = fastrand::choice(&unseen).copied() else {
+        return StatusCode::NO_CONTENT.into_response();
+    };
+
+    let mut question = match write_lock.questions.get(question_id).await {
+        Ok(mut questions) => questions.remove(0),
+        Err(e) => return QuestionBankError::response(error_status(e.as_ref()), e),
+    };
+    question.accepted_answer = None;
+    match write_lock.quizzes.serve(session_id, question_id).await {
+        Ok(()) => Json(QuizQuestion {
+            session_id,
+            question,
+            remaining: unseen.len() as i64 - 1,
+        })
+        .into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/quiz/sessions/{id}/answer",
+    security(("session" = []), ("token" = ["write"])),
+    request_body(
+        content = QuizSubmission,
+        description = "The answer to a question the session served"
+    ),
+    responses(
+        (status = 200, description = "The grade, the correct answer, the session's new score \
+            and when the question is due for review", body = QuizGrade),
+        (status = 400, description = "Empty or too long answer, an answer that doesn't fit the \
+            kind of question or a question the session didn't serve",
+            body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Someone else's session", body = QuestionBankError),
+        (status = 404, description = "Session or answer not found", body = QuestionBankError),
+        (status = 409, description = "Question already answered", body = QuestionBankError),
+    )
+)]
+pub async fn answer_quiz_question(
+    State(quizzes): State<Arc<RwLock<QuestionBank>>>,
+    user: CurrentUser,
+    Path(session_id): Path<i32>,
+    Json(submission): Json<QuizSubmission>,
+) -> Response {
+    tracing::info!("answer quiz question");
+    if matches!(&submission.answer, SubmittedAnswer::Text(text) if text.trim().is_empty()) {
+        return QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(
+                "an answer is required".to_string(),
+            )),
+        );
+    }
+    let text = submission.answer.to_text();
+    if text.chars().count() > grading::MAX_SUBMISSION_CHARS {
+        return QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(format!(
+                "answers are limited to {} characters",
+                grading::MAX_SUBMISSION_CHARS
+            ))),
+        );
+    }
+
+    let (kind, answer) = {
+        let read_lock = quizzes.read().await;
+        if let Err(response) = own_session(&read_lock, &user, session_id).await {
+            return response;
+        }
+        let kind = match read_lock.questions.get(submission.question_id).await {
+            Ok(mut questions) => questions.remove(0).kind.unwrap_or_default(),
+            Err(e) => return QuestionBankError::response(error_status(e.as_ref()), e),
+        };
+        match read_lock
+            .quizzes
+            .correct_answer(submission.question_id)
+            .await
+        {
+            Ok(answer) => (kind, answer),
+            Err(e) => return QuestionBankError::response(error_status(e.as_ref()), e),
+        }
+    };
+    // Graded without holding the lock
+    let grade = match grading::grade(&kind, &answer, &submission.answer) {
+        Ok(grade) => grade,
+        Err(message) => {
+            return QuestionBankError::response(
+                StatusCode::BAD_REQUEST,
+                Box::new(QuestionBankErr::InvalidInput(message)),
+            )
+        }
+    };
+
+    let write_lock = quizzes.write().await;
+    let session = match write_lock
+        .quizzes
+        .grade(
+            session_id,
+            submission.question_id,
+            text.trim(),
+            grade.correct,
+            grade.similarity,
+        )
+        .await
+    {
+        Ok(session) => session,
+        Err(e) => return QuestionBankError::response(error_status(e.as_ref()), e),
+    };
+
+    let card = match write_lock
+        .reviews
+        .get(user.0.id, submission.question_id)
+        .await
+    {
+        Ok(review) => review.map(|review| review.card()).unwrap_or_default(),
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    let card = scheduling::review(card, scheduling::quality(&grade));
+    match write_lock
+        .reviews
+        .save(user.0.id, submission.question_id, &card)
+        .await
+    {
+        Ok(review) => Json(QuizGrade {
+            question_id: submission.question_id,
+            correct: grade.correct,
+            similarity: grade.similarity,
+            answer,
+            session,
+            review,
+        })
+        .into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
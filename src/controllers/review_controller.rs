@@ -0,0 +1,62 @@
+use crate::{
+    auth::CurrentUser, controllers::lib::*, entities::review::DueQuery, models::errors::*,
+};
+
+/// Due reviews returned when the request names no limit
+pub const DEFAULT_DUE_LIMIT: i64 = 20;
+
+/// Most due reviews returned at once
+pub const MAX_DUE_LIMIT: i64 = 100;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/review/due",
+    security(("session" = []), ("token" = ["read"])),
+    params(DueQuery),
+    responses(
+        (status = 200, description = "The logged in user's questions due for review, longest \
+            overdue first, without their answers", body = [DueCard]),
+        (status = 400, description = "Invalid limit", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+    )
+)]
+pub async fn due_reviews(
+    State(reviews): State<Arc<RwLock<QuestionBank>>>,
+    user: CurrentUser,
+    Query(query): Query<DueQuery>,
+) -> Response {
+    tracing::info!("due reviews");
+    let limit = query.limit.unwrap_or(DEFAULT_DUE_LIMIT);
+    if !(1..=MAX_DUE_LIMIT).contains(&limit) {
+        return QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(format!(
+                "limit must be between 1 and {MAX_DUE_LIMIT}"
+            ))),
+        );
+    }
+
+    let read_lock = reviews.read().await;
+    // A synonym filters by its tag, like it is stored on questions
+    let tag = match read_lock
+        .tags
+        .canonical(query.tag.map(|tag| vec![tag]))
+        .await
+    {
+        Ok(tags) => tags.and_then(|mut tags| tags.pop()),
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    let mut cards = match read_lock
+        .reviews
+        .due(user.0.id, tag.as_deref(), limit)
+        .await
+    {
+        Ok(cards) => cards,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    };
+    for card in cards.iter_mut() {
+        card.question.accepted_answer = None;
+    }
+
+    Json(cards).into_response()
+}
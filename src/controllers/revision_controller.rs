@@ -0,0 +1,220 @@
+use crate::{
+    auth::Moderator,
+    controllers::lib::*,
+    entities::{answer::Answer, question::Question, revision::*},
+    models::errors::*,
+};
+
+/// Compares two revisions looked up by `get`, responding with 404 if either is missing and
+/// 400 if they are too long to diff.
+async fn diff<F, Fut>(query: DiffQuery, get: F) -> Response
+where
+    F: Fn(i32) -> Fut,
+    Fut: std::future::Future<Output = Result<Revision, Box<dyn std::error::Error>>>,
+{
+    let from = match get(query.from).await {
+        Ok(revision) => revision,
+        Err(e) => return QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    };
+    match get(query.to).await {
+        Ok(to) => match RevisionDiff::between(&from, &to) {
+            Ok(diff) => Json(diff).into_response(),
+            Err(e) => QuestionBankError::response(
+                StatusCode::BAD_REQUEST,
+                Box::new(QuestionBankErr::InvalidInput(e)),
+            ),
+        },
+        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/questions/{id}/revisions",
+    responses(
+        (status = 200, description = "Every revision of the question, oldest first",
+            body = [Revision]),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_question_revisions(
+    State(revisions): State<Arc<RwLock<QuestionBank>>>,
+    Path(question_id): Path<i32>,
+) -> Response {
+    tracing::info!("get question revisions");
+    let read_lock = revisions.read().await;
+    if let Err(e) = read_lock.questions.get(question_id).await {
+        return QuestionBankError::response(StatusCode::NOT_FOUND, e);
+    }
+    match read_lock
+        .revisions
+        .get_question_revisions(question_id)
+        .await
+    {
+        Ok(revisions) => Json(revisions).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/questions/{id}/revisions/diff",
+    params(DiffQuery),
+    responses(
+        (status = 200, description = "Line diff between two revisions of the question",
+            body = RevisionDiff),
+        (status = 400, description = "Revisions too long to diff", body = QuestionBankError),
+        (status = 404, description = "Question or revision not found", body = QuestionBankError),
+    )
+)]
+pub async fn diff_question_revisions(
+    State(revisions): State<Arc<RwLock<QuestionBank>>>,
+    Path(question_id): Path<i32>,
+    Query(query): Query<DiffQuery>,
+) -> Response {
+    tracing::info!("diff question revisions");
+    let read_lock = revisions.read().await;
+    if let Err(e) = read_lock.questions.get(question_id).await {
+        return QuestionBankError::response(StatusCode::NOT_FOUND, e);
+    }
+    diff(query, |revision| {
+        read_lock
+            .revisions
+            .get_question_revision(question_id, revision)
+    })
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/{id}/revisions/{revision}/rollback",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Restored the question as it was in the revision, \
+            recorded as a new revision", body = Question),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+        (status = 404, description = "Question or revision not found", body = QuestionBankError),
+    )
+)]
+pub async fn rollback_question(
+    State(revisions): State<Arc<RwLock<QuestionBank>>>,
+    user: Moderator,
+    Path((question_id, revision)): Path<(i32, i32)>,
+) -> Response {
+    tracing::info!("rollback question");
+    let write_lock = revisions.write().await;
+    let revision = match write_lock
+        .revisions
+        .get_question_revision(question_id, revision)
+        .await
+    {
+        Ok(revision) => revision,
+        Err(e) => return QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    };
+
+    let question = Question {
+        title: revision.title.unwrap_or_default(),
+        content: revision.content,
+        tags: revision.tags,
+        ..Question::new(Some(question_id), "", "", &[])
+    };
+    match write_lock
+        .questions
+        .update(question_id, question, Some(user.0.id))
+        .await
+    {
+        Ok(mut questions) => Json(questions.remove(0)).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/answers/{answer_id}/revisions",
+    responses(
+        (status = 200, description = "Every revision of the answer, oldest first",
+            body = [Revision]),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_answer_revisions(
+    State(revisions): State<Arc<RwLock<QuestionBank>>>,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    tracing::info!("get answer revisions");
+    let read_lock = revisions.read().await;
+    if let Err(e) = read_lock.answers.get(answer_id).await {
+        return QuestionBankError::response(StatusCode::NOT_FOUND, e);
+    }
+    match read_lock.revisions.get_answer_revisions(answer_id).await {
+        Ok(revisions) => Json(revisions).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/answers/{answer_id}/revisions/diff",
+    params(DiffQuery),
+    responses(
+        (status = 200, description = "Line diff between two revisions of the answer",
+            body = RevisionDiff),
+        (status = 400, description = "Revisions too long to diff", body = QuestionBankError),
+        (status = 404, description = "Answer or revision not found", body = QuestionBankError),
+    )
+)]
+pub async fn diff_answer_revisions(
+    State(revisions): State<Arc<RwLock<QuestionBank>>>,
+    Path(answer_id): Path<i32>,
+    Query(query): Query<DiffQuery>,
+) -> Response {
+    tracing::info!("diff answer revisions");
+    let read_lock = revisions.read().await;
+    if let Err(e) = read_lock.answers.get(answer_id).await {
+        return QuestionBankError::response(StatusCode::NOT_FOUND, e);
+    }
+    diff(query, |revision| {
+        read_lock.revisions.get_answer_revision(answer_id, revision)
+    })
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/answers/{answer_id}/revisions/{revision}/rollback",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Restored the answer as it was in the revision, \
+            recorded as a new revision", body = Answer),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+        (status = 404, description = "Answer or revision not found", body = QuestionBankError),
+    )
+)]
+pub async fn rollback_answer(
+    State(revisions): State<Arc<RwLock<QuestionBank>>>,
+    user: Moderator,
+    Path((answer_id, revision)): Path<(i32, i32)>,
+) -> Response {
+    tracing::info!("rollback answer");
+    let write_lock = revisions.write().await;
+    let revision = match write_lock
+        .revisions
+        .get_answer_revision(answer_id, revision)
+        .await
+    {
+        Ok(revision) => revision,
+        Err(e) => return QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    };
+
+    let answer = Answer::new(Some(answer_id), &revision.content, None);
+    match write_lock
+        .answers
+        .update(answer_id, answer, Some(user.0.id))
+        .await
+    {
+        Ok(answer) => Json(answer).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
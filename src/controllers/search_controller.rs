@@ -0,0 +1,105 @@
+use crate::{
+    controllers::lib::*,
+    entities::search::{SearchQuery, SimilarQuery},
+    models::{errors::*, search_model},
+    pagination::Pagination,
+};
+
+/// Candidates returned when the request names no limit
+pub const DEFAULT_SIMILAR_LIMIT: i64 = 5;
+
+/// Most candidates returned at once
+pub const MAX_SIMILAR_LIMIT: i64 = 20;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/search",
+    params(
+        ("q" = String, Query, description = "Search terms"),
+        ("tag" = Option<String>, Query, description = "Only return questions with this tag"),
+        Pagination
+    ),
+    responses(
+        (status = 200, description = "Ranked search results, paginated like the question list",
+            body = [SearchResult]),
+        (status = 400, description = "Missing search terms or invalid pagination",
+            body = QuestionBankError)
+    )
+)]
+pub async fn search(
+    State(questions): State<Arc<RwLock<QuestionBank>>>,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<Pagination>,
+    Query(mut query): Query<SearchQuery>,
+) -> Response {
+    let page = match params.validate(&search_model::ORDER) {
+        Ok(page) => page,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+    if query.q.trim().is_empty() {
+        return QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(
+                "search terms are required".to_string(),
+            )),
+        );
+    }
+
+    let read_lock = questions.read().await;
+    // A synonym filters by its tag, like it is stored on questions
+    query.tag = match read_lock
+        .tags
+        .canonical(query.tag.map(|tag| vec![tag]))
+        .await
+    {
+        Ok(tags) => tags.and_then(|mut tags| tags.pop()),
+        Err(e) => return QuestionBankError::response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    match read_lock.search.search(&query, &page).await {
+        Ok(res) => res.response(&uri),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/similar",
+    request_body(
+        content = SimilarQuery,
+        description = "Title to find likely duplicates of, nothing is added"
+    ),
+    responses(
+        (status = 200, description = "Questions with a similar title, most similar first",
+            body = [SimilarQuestion]),
+        (status = 400, description = "Missing title or invalid limit", body = QuestionBankError)
+    )
+)]
+pub async fn similar_questions(
+    State(questions): State<Arc<RwLock<QuestionBank>>>,
+    Json(query): Json<SimilarQuery>,
+) -> Response {
+    tracing::info!("similar questions");
+    if query.title.trim().is_empty() {
+        return QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(
+                "a title is required".to_string(),
+            )),
+        );
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_SIMILAR_LIMIT);
+    if !(1..=MAX_SIMILAR_LIMIT).contains(&limit) {
+        return QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(format!(
+                "limit must be between 1 and {MAX_SIMILAR_LIMIT}"
+            ))),
+        );
+    }
+
+    let read_lock = questions.read().await;
+    match read_lock.search.similar(&query.title, limit).await {
+        Ok(similar) => Json(similar).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
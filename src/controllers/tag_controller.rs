@@ -0,0 +1,238 @@
+use crate::{
+    auth::{Admin, Moderator},
+    controllers::lib::*,
+    entities::tag::{normalize_tag, Synonym, TagMerge, TagUpdate},
+    models::{errors::*, tag_model},
+    pagination::{Pagination, TagFilter},
+};
+use std::error::Error;
+
+/// The status to answer a failed tag operation with.
+fn error_status(e: &(dyn Error + 'static)) -> StatusCode {
+    match e.downcast_ref::<QuestionBankErr>() {
+        Some(QuestionBankErr::TagDoesNotExist(_) | QuestionBankErr::SynonymDoesNotExist(_)) => {
+            StatusCode::NOT_FOUND
+        }
+        Some(QuestionBankErr::AlreadyExists(_)) => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/tags",
+    params(Pagination, TagFilter),
+    responses(
+        (status = 200, description = "List tags with how many questions use them, paginated \
+            like the question list", body = [Tag]),
+        (status = 400, description = "Invalid pagination", body = QuestionBankError),
+    )
+)]
+pub async fn get_tags(
+    State(tags): State<Arc<RwLock<QuestionBank>>>,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<Pagination>,
+    Query(filter): Query<TagFilter>,
+) -> Response {
+    let page = match params.validate(&tag_model::order(filter.sort)) {
+        Ok(page) => page,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+
+    let read_lock = tags.read().await;
+    match read_lock.tags.get_all(&page, filter.sort).await {
+        Ok(res) => res.response(&uri),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/tags/{name}",
+    responses(
+        (status = 200, description = "The tag with its description and synonyms", body = Tag),
+        (status = 404, description = "Tag not found", body = QuestionBankError),
+    )
+)]
+pub async fn get_tag(
+    State(tags): State<Arc<RwLock<QuestionBank>>>,
+    Path(name): Path<String>,
+) -> Response {
+    let read_lock = tags.read().await;
+    match read_lock.tags.get(&normalize_tag(&name)).await {
+        Ok(tag) => Json(tag).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/tags/{name}",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    request_body(
+        content = inline(TagUpdate),
+        description = "The tag's new description"
+    ),
+    responses(
+        (status = 200, description = "Updated tag", body = Tag),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+        (status = 404, description = "Tag not found", body = QuestionBankError),
+    )
+)]
+pub async fn update_tag(
+    State(tags): State<Arc<RwLock<QuestionBank>>>,
+    _moderator: Moderator,
+    Path(name): Path<String>,
+    Json(update): Json<TagUpdate>,
+) -> Response {
+    tracing::info!("update tag {}", name);
+    let description = update
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty());
+
+    let write_lock = tags.write().await;
+    match write_lock
+        .tags
+        .set_description(&normalize_tag(&name), description)
+        .await
+    {
+        Ok(tag) => Json(tag).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/tags/{name}/synonyms",
+    security(("session" = ["admin"]), ("token" = ["admin"])),
+    request_body(
+        content = inline(Synonym),
+        description = "Name to replace by the tag when questions are written. If it is a tag \
+            itself, it is merged into this one"
+    ),
+    responses(
+        (status = 200, description = "Updated tag", body = Tag),
+        (status = 400, description = "The synonym is the tag itself", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not an admin", body = QuestionBankError),
+        (status = 404, description = "Tag not found", body = QuestionBankError),
+        (status = 409, description = "Already a synonym", body = QuestionBankError),
+    )
+)]
+pub async fn add_synonym(
+    State(tags): State<Arc<RwLock<QuestionBank>>>,
+    _admin: Admin,
+    Path(name): Path<String>,
+    Json(synonym): Json<Synonym>,
+) -> Response {
+    tracing::info!("add synonym {} to tag {}", synonym.synonym, name);
+    let synonym = normalize_tag(&synonym.synonym);
+    if synonym.is_empty() {
+        return QuestionBankError::response(
+            StatusCode::BAD_REQUEST,
+            Box::new(QuestionBankErr::InvalidInput(
+                "the synonym can't be empty".to_string(),
+            )),
+        );
+    }
+
+    let write_lock = tags.write().await;
+    match write_lock
+        .tags
+        .add_synonym(&normalize_tag(&name), &synonym)
+        .await
+    {
+        Ok(tag) => Json(tag).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tags/{name}/synonyms/{synonym}",
+    security(("session" = ["admin"]), ("token" = ["admin"])),
+    responses(
+        (status = 200, description = "Updated tag", body = Tag),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not an admin", body = QuestionBankError),
+        (status = 404, description = "Tag or synonym not found", body = QuestionBankError),
+    )
+)]
+pub async fn delete_synonym(
+    State(tags): State<Arc<RwLock<QuestionBank>>>,
+    _admin: Admin,
+    Path((name, synonym)): Path<(String, String)>,
+) -> Response {
+    tracing::info!("delete synonym {} of tag {}", synonym, name);
+    let write_lock = tags.write().await;
+    match write_lock
+        .tags
+        .delete_synonym(&normalize_tag(&name), &normalize_tag(&synonym))
+        .await
+    {
+        Ok(tag) => Json(tag).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/tags/{name}/merge",
+    security(("session" = ["admin"]), ("token" = ["admin"])),
+    request_body(
+        content = inline(TagMerge),
+        description = "The tag to merge this one into"
+    ),
+    responses(
+        (status = 200, description = "Moved every question to the other tag, and made this \
+            tag's name a synonym of it", body = Tag),
+        (status = 400, description = "Merging a tag into itself", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not an admin", body = QuestionBankError),
+        (status = 404, description = "Tag not found", body = QuestionBankError),
+    )
+)]
+pub async fn merge_tag(
+    State(tags): State<Arc<RwLock<QuestionBank>>>,
+    _admin: Admin,
+    Path(name): Path<String>,
+    Json(merge): Json<TagMerge>,
+) -> Response {
+    tracing::info!("merge tag {} into {}", name, merge.into);
+    let write_lock = tags.write().await;
+    match write_lock
+        .tags
+        .merge(&normalize_tag(&name), &normalize_tag(&merge.into))
+        .await
+    {
+        Ok(tag) => Json(tag).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tags/{name}",
+    security(("session" = ["admin"]), ("token" = ["admin"])),
+    responses(
+        (status = 200, description = "Removed the tag from every question", body = ()),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not an admin", body = QuestionBankError),
+        (status = 404, description = "Tag not found", body = QuestionBankError),
+    )
+)]
+pub async fn delete_tag(
+    State(tags): State<Arc<RwLock<QuestionBank>>>,
+    _admin: Admin,
+    Path(name): Path<String>,
+) -> Response {
+    tracing::info!("delete tag {}", name);
+    let write_lock = tags.write().await;
+    match write_lock.tags.delete(&normalize_tag(&name)).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    auth::{new_api_token, Account},
+    controllers::lib::*,
+    entities::token::{NewApiToken, TokenRequest},
+    models::errors::*,
+};
+
+/// Longest accepted token name
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Longest accepted token lifetime, about ten years
+const MAX_EXPIRES_IN_DAYS: u32 = 3650;
+
+/// Checks that a token request has a name and at least one scope.
+fn validate(request: &TokenRequest) -> Result<(), QuestionBankErr> {
+    let name = request.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(QuestionBankErr::InvalidInput(format!(
+            "token name must be 1 to {MAX_NAME_LENGTH} characters"
+        )));
+    }
+    if request.scopes.is_empty() {
+        return Err(QuestionBankErr::InvalidInput(
+            "a token needs at least one scope".to_string(),
+        ));
+    }
+    if request
+        .expires_in_days
+        .is_some_and(|days| !(1..=MAX_EXPIRES_IN_DAYS).contains(&days))
+    {
+        return Err(QuestionBankErr::InvalidInput(format!(
+            "expires_in_days must be between 1 and {MAX_EXPIRES_IN_DAYS}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/tokens",
+    security(("session" = []), ("token" = ["admin"])),
+    request_body(
+        content = TokenRequest,
+        description = "Name, scopes and lifetime of the new token"
+    ),
+    responses(
+        (status = 201, description = "Created token. The `token` is only shown this once",
+            body = NewApiToken),
+        (status = 400, description = "Invalid name, scopes or expiry", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "API token without the admin scope", body = QuestionBankError),
+    )
+)]
+pub async fn create_token(
+    State(tokens): State<Arc<RwLock<QuestionBank>>>,
+    user: Account,
+    Json(request): Json<TokenRequest>,
+) -> Response {
+    tracing::info!("create token {}", request.name);
+    if let Err(e) = validate(&request) {
+        return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e));
+    }
+
+    let (token, token_hash) = new_api_token();
+    let expires_at = request
+        .expires_in_days
+        .map(|days| time::OffsetDateTime::now_utc() + time::Duration::days(days.into()));
+    let mut scopes = request.scopes;
+    scopes.sort();
+    scopes.dedup();
+
+    let write_lock = tokens.write().await;
+    match write_lock
+        .tokens
+        .create(
+            user.0.id,
+            request.name.trim(),
+            &token_hash,
+            &scopes,
+            expires_at,
+        )
+        .await
+    {
+        Ok(info) => (StatusCode::CREATED, Json(NewApiToken { token, info })).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens",
+    security(("session" = []), ("token" = ["admin"])),
+    responses(
+        (status = 200, description = "List the user's API tokens, without their secrets",
+            body = [ApiToken]),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "API token without the admin scope", body = QuestionBankError),
+    )
+)]
+pub async fn get_tokens(
+    State(tokens): State<Arc<RwLock<QuestionBank>>>,
+    user: Account,
+) -> Response {
+    let read_lock = tokens.read().await;
+    match read_lock.tokens.get_all(user.0.id).await {
+        Ok(tokens) => Json(tokens).into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tokens/{token_id}",
+    security(("session" = []), ("token" = ["admin"])),
+    responses(
+        (status = 200, description = "Revoked the token", body = ()),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "API token without the admin scope", body = QuestionBankError),
+        (status = 404, description = "The user has no such token", body = QuestionBankError),
+    )
+)]
+pub async fn revoke_token(
+    State(tokens): State<Arc<RwLock<QuestionBank>>>,
+    user: Account,
+    Path(token_id): Path<i32>,
+) -> Response {
+    tracing::info!("revoke token");
+    let write_lock = tokens.write().await;
+    match write_lock.tokens.delete(user.0.id, token_id).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => QuestionBankError::response(StatusCode::NOT_FOUND, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::token::Scope;
+
+    fn request(expires_in_days: Option<u32>) -> TokenRequest {
+        TokenRequest {
+            name: "ci".to_string(),
+            scopes: vec![Scope::Read],
+            expires_in_days,
+        }
+    }
+
+    #[test]
+    fn lifetimes_are_bounded() {
+        assert!(validate(&request(None)).is_ok());
+        assert!(validate(&request(Some(1))).is_ok());
+        assert!(validate(&request(Some(MAX_EXPIRES_IN_DAYS))).is_ok());
+        assert!(validate(&request(Some(0))).is_err());
+        assert!(validate(&request(Some(4_000_000))).is_err());
+    }
+}
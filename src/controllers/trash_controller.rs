@@ -0,0 +1,133 @@
+use crate::{
+    auth::Moderator,
+    controllers::lib::*,
+    models::{errors::*, trash_model},
+    pagination::Pagination,
+};
+use std::error::Error;
+
+/// The status to answer a failed restore with.
+fn error_status(e: &(dyn Error + 'static)) -> StatusCode {
+    match e.downcast_ref::<QuestionBankErr>() {
+        Some(
+            QuestionBankErr::DoesNotExist(_)
+            | QuestionBankErr::AnswerDoesNotExist(_)
+            | QuestionBankErr::NotInTrash(_),
+        ) => StatusCode::NOT_FOUND,
+        Some(QuestionBankErr::InTrash(_)) => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/trash/questions",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    params(Pagination),
+    responses(
+        (status = 200, description = "List deleted questions, the most recently deleted first, \
+            paginated like the question list", body = [Question]),
+        (status = 400, description = "Invalid pagination", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+    )
+)]
+pub async fn get_trashed_questions(
+    State(trash): State<Arc<RwLock<QuestionBank>>>,
+    _moderator: Moderator,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<Pagination>,
+) -> Response {
+    let page = match params.validate(&trash_model::QUESTION_ORDER) {
+        Ok(page) => page,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+
+    let read_lock = trash.read().await;
+    match read_lock.trash.get_questions(&page).await {
+        Ok(res) => res.response(&uri),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/trash/answers",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    params(Pagination),
+    responses(
+        (status = 200, description = "List answers deleted on their own, the most recently \
+            deleted first, paginated like the question list", body = [Answer]),
+        (status = 400, description = "Invalid pagination", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+    )
+)]
+pub async fn get_trashed_answers(
+    State(trash): State<Arc<RwLock<QuestionBank>>>,
+    _moderator: Moderator,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<Pagination>,
+) -> Response {
+    let page = match params.validate(&trash_model::ANSWER_ORDER) {
+        Ok(page) => page,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+
+    let read_lock = trash.read().await;
+    match read_lock.trash.get_answers(&page).await {
+        Ok(res) => res.response(&uri),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/{id}/restore",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Restored the question along with the answers deleted \
+            with it", body = Question),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+        (status = 404, description = "Question not found in the trash", body = QuestionBankError),
+    )
+)]
+pub async fn restore_question(
+    State(trash): State<Arc<RwLock<QuestionBank>>>,
+    _moderator: Moderator,
+    Path(question_id): Path<i32>,
+) -> Response {
+    tracing::info!("restore question");
+    let write_lock = trash.write().await;
+    match write_lock.trash.restore_question(question_id).await {
+        Ok(question) => Json(question).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/answers/{answer_id}/restore",
+    security(("session" = ["moderator"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Restored the answer", body = Answer),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not a moderator", body = QuestionBankError),
+        (status = 404, description = "Answer not found in the trash", body = QuestionBankError),
+        (status = 409, description = "The answer's question is in the trash",
+            body = QuestionBankError),
+    )
+)]
+pub async fn restore_answer(
+    State(trash): State<Arc<RwLock<QuestionBank>>>,
+    _moderator: Moderator,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    tracing::info!("restore answer");
+    let write_lock = trash.write().await;
+    match write_lock.trash.restore_answer(answer_id).await {
+        Ok(answer) => Json(answer).into_response(),
+        Err(e) => QuestionBankError::response(error_status(e.as_ref()), e),
+    }
+}
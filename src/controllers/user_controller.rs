@@ -0,0 +1,106 @@
+use crate::{
+    auth::Admin,
+    controllers::lib::*,
+    entities::user::RoleChange,
+    models::{errors::*, user_model},
+    pagination::Pagination,
+};
+use std::error::Error;
+
+/// The status to answer a failed role change or deletion with.
+fn error_status(e: &(dyn Error + 'static)) -> StatusCode {
+    match e.downcast_ref::<QuestionBankErr>() {
+        Some(QuestionBankErr::UserDoesNotExist(_)) => StatusCode::NOT_FOUND,
+        Some(QuestionBankErr::LastAdmin(_)) => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    security(("session" = ["admin"]), ("token" = ["admin"])),
+    params(Pagination),
+    responses(
+        (status = 200, description = "List users in the order they registered, paginated like \
+            the question list", body = [User]),
+        (status = 400, description = "Invalid pagination", body = QuestionBankError),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not an admin", body = QuestionBankError),
+    )
+)]
+pub async fn get_users(
+    State(users): State<Arc<RwLock<QuestionBank>>>,
+    _admin: Admin,
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<Pagination>,
+) -> Response {
+    let page = match params.validate(&user_model::ORDER) {
+        Ok(page) => page,
+        Err(e) => return QuestionBankError::response(StatusCode::BAD_REQUEST, Box::new(e)),
+    };
+
+    let read_lock = users.read().await;
+    match read_lock.users.get_all(&page).await {
+        Ok(res) => res.response(&uri),
+        Err(e) => QuestionBankError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{user_id}/role",
+    security(("session" = ["admin"]), ("token" = ["admin"])),
+    request_body(
+        content = RoleChange,
+        description = "The user's new role"
+    ),
+    responses(
+        (status = 200, description = "Changed the user's role", body = User),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not an admin", body = QuestionBankError),
+        (status = 404, description = "User not found", body = QuestionBankError),
+        (status = 409, description = "The user is the last admin and would lose the role",
+            body = QuestionBankError),
+        (status = 422, description = "Unknown role", body = QuestionBankError),
+    )
+)]
+pub async fn set_user_role(
+    State(users): State<Arc<RwLock<QuestionBank>>>,
+    _admin: Admin,
+    Path(user_id): Path<i32>,
+    Json(change): Json<RoleChange>,
+) -> Response {
+    tracing::info!("set role of user {} to {}", user_id, change.role);
+    let write_lock = users.write().await;
+    match write_lock.users.set_role(user_id, change.role).await {
+        Ok(user) => Json(user).into_response(),
+        Err(e) => QuestionBankError::response(error_status(&*e), e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{user_id}",
+    security(("session" = ["admin"]), ("token" = ["admin"])),
+    responses(
+        (status = 200, description = "Deleted user, their questions and answers are kept \
+            without an author", body = ()),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Not an admin", body = QuestionBankError),
+        (status = 404, description = "User not found", body = QuestionBankError),
+        (status = 409, description = "The user is the last admin", body = QuestionBankError),
+    )
+)]
+pub async fn delete_user(
+    State(users): State<Arc<RwLock<QuestionBank>>>,
+    _admin: Admin,
+    Path(user_id): Path<i32>,
+) -> Response {
+    tracing::info!("delete user");
+    let write_lock = users.write().await;
+    match write_lock.users.delete(user_id).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => QuestionBankError::response(error_status(&*e), e),
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    auth::{CurrentUser, Member},
+    controllers::lib::*,
+    entities::{answer::Answer, question::Question, vote::*},
+    models::errors::*,
+};
+use std::error::Error;
+
+/// The status to answer a failed vote with.
+fn error_status(e: &(dyn Error + 'static)) -> StatusCode {
+    match e.downcast_ref::<QuestionBankErr>() {
+        Some(QuestionBankErr::DoesNotExist(_) | QuestionBankErr::AnswerDoesNotExist(_)) => {
+            StatusCode::NOT_FOUND
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Fills in the caller's own votes on questions and their accepted answers.
+///
+/// # Parameters
+///
+/// * `user`: The logged in caller, nothing is filled in for anonymous callers.
+/// * `questions`: The questions to fill in.
+pub async fn fill_question_votes(
+    bank: &QuestionBank,
+    user: &Option<CurrentUser>,
+    questions: &mut [Question],
+) -> Result<(), Box<dyn Error>> {
+    let Some(CurrentUser(user, _)) = user else {
+        return Ok(());
+    };
+
+    let ids: Vec<i32> = questions
+        .iter()
+        .filter_map(|question| question.id)
+        .collect();
+    let answer_ids: Vec<i32> = questions
+        .iter()
+        .filter_map(|question| question.accepted_answer.as_ref()?.id)
+        .collect();
+    let votes = bank
+        .votes
+        .user_votes(VoteTarget::Question, user.id, &ids)
+        .await?;
+    let answer_votes = bank
+        .votes
+        .user_votes(VoteTarget::Answer, user.id, &answer_ids)
+        .await?;
+
+    for question in questions.iter_mut() {
+        question.my_vote = question.id.and_then(|id| votes.get(&id).copied());
+        if let Some(answer) = question.accepted_answer.as_mut() {
+            answer.my_vote = answer.id.and_then(|id| answer_votes.get(&id).copied());
+        }
+    }
+
+    Ok(())
+}
+
+/// Fills in the caller's own votes on answers.
+///
+/// # Parameters
+///
+/// * `user`: The logged in caller, nothing is filled in for anonymous callers.
+/// * `answers`: The answers to fill in.
+pub async fn fill_answer_votes(
+    bank: &QuestionBank,
+    user: &Option<CurrentUser>,
+    answers: &mut [Answer],
+) -> Result<(), Box<dyn Error>> {
+    let Some(CurrentUser(user, _)) = user else {
+        return Ok(());
+    };
+
+    let ids: Vec<i32> = answers.iter().filter_map(|answer| answer.id).collect();
+    let votes = bank
+        .votes
+        .user_votes(VoteTarget::Answer, user.id, &ids)
+        .await?;
+    for answer in answers.iter_mut() {
+        answer.my_vote = answer.id.and_then(|id| votes.get(&id).copied());
+    }
+
+    Ok(())
+}
+
+/// Sets or removes the caller's vote and responds with the new score.
+async fn vote(
+    bank: &RwLock<QuestionBank>,
+    user: &Member,
+    target: VoteTarget,
+    index: i32,
+    value: Option<i16>,
+) -> Response {
+    tracing::info!("vote {:?} on {:?} {}", value, target, index);
+    let write_lock = bank.write().await;
+    match write_lock
+        .votes
+        .set_vote(target, index, user.0.id, value)
+        .await
+    {
+        Ok(vote) => Json(vote).into_response(),
+        Err(e) => QuestionBankError::response(error_status(&*e), e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/{id}/upvote",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Upvoted the question, replacing any earlier vote",
+            body = Vote),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't vote", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+    )
+)]
+pub async fn upvote_question(
+    State(votes): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(question_id): Path<i32>,
+) -> Response {
+    vote(&votes, &user, VoteTarget::Question, question_id, Some(1)).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions/{id}/downvote",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Downvoted the question, replacing any earlier vote",
+            body = Vote),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't vote", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+    )
+)]
+pub async fn downvote_question(
+    State(votes): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(question_id): Path<i32>,
+) -> Response {
+    vote(&votes, &user, VoteTarget::Question, question_id, Some(-1)).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/questions/{id}/vote",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Removed the caller's vote on the question", body = Vote),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't vote", body = QuestionBankError),
+        (status = 404, description = "Question not found", body = QuestionBankError),
+    )
+)]
+pub async fn unvote_question(
+    State(votes): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(question_id): Path<i32>,
+) -> Response {
+    vote(&votes, &user, VoteTarget::Question, question_id, None).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/answers/{answer_id}/upvote",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Upvoted the answer, replacing any earlier vote",
+            body = Vote),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't vote", body = QuestionBankError),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn upvote_answer(
+    State(votes): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    vote(&votes, &user, VoteTarget::Answer, answer_id, Some(1)).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/answers/{answer_id}/downvote",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Downvoted the answer, replacing any earlier vote",
+            body = Vote),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't vote", body = QuestionBankError),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn downvote_answer(
+    State(votes): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    vote(&votes, &user, VoteTarget::Answer, answer_id, Some(-1)).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/answers/{answer_id}/vote",
+    security(("session" = ["member"]), ("token" = ["write"])),
+    responses(
+        (status = 200, description = "Removed the caller's vote on the answer", body = Vote),
+        (status = 401, description = "Not logged in", body = QuestionBankError),
+        (status = 403, description = "Readers can't vote", body = QuestionBankError),
+        (status = 404, description = "Answer not found", body = QuestionBankError),
+    )
+)]
+pub async fn unvote_answer(
+    State(votes): State<Arc<RwLock<QuestionBank>>>,
+    user: Member,
+    Path(answer_id): Path<i32>,
+) -> Response {
+    vote(&votes, &user, VoteTarget::Answer, answer_id, None).await
+}
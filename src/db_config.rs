@@ -1,3 +1,4 @@
+use crate::models::{answer_model, question_model};
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 use std::error::Error;
 use tracing::trace;
@@ -16,6 +17,14 @@ pub async fn db_setup() -> Result<Pool<Postgres>, Box<dyn Error>> {
     tracing::info!("Connected to: {:?}", connection);
     tracing::info!("Running migrations if any are needed");
     sqlx::migrate!().run(&connection).await?;
+    let rendered = question_model::render_missing(&connection).await?
+        + answer_model::render_missing(&connection).await?;
+    if rendered > 0 {
+        tracing::info!(
+            "Rendered the Markdown of {} questions and answers",
+            rendered
+        );
+    }
 
     Ok(connection)
 }
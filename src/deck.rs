@@ -0,0 +1,434 @@
+use crate::entities::deck::{DeckCard, DeckFormat};
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use std::error::Error;
+
+/// Header names of the title column, compared case-insensitively
+const TITLE_COLUMNS: &[&str] = &["title", "front", "question"];
+
+/// Header names of the content column
+const CONTENT_COLUMNS: &[&str] = &["content", "body"];
+
+/// Header names of the answer column
+const ANSWER_COLUMNS: &[&str] = &["answer", "back"];
+
+/// Header names of the tags column
+const TAGS_COLUMNS: &[&str] = &["tags"];
+
+/// The columns of CSV and TSV exports
+const COLUMNS: &[&str] = &["title", "content", "answer", "tags"];
+
+/// The header lines of Anki exports: tab separated plain text with the tags in the third column
+const ANKI_HEADER: &str = "#separator:tab\n#html:false\n#tags column:3\n";
+
+/// A row of a deck file
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeckRow {
+    /// Line the row starts on, from 1
+    pub line: u64,
+    /// The card, or why the row couldn't be read
+    pub card: Result<DeckCard, String>,
+}
+
+/// Where the fields of a card are in the rows of a file, and how to read them
+#[derive(Debug, Clone, Default)]
+struct Columns {
+    title: usize,
+    content: Option<usize>,
+    answer: Option<usize>,
+    tags: Option<usize>,
+    /// Tags are separated by spaces, as in Anki, rather than commas
+    spaced_tags: bool,
+    /// Fields are HTML, as in Anki exports with `#html:true`
+    html: bool,
+}
+
+impl Columns {
+    /// Finds the columns in a header row.
+    fn from_header(header: &StringRecord) -> Result<Self, String> {
+        let find = |names: &[&str]| {
+            header
+                .iter()
+                .position(|name| names.contains(&name.trim().to_lowercase().as_str()))
+        };
+        Ok(Self {
+            title: find(TITLE_COLUMNS)
+                .ok_or_else(|| format!("the header needs a {} column", TITLE_COLUMNS.join(", ")))?,
+            content: find(CONTENT_COLUMNS),
+            answer: find(ANSWER_COLUMNS),
+            tags: find(TAGS_COLUMNS),
+            ..Default::default()
+        })
+    }
+
+    /// Reads the card of a row.
+    fn card(&self, record: &StringRecord) -> Result<DeckCard, String> {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(|field| match self.html {
+                    true => html_to_text(field),
+                    false => field.trim().to_string(),
+                })
+                .filter(|field| !field.is_empty())
+        };
+        let front = field(Some(self.title)).ok_or("a title is required")?;
+        let tags = field(self.tags).unwrap_or_default();
+        let tags = match self.spaced_tags {
+            true => tags.split_whitespace().map(String::from).collect(),
+            false => tags
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        };
+
+        Ok(DeckCard {
+            title: front.split_whitespace().collect::<Vec<&str>>().join(" "),
+            content: field(self.content).unwrap_or(front),
+            answer: field(self.answer),
+            tags,
+        })
+    }
+}
+
+/// Turns the HTML of an Anki field into text: line breaks and blocks become new lines, other
+/// tags are dropped and the common entities decoded.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = rest[1..end]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if matches!(tag.as_str(), "br" | "div" | "p" | "li") {
+            text.push('\n');
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Reads the `#key:value` header lines of an Anki export.
+///
+/// # Returns
+///
+/// The columns, the field separator and the number of header lines.
+fn anki_header(text: &str) -> Result<(Columns, u8, u64), String> {
+    let mut delimiter = b'\t';
+    let mut html = false;
+    let mut names: Option<&str> = None;
+    let mut tags = None;
+    let mut special = Vec::new();
+    let mut lines = 0;
+    for line in text.lines().take_while(|line| line.starts_with('#')) {
+        lines += 1;
+        let Some((key, value)) = line[1..].split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_lowercase().as_str() {
+            "separator" => {
+                delimiter = match value.to_lowercase().as_str() {
+                    "tab" => b'\t',
+                    "comma" => b',',
+                    "semicolon" => b';',
+                    "pipe" => b'|',
+                    "space" => b' ',
+                    "colon" => b':',
+                    other if other.len() == 1 => other.as_bytes()[0],
+                    _ => return Err(format!("unknown separator {value}")),
+                }
+            }
+            "html" => html = value.eq_ignore_ascii_case("true"),
+            "columns" => names = Some(value),
+            "tags column" | "guid column" | "notetype column" | "deck column" => {
+                let column = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|column| *column >= 1)
+                    .ok_or_else(|| format!("invalid {key}: {value}"))?;
+                if key.trim().eq_ignore_ascii_case("tags column") {
+                    tags = Some(column - 1);
+                }
+                special.push(column - 1);
+            }
+            _ => {}
+        }
+    }
+
+    let mut columns = match names {
+        Some(names) => {
+            let header = ReaderBuilder::new()
+                .delimiter(delimiter)
+                .has_headers(false)
+                .from_reader(names.as_bytes())
+                .records()
+                .next()
+                .and_then(Result::ok)
+                .unwrap_or_default();
+            Columns::from_header(&header)?
+        }
+        None => {
+            // Front and back are the first two columns left by the others
+            let mut fields = (0..).filter(|index| !special.contains(index));
+            Columns {
+                title: fields.next().unwrap_or_default(),
+                answer: fields.next(),
+                tags,
+                ..Default::default()
+            }
+        }
+    };
+    columns.tags = tags.or(columns.tags);
+    columns.spaced_tags = true;
+    columns.html = html;
+
+    Ok((columns, delimiter, lines))
+}
+
+/// Reads the cards of a deck file.
+///
+/// # Parameters
+///
+/// * `format`: The format of the file.
+/// * `text`: The content of the file.
+///
+/// # Returns
+///
+/// Every row, read or not, or why the file can't be read at all.
+pub fn parse(format: DeckFormat, text: &str) -> Result<Vec<DeckRow>, String> {
+    let (columns, delimiter, skipped) = match format {
+        DeckFormat::Anki => anki_header(text)?,
+        DeckFormat::Csv => (Columns::default(), b',', 0),
+        DeckFormat::Tsv => (Columns::default(), b'\t', 0),
+    };
+    let body = text
+        .split_inclusive('\n')
+        .skip(skipped as usize)
+        .collect::<String>();
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(body.as_bytes());
+    let mut records = reader.records();
+
+    let columns = match format {
+        DeckFormat::Anki => columns,
+        _ => match records.next() {
+            Some(Ok(header)) => Columns::from_header(&header)?,
+            Some(Err(e)) => return Err(e.to_string()),
+            None => return Err("the file is empty".to_string()),
+        },
+    };
+    Ok(records
+        .map(|record| match record {
+            Ok(record) => DeckRow {
+                line: skipped + record.position().map_or(0, |position| position.line()),
+                card: columns.card(&record),
+            },
+            Err(e) => DeckRow {
+                line: skipped + e.position().map_or(0, |position| position.line()),
+                card: Err(e.to_string()),
+            },
+        })
+        .collect())
+}
+
+/// Writes cards as a deck file, or a part of one.
+///
+/// CSV and TSV files get a header row naming the `title`, `content`, `answer` and `tags`
+/// columns, with comma separated tags. Anki files have the title as the front, the answer as
+/// the back and space separated tags, spaces within a tag becoming underscores.
+///
+/// # Parameters
+///
+/// * `format`: The format of the file.
+/// * `cards`: The cards to write.
+/// * `header`: Whether to start with the header, for the first part of a file.
+pub fn write(
+    format: DeckFormat,
+    cards: &[DeckCard],
+    header: bool,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut body = Vec::new();
+    let delimiter = match format {
+        DeckFormat::Csv => b',',
+        DeckFormat::Tsv | DeckFormat::Anki => b'\t',
+    };
+    if header && format == DeckFormat::Anki {
+        body.extend_from_slice(ANKI_HEADER.as_bytes());
+    }
+    let mut writer = WriterBuilder::new().delimiter(delimiter).from_writer(body);
+    if header && format != DeckFormat::Anki {
+        writer.write_record(COLUMNS)?;
+    }
+    for card in cards {
+        let answer = card.answer.as_deref().unwrap_or_default();
+        match format {
+            DeckFormat::Anki => {
+                let tags = card
+                    .tags
+                    .iter()
+                    .map(|tag| tag.split_whitespace().collect::<Vec<&str>>().join("_"))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                writer.write_record([card.title.as_str(), answer, &tags])?
+            }
+            _ => writer.write_record([
+                card.title.as_str(),
+                &card.content,
+                answer,
+                &card.tags.join(","),
+            ])?,
+        }
+    }
+
+    Ok(writer.into_inner().map_err(|e| e.to_string())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(title: &str, content: &str, answer: Option<&str>, tags: &[&str]) -> DeckCard {
+        DeckCard {
+            title: title.to_string(),
+            content: content.to_string(),
+            answer: answer.map(String::from),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    fn cards(rows: Vec<DeckRow>) -> Vec<DeckCard> {
+        rows.into_iter().map(|row| row.card.unwrap()).collect()
+    }
+
+    fn sample() -> Vec<DeckCard> {
+        vec![
+            card("Capital of France", "Which city?", Some("Paris"), &["geo"]),
+            card(
+                "Quotes, \"commas\"",
+                "Line one\nline two",
+                None,
+                &["a", "b"],
+            ),
+        ]
+    }
+
+    #[test]
+    fn csv_and_tsv_round_trip() {
+        for format in [DeckFormat::Csv, DeckFormat::Tsv] {
+            let text = write(format, &sample(), true).unwrap();
+            let rows = parse(format, std::str::from_utf8(&text).unwrap()).unwrap();
+            assert_eq!(cards(rows), sample(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn anki_round_trip() {
+        let deck = vec![card(
+            "Capital of France",
+            "Capital of France",
+            Some("Paris"),
+            &["geo", "two words"],
+        )];
+        let text = write(DeckFormat::Anki, &deck, true).unwrap();
+        let text = std::str::from_utf8(&text).unwrap();
+        assert!(text.starts_with(ANKI_HEADER));
+
+        let parsed = cards(parse(DeckFormat::Anki, text).unwrap());
+        assert_eq!(
+            parsed,
+            [card(
+                "Capital of France",
+                "Capital of France",
+                Some("Paris"),
+                &["geo", "two_words",]
+            )]
+        );
+    }
+
+    #[test]
+    fn header_only_on_the_first_part() {
+        let first = write(DeckFormat::Csv, &sample()[..1], true).unwrap();
+        let rest = write(DeckFormat::Csv, &sample()[1..], false).unwrap();
+        assert!(!rest.starts_with(b"title"));
+
+        let text = [first, rest].concat();
+        let rows = parse(DeckFormat::Csv, std::str::from_utf8(&text).unwrap()).unwrap();
+        assert_eq!(cards(rows), sample());
+    }
+
+    #[test]
+    fn rows_are_read_one_by_one() {
+        let text = "Front,Back,Tags\nOne,1,\"x, y\"\n,no title,z\n  Two   words ,2,\n";
+        let rows = parse(DeckFormat::Csv, text).unwrap();
+        assert_eq!(rows.len(), 3);
+
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].card, Ok(card("One", "One", Some("1"), &["x", "y"])));
+        assert_eq!(rows[1].line, 3);
+        assert_eq!(rows[1].card, Err("a title is required".to_string()));
+        assert_eq!(rows[2].line, 4);
+        assert_eq!(
+            rows[2].card,
+            Ok(card("Two words", "Two   words", Some("2"), &[]))
+        );
+    }
+
+    #[test]
+    fn bad_files_are_refused() {
+        assert!(parse(DeckFormat::Csv, "").is_err());
+        assert!(parse(DeckFormat::Csv, "name,answer\nx,y\n").is_err());
+        assert!(parse(DeckFormat::Anki, "#separator:nonsense\nx\ty\n").is_err());
+        assert!(parse(DeckFormat::Anki, "#tags column:0\nx\ty\n").is_err());
+    }
+
+    #[test]
+    fn anki_header_picks_the_columns() {
+        let text = "#separator:Semicolon\n#html:true\n#notetype column:1\n#tags column:4\n\
+            Basic;<b>Front</b><br>side;\"Back &amp; more\";t1 t2\n";
+        let rows = parse(DeckFormat::Anki, text).unwrap();
+        assert_eq!(rows[0].line, 5);
+        assert_eq!(
+            rows[0].card,
+            Ok(card(
+                "Front side",
+                "Front\nside",
+                Some("Back & more"),
+                &["t1", "t2"]
+            ))
+        );
+
+        let text = "#columns:Back\tFront\nParis\tCapital of France\n";
+        let rows = cards(parse(DeckFormat::Anki, text).unwrap());
+        assert_eq!(rows[0].title, "Capital of France");
+        assert_eq!(rows[0].answer.as_deref(), Some("Paris"));
+    }
+
+    #[test]
+    fn html_becomes_text() {
+        assert_eq!(html_to_text("a<br>b<div>c</div>"), "a\nb\nc");
+        assert_eq!(html_to_text("<span class=\"x\">1 &lt; 2</span>"), "1 < 2");
+        assert_eq!(html_to_text("&amp;lt; &nbsp;x"), "&lt;  x");
+        assert_eq!(html_to_text("1 < 2"), "1 < 2");
+    }
+}
@@ -1,15 +1,70 @@
-use crate::entities::lib::*;
+use crate::{
+    entities::{kind::AnswerKey, lib::*},
+    markdown,
+    negotiate::Tabular,
+};
+use time::OffsetDateTime;
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Answer {
     #[schema(example = 5)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<i32>,
+    /// The answer, in Markdown
     #[schema(example = "Answer")]
     pub answer: String,
+    /// The answer rendered to sanitized HTML. Ignored on writes.
+    #[schema(example = "<p>Answer</p>\n")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_html: Option<String>,
+    /// The structured answer, required for and only allowed on questions that aren't free
+    /// text. Unchanged if left out when editing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<AnswerKey>,
     #[schema(example = "5")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub question_id: Option<i32>,
+    /// Whether this is the question's accepted answer, only changed through
+    /// the accept endpoints
+    #[schema(example = false)]
+    #[serde(default)]
+    pub accepted: bool,
+    /// The user who wrote the answer. Ignored on writes.
+    #[schema(example = 1)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<i32>,
+    /// Upvotes minus downvotes. Ignored on writes.
+    #[schema(example = 3)]
+    #[serde(default)]
+    pub score: i32,
+    /// The logged in caller's vote, 1 for up and -1 for down. Ignored on writes.
+    #[schema(example = 1)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub my_vote: Option<i16>,
+    /// When the answer was written. Ignored on writes.
+    #[schema(value_type = Option<String>, example = "2024-05-01T12:00:00Z")]
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    /// When the answer was last edited. Ignored on writes.
+    #[schema(value_type = Option<String>, example = "2024-05-02T12:00:00Z")]
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+    /// When the answer was moved to the trash, only set on trashed answers. Ignored on writes.
+    #[schema(value_type = Option<String>, example = "2024-05-03T12:00:00Z")]
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deleted_at: Option<OffsetDateTime>,
 }
 
 impl From<PgRow> for Answer {
@@ -20,18 +75,59 @@ impl From<PgRow> for Answer {
         let answer: String = single_row.get("answer");
         tracing::trace!(answer);
 
+        let answer_html = single_row
+            .try_get::<Option<String>, _>("answer_html")
+            .ok()
+            .flatten();
+
+        let key = single_row
+            .try_get::<Option<sqlx::types::Json<AnswerKey>>, _>("key")
+            .ok()
+            .flatten()
+            .map(|key| key.0);
+
         let question_id: Option<i32> = single_row.get("question_id");
-        tracing::trace!(id);
+        tracing::trace!(question_id);
+
+        let accepted: bool = single_row.get("accepted");
+        tracing::trace!(accepted);
+
+        let author_id: Option<i32> = single_row.get("author_id");
+        tracing::trace!(author_id);
+
+        let score: i32 = single_row.get("score");
+        tracing::trace!(score);
+
+        let time = |column: &str| {
+            single_row
+                .try_get::<Option<OffsetDateTime>, _>(column)
+                .ok()
+                .flatten()
+        };
 
         Self {
             id,
             answer,
+            answer_html,
+            key,
             question_id,
+            accepted,
+            author_id,
+            score,
+            my_vote: None,
+            created_at: time("created_at"),
+            updated_at: time("updated_at"),
+            deleted_at: time("deleted_at"),
         }
     }
 }
 
 impl Answer {
+    /// Renders the Markdown answer to HTML, see [`markdown::render`].
+    pub fn render(&mut self) {
+        self.answer_html = Some(markdown::render(&self.answer));
+    }
+
     /// Creates a new `Answer` instance.
     ///
     /// # Parameters
@@ -48,19 +144,39 @@ impl Answer {
         Self {
             id: None,
             answer,
+            answer_html: None,
+            key: None,
             question_id,
+            accepted: false,
+            author_id: None,
+            score: 0,
+            my_vote: None,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
         }
     }
 }
 
-impl IntoResponse for &Answer {
-    /// Converts a `&Answer` into an HTTP response.
-    ///
-    /// # Returns
-    ///
-    /// A `Response` object with a status code of 200 OK and a JSON body containing the answer data.
-    fn into_response(self) -> Response {
-        tracing::info!("{:?}", &self);
-        (StatusCode::OK, Json(&self)).into_response()
+impl Tabular for Answer {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "question_id",
+        "answer",
+        "accepted",
+        "author_id",
+        "score",
+    ];
+
+    fn row(&self) -> Vec<String> {
+        let optional = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+        vec![
+            optional(self.id),
+            optional(self.question_id),
+            self.answer.clone(),
+            self.accepted.to_string(),
+            optional(self.author_id),
+            self.score.to_string(),
+        ]
     }
 }
@@ -0,0 +1,197 @@
+use crate::entities::{
+    answer::Answer,
+    kind::{AnswerKey, QuestionKind},
+    lib::*,
+    question::Question,
+};
+
+/// An answer nested in a bulk imported or exported question
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BulkAnswer {
+    #[schema(example = "Paris")]
+    pub answer: String,
+    /// Whether it is the question's accepted answer, at most one per question
+    #[schema(example = true)]
+    #[serde(default)]
+    pub accepted: bool,
+    /// The structured answer, needed unless the question is free text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<AnswerKey>,
+}
+
+/// A question with its answers, as read by `POST /import` and written by `GET /export`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BulkQuestion {
+    /// The question's ID when exported, ignored when importing
+    #[schema(example = 5)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[schema(example = "Capital of France")]
+    pub title: String,
+    #[schema(example = "Which city is the capital of France?")]
+    pub content: String,
+    #[schema(example = r#"["geo"]"#)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Free text if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<QuestionKind>,
+    #[serde(default)]
+    pub answers: Vec<BulkAnswer>,
+}
+
+impl BulkQuestion {
+    /// Checks a question to import, the way asking and answering it one by one would.
+    ///
+    /// # Returns
+    ///
+    /// The question with its kind and answer keys cleaned up, or why it is invalid.
+    pub fn validate(mut self) -> Result<Self, String> {
+        if self.title.trim().is_empty() {
+            return Err("a title is required".to_string());
+        }
+        if self.content.trim().is_empty() {
+            return Err("content is required".to_string());
+        }
+        let kind = self.kind.take().unwrap_or_default().validate()?;
+        for answer in self.answers.iter_mut() {
+            if answer.answer.trim().is_empty() {
+                return Err("answers can't be empty".to_string());
+            }
+            answer.key = match answer.key.take() {
+                Some(key) => Some(key.validate(&kind)?),
+                None if kind == QuestionKind::FreeText => None,
+                None => {
+                    return Err(format!(
+                        "an answer to a {} question needs a key",
+                        kind.name()
+                    ))
+                }
+            };
+        }
+        if self.answers.iter().filter(|answer| answer.accepted).count() > 1 {
+            return Err("at most one answer can be accepted".to_string());
+        }
+        self.kind = Some(kind);
+
+        Ok(self)
+    }
+
+    /// The question to add by `author_id`, without its answers.
+    pub fn question(&self, author_id: i32) -> Question {
+        let mut question = Question::new(None, &self.title, &self.content, &[]);
+        question.tags = Some(self.tags.clone());
+        question.kind = self.kind.clone();
+        question.author_id = Some(author_id);
+        question
+    }
+
+    /// The answers to add by `author_id` to the question `question_id`, along with whether
+    /// they are accepted.
+    pub fn answers(&self, author_id: i32, question_id: i32) -> Vec<(Answer, bool)> {
+        self.answers
+            .iter()
+            .map(|bulk| {
+                let mut answer = Answer::new(None, &bulk.answer, Some(question_id));
+                answer.key = bulk.key.clone();
+                answer.author_id = Some(author_id);
+                (answer, bulk.accepted)
+            })
+            .collect()
+    }
+}
+
+/// What a bulk import added
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct BulkSummary {
+    #[schema(example = 250)]
+    pub questions: usize,
+    #[schema(example = 410)]
+    pub answers: usize,
+    /// Transactions the questions were written in
+    #[schema(example = 3)]
+    pub batches: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(json: &str) -> Result<BulkQuestion, String> {
+        serde_json::from_str::<BulkQuestion>(json)
+            .unwrap()
+            .validate()
+    }
+
+    #[test]
+    fn free_text_is_the_default_kind() {
+        let question =
+            validate(r#"{"title": "t", "content": "c", "answers": [{"answer": "a"}]}"#).unwrap();
+        assert_eq!(question.kind, Some(QuestionKind::FreeText));
+        assert_eq!(question.answers[0].key, None);
+        assert!(!question.answers[0].accepted);
+    }
+
+    #[test]
+    fn needs_a_title_content_and_answer_text() {
+        assert_eq!(
+            validate(r#"{"title": " ", "content": "c"}"#).unwrap_err(),
+            "a title is required"
+        );
+        assert_eq!(
+            validate(r#"{"title": "t", "content": ""}"#).unwrap_err(),
+            "content is required"
+        );
+        assert_eq!(
+            validate(r#"{"title": "t", "content": "c", "answers": [{"answer": "\n"}]}"#)
+                .unwrap_err(),
+            "answers can't be empty"
+        );
+    }
+
+    #[test]
+    fn accepts_at_most_one_answer() {
+        let json = r#"{"title": "t", "content": "c", "answers": [
+            {"answer": "a", "accepted": true}, {"answer": "b", "accepted": true}]}"#;
+        assert_eq!(
+            validate(json).unwrap_err(),
+            "at most one answer can be accepted"
+        );
+    }
+
+    #[test]
+    fn checks_answer_keys_against_the_kind() {
+        let json = r#"{"title": "t", "content": "c",
+            "kind": {"type": "multiple_choice", "choices": [" Paris ", "Lyon"]},
+            "answers": [{"answer": "Paris", "key": {"type": "multiple_choice", "correct": [0, 0]}}]}"#;
+        let question = validate(json).unwrap();
+        assert_eq!(
+            question.kind,
+            Some(QuestionKind::MultipleChoice {
+                choices: vec!["Paris".to_string(), "Lyon".to_string()]
+            })
+        );
+        assert_eq!(
+            question.answers[0].key,
+            Some(AnswerKey::MultipleChoice { correct: vec![0] })
+        );
+
+        let json = r#"{"title": "t", "content": "c", "kind": {"type": "true_false"},
+            "answers": [{"answer": "yes"}]}"#;
+        assert_eq!(
+            validate(json).unwrap_err(),
+            "an answer to a true_false question needs a key"
+        );
+
+        let json = r#"{"title": "t", "content": "c", "kind": {"type": "true_false"},
+            "answers": [{"answer": "9.81", "key": {"type": "numeric", "value": 9.81}}]}"#;
+        assert_eq!(
+            validate(json).unwrap_err(),
+            "the answer key doesn't fit a true_false question"
+        );
+
+        let json = r#"{"title": "t", "content": "c",
+            "kind": {"type": "multiple_choice", "choices": ["only"]}}"#;
+        assert!(validate(json).unwrap_err().contains("needs 2 to"));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::entities::lib::*;
+use std::{fmt, str::FromStr};
+use time::OffsetDateTime;
+
+/// Why a question was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    /// Asked before, see `duplicate_of`
+    Duplicate,
+    OffTopic,
+    Resolved,
+}
+
+impl CloseReason {
+    /// The name of the reason, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseReason::Duplicate => "duplicate",
+            CloseReason::OffTopic => "off_topic",
+            CloseReason::Resolved => "resolved",
+        }
+    }
+
+    /// How the reason reads on the web site.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CloseReason::Duplicate => "a duplicate",
+            CloseReason::OffTopic => "off-topic",
+            CloseReason::Resolved => "resolved",
+        }
+    }
+}
+
+impl fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CloseReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "duplicate" => Ok(CloseReason::Duplicate),
+            "off_topic" => Ok(CloseReason::OffTopic),
+            "resolved" => Ok(CloseReason::Resolved),
+            other => Err(format!("unknown close reason {other}")),
+        }
+    }
+}
+
+/// How and by whom a question was closed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Closure {
+    pub reason: CloseReason,
+    #[schema(example = "Asked and answered last week")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// The question this one duplicates
+    #[schema(example = 3)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<i32>,
+    /// The moderator who closed the question
+    #[schema(example = 1)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_by: Option<i32>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub closed_at: OffsetDateTime,
+}
+
+impl Closure {
+    /// Reads the closure of a question selected with its `closed_at`, `close_reason`,
+    /// `close_note`, `duplicate_of` and `closed_by` columns.
+    ///
+    /// # Returns
+    ///
+    /// The closure, or `None` if the question is open.
+    pub fn from_row(row: &PgRow) -> Option<Self> {
+        let closed_at = row
+            .try_get::<Option<OffsetDateTime>, _>("closed_at")
+            .ok()
+            .flatten()?;
+        let reason: String = row.get("close_reason");
+
+        Some(Self {
+            reason: reason.parse().unwrap_or(CloseReason::Resolved),
+            note: row.get("close_note"),
+            duplicate_of: row.get("duplicate_of"),
+            closed_by: row.get("closed_by"),
+            closed_at,
+        })
+    }
+}
+
+/// Why to close a question
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CloseRequest {
+    pub reason: CloseReason,
+    #[schema(example = "Asked and answered last week")]
+    #[serde(default)]
+    pub note: Option<String>,
+    /// The question this one duplicates, required for and only allowed with `duplicate`
+    #[schema(example = 3)]
+    #[serde(default)]
+    pub duplicate_of: Option<i32>,
+}
+
+/// Why to reopen a question
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct ReopenRequest {
+    #[schema(example = "Not a duplicate after all")]
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Whether a question was closed or reopened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ClosureAction {
+    Close,
+    Reopen,
+}
+
+impl ClosureAction {
+    /// The name of the action, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClosureAction::Close => "close",
+            ClosureAction::Reopen => "reopen",
+        }
+    }
+}
+
+/// A question being closed or reopened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ClosureEvent {
+    #[schema(example = 5)]
+    pub question_id: i32,
+    pub action: ClosureAction,
+    /// Why the question was closed, missing for reopens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<CloseReason>,
+    #[schema(example = "Asked and answered last week")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[schema(example = 3)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<i32>,
+    /// The moderator who closed or reopened the question
+    #[schema(example = 1)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i32>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub created_at: OffsetDateTime,
+}
+
+impl From<PgRow> for ClosureEvent {
+    fn from(single_row: PgRow) -> Self {
+        let question_id: i32 = single_row.get("question_id");
+        tracing::trace!(question_id);
+
+        let action: String = single_row.get("action");
+        let reason: Option<String> = single_row.get("reason");
+
+        Self {
+            question_id,
+            action: if action == ClosureAction::Reopen.as_str() {
+                ClosureAction::Reopen
+            } else {
+                ClosureAction::Close
+            },
+            reason: reason.and_then(|reason| reason.parse().ok()),
+            note: single_row.get("note"),
+            duplicate_of: single_row.get("duplicate_of"),
+            user_id: single_row.get("user_id"),
+            created_at: single_row.get("created_at"),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::entities::lib::*;
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+/// What a comment is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentTarget {
+    Question,
+    Answer,
+}
+
+impl CommentTarget {
+    /// The column of the comments table referencing the commented item.
+    pub fn column(&self) -> &'static str {
+        match self {
+            CommentTarget::Question => "question_id",
+            CommentTarget::Answer => "answer_id",
+        }
+    }
+
+    /// What the commented item is called in messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommentTarget::Question => "question",
+            CommentTarget::Answer => "answer",
+        }
+    }
+}
+
+/// A short comment on a question or answer, possibly replying to another comment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Comment {
+    #[schema(example = 7)]
+    pub id: i32,
+    /// The commented question, missing for comments on answers
+    #[schema(example = 5)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question_id: Option<i32>,
+    /// The commented answer, missing for comments on questions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer_id: Option<i32>,
+    /// The comment this one replies to
+    #[schema(example = 6)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<i32>,
+    /// The user who wrote the comment
+    #[schema(example = 1)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<i32>,
+    #[schema(example = "Could you add a source?")]
+    pub body: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-02T12:00:00Z")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl From<PgRow> for Comment {
+    fn from(single_row: PgRow) -> Self {
+        let id: i32 = single_row.get("id");
+        tracing::trace!(id);
+
+        Self {
+            id,
+            question_id: single_row.get("question_id"),
+            answer_id: single_row.get("answer_id"),
+            parent_id: single_row.get("parent_id"),
+            author_id: single_row.get("author_id"),
+            body: single_row.get("body"),
+            created_at: single_row.get("created_at"),
+            updated_at: single_row.get("updated_at"),
+        }
+    }
+}
+
+/// A comment to add or the new text of an edited comment
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CommentRequest {
+    #[schema(example = "Could you add a source?")]
+    pub body: String,
+    /// The comment to reply to, on the same question or answer. Ignored on edits.
+    #[schema(example = 6)]
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+}
+
+/// Comments in thread order, each with how deep it is nested
+pub type Thread = Vec<(usize, Comment)>;
+
+/// Orders comments as a thread: each comment is followed by its replies, oldest first.
+///
+/// # Parameters
+///
+/// * `comments`: The comments on one question or answer.
+///
+/// # Returns
+///
+/// The comments with how deep each one is nested, 0 for comments that reply to nothing.
+pub fn thread(comments: Vec<Comment>) -> Thread {
+    let ids: Vec<i32> = comments.iter().map(|comment| comment.id).collect();
+    let mut replies: BTreeMap<Option<i32>, Vec<Comment>> = BTreeMap::new();
+    for comment in comments {
+        // Replies to comments that aren't there are shown at the top level
+        let parent = comment.parent_id.filter(|parent| ids.contains(parent));
+        replies.entry(parent).or_default().push(comment);
+    }
+
+    let mut threaded = Vec::new();
+    let mut stack: Vec<(usize, Comment)> = replies
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|comment| (0, comment))
+        .collect();
+    while let Some((depth, comment)) = stack.pop() {
+        if let Some(children) = replies.remove(&Some(comment.id)) {
+            stack.extend(children.into_iter().rev().map(|child| (depth + 1, child)));
+        }
+        threaded.push((depth, comment));
+    }
+
+    threaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: i32, parent_id: Option<i32>) -> Comment {
+        Comment {
+            id,
+            question_id: Some(1),
+            answer_id: None,
+            parent_id,
+            author_id: None,
+            body: format!("comment {id}"),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn shape(thread: Thread) -> Vec<(usize, i32)> {
+        thread
+            .into_iter()
+            .map(|(depth, comment)| (depth, comment.id))
+            .collect()
+    }
+
+    #[test]
+    fn replies_follow_their_parent_oldest_first() {
+        let comments = vec![
+            comment(1, None),
+            comment(2, None),
+            comment(3, Some(1)),
+            comment(4, Some(3)),
+            comment(5, Some(1)),
+            comment(6, Some(2)),
+        ];
+        assert_eq!(
+            shape(thread(comments)),
+            [(0, 1), (1, 3), (2, 4), (1, 5), (0, 2), (1, 6)]
+        );
+    }
+
+    #[test]
+    fn orphaned_replies_are_shown_at_the_top_level() {
+        let comments = vec![comment(2, Some(1)), comment(3, None), comment(4, Some(2))];
+        assert_eq!(shape(thread(comments)), [(0, 2), (1, 4), (0, 3)]);
+    }
+
+    #[test]
+    fn nesting_has_no_depth_limit() {
+        let comments: Vec<Comment> = (1..=50)
+            .map(|id| comment(id, (id > 1).then_some(id - 1)))
+            .collect();
+        let threaded = shape(thread(comments));
+        assert_eq!(threaded.len(), 50);
+        assert!(threaded
+            .iter()
+            .enumerate()
+            .all(|(i, &(depth, id))| depth == i && id == i as i32 + 1));
+    }
+
+    #[test]
+    fn no_comments_make_an_empty_thread() {
+        assert!(thread(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::entities::lib::*;
+
+/// A flashcard file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeckFormat {
+    /// Comma separated values with a header row naming the `title`, `content`, `answer` and
+    /// `tags` columns
+    #[default]
+    Csv,
+    /// Tab separated values with the same header row as CSV
+    Tsv,
+    /// Anki's "Notes in Plain Text" export: tab separated front, back and space separated tags,
+    /// after optional `#separator`, `#html`, `#columns` and `#... column` header lines
+    Anki,
+}
+
+impl DeckFormat {
+    /// The media type of a file in this format.
+    pub fn media_type(self) -> &'static str {
+        match self {
+            DeckFormat::Csv => "text/csv",
+            DeckFormat::Tsv => "text/tab-separated-values",
+            DeckFormat::Anki => "text/plain",
+        }
+    }
+
+    /// The file name extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            DeckFormat::Csv => "csv",
+            DeckFormat::Tsv => "tsv",
+            DeckFormat::Anki => "txt",
+        }
+    }
+}
+
+/// One flashcard of a deck file: a question along with its answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeckCard {
+    pub title: String,
+    pub content: String,
+    pub answer: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// How to import a deck
+#[derive(Debug, Clone, Copy, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    /// Format of the request body
+    #[param(inline)]
+    #[serde(default)]
+    pub format: DeckFormat,
+    /// Check the rows and report what would be imported without adding anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// How to export a deck
+#[derive(Debug, Clone, Copy, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// Format of the file
+    #[param(inline)]
+    #[serde(default)]
+    pub format: DeckFormat,
+}
+
+/// What became of a row of an imported deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RowStatus {
+    /// Added as a question, with its answer if it has one
+    Imported,
+    /// Would be added, in a dry run
+    Ready,
+    /// Skipped: the title is nearly the same as an existing question's or an earlier row's
+    Duplicate,
+    /// Skipped: the row couldn't be read or misses its title
+    Invalid,
+}
+
+/// The outcome of one row of an imported deck
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportRow {
+    /// Line of the file the row starts on, from 1
+    #[schema(example = 2)]
+    pub line: u64,
+    pub status: RowStatus,
+    #[schema(example = "Capital of France")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The added question
+    #[schema(example = 12)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question_id: Option<i32>,
+    /// Existing questions with nearly the same title
+    #[schema(example = "[5]")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<i32>,
+    #[schema(example = "a title is required")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The outcome of a deck import, row by row
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportReport {
+    #[schema(example = false)]
+    pub dry_run: bool,
+    /// Rows imported, or that would be in a dry run
+    #[schema(example = 8)]
+    pub imported: usize,
+    /// Rows skipped as duplicates
+    #[schema(example = 1)]
+    pub duplicates: usize,
+    /// Rows skipped as invalid
+    #[schema(example = 1)]
+    pub invalid: usize,
+    pub rows: Vec<ImportRow>,
+}
+
+impl ImportReport {
+    /// Adds the outcome of a row to the report, counting it.
+    pub fn push(&mut self, row: ImportRow) {
+        match row.status {
+            RowStatus::Imported | RowStatus::Ready => self.imported += 1,
+            RowStatus::Duplicate => self.duplicates += 1,
+            RowStatus::Invalid => self.invalid += 1,
+        }
+        self.rows.push(row);
+    }
+}
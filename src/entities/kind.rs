@@ -0,0 +1,244 @@
+use crate::entities::lib::*;
+use std::collections::BTreeSet;
+
+/// Most choices a multiple choice question can offer
+pub const MAX_CHOICES: usize = 20;
+
+/// Longest unit of a numeric question
+pub const MAX_UNIT_LENGTH: usize = 20;
+
+/// How a question is answered
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuestionKind {
+    /// Answered in words, graded by how close they come to the answer
+    #[default]
+    FreeText,
+    /// Answered by picking one or more of the choices, by their position from 0
+    MultipleChoice {
+        #[schema(example = r#"["Paris", "Lyon", "Marseille"]"#)]
+        choices: Vec<String>,
+    },
+    /// Answered by true or false
+    TrueFalse,
+    /// Answered by a number, in `unit` if there is one
+    Numeric {
+        #[schema(example = "m/s²")]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+    },
+}
+
+impl QuestionKind {
+    /// The name of the kind, as in its `type`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuestionKind::FreeText => "free_text",
+            QuestionKind::MultipleChoice { .. } => "multiple_choice",
+            QuestionKind::TrueFalse => "true_false",
+            QuestionKind::Numeric { .. } => "numeric",
+        }
+    }
+
+    /// Checks the kind of a question being written, trimming choices and the unit.
+    ///
+    /// # Returns
+    ///
+    /// The cleaned up kind, or why it is invalid.
+    pub fn validate(self) -> Result<Self, String> {
+        match self {
+            QuestionKind::MultipleChoice { choices } => {
+                let choices: Vec<String> = choices
+                    .iter()
+                    .map(|choice| choice.trim().to_string())
+                    .collect();
+                if !(2..=MAX_CHOICES).contains(&choices.len()) {
+                    return Err(format!(
+                        "a multiple choice question needs 2 to {MAX_CHOICES} choices"
+                    ));
+                }
+                if choices.iter().any(String::is_empty) {
+                    return Err("choices can't be empty".to_string());
+                }
+                let distinct: BTreeSet<String> =
+                    choices.iter().map(|choice| choice.to_lowercase()).collect();
+                if distinct.len() < choices.len() {
+                    return Err("choices must differ".to_string());
+                }
+                Ok(QuestionKind::MultipleChoice { choices })
+            }
+            QuestionKind::Numeric { unit } => {
+                let unit = unit
+                    .map(|unit| unit.trim().to_string())
+                    .filter(|unit| !unit.is_empty());
+                if unit
+                    .as_ref()
+                    .is_some_and(|unit| unit.chars().count() > MAX_UNIT_LENGTH)
+                {
+                    return Err(format!(
+                        "a unit must be at most {MAX_UNIT_LENGTH} characters"
+                    ));
+                }
+                Ok(QuestionKind::Numeric { unit })
+            }
+            kind => Ok(kind),
+        }
+    }
+}
+
+/// The structured answer to a question of a kind other than free text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnswerKey {
+    /// The positions of the correct choices, from 0
+    MultipleChoice {
+        #[schema(example = "[0]")]
+        correct: Vec<usize>,
+    },
+    TrueFalse {
+        #[schema(example = true)]
+        correct: bool,
+    },
+    /// The answer is right within `tolerance` of `value`
+    Numeric {
+        #[schema(example = 9.81)]
+        value: f64,
+        #[schema(example = 0.01)]
+        #[serde(default)]
+        tolerance: f64,
+    },
+}
+
+impl AnswerKey {
+    /// Checks that an answer key fits the kind of its question, sorting the correct choices.
+    ///
+    /// # Parameters
+    ///
+    /// * `kind`: The kind of the question answered.
+    ///
+    /// # Returns
+    ///
+    /// The cleaned up key, or why it doesn't fit.
+    pub fn validate(self, kind: &QuestionKind) -> Result<Self, String> {
+        match (self, kind) {
+            (AnswerKey::MultipleChoice { correct }, QuestionKind::MultipleChoice { choices }) => {
+                let correct: BTreeSet<usize> = correct.into_iter().collect();
+                if correct.is_empty() {
+                    return Err("at least one choice must be correct".to_string());
+                }
+                if correct.iter().any(|index| *index >= choices.len()) {
+                    return Err(format!(
+                        "correct choices must be between 0 and {}",
+                        choices.len() - 1
+                    ));
+                }
+                Ok(AnswerKey::MultipleChoice {
+                    correct: correct.into_iter().collect(),
+                })
+            }
+            (key @ AnswerKey::TrueFalse { .. }, QuestionKind::TrueFalse) => Ok(key),
+            (AnswerKey::Numeric { value, tolerance }, QuestionKind::Numeric { .. }) => {
+                if !value.is_finite() || !tolerance.is_finite() || tolerance < 0.0 {
+                    return Err(
+                        "a numeric answer needs a finite value and tolerance, and the \
+                        tolerance can't be negative"
+                            .to_string(),
+                    );
+                }
+                Ok(AnswerKey::Numeric { value, tolerance })
+            }
+            (_, kind) => Err(format!(
+                "the answer key doesn't fit a {} question",
+                kind.name()
+            )),
+        }
+    }
+}
+
+/// An answer submitted to a quiz: the positions of the picked choices, true or false, a
+/// number, or text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum SubmittedAnswer {
+    Choices(Vec<usize>),
+    Boolean(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl SubmittedAnswer {
+    /// The answer as stored with a quiz session: text as is, anything else as JSON.
+    pub fn to_text(&self) -> String {
+        match self {
+            SubmittedAnswer::Text(text) => text.clone(),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices(choices: &[&str]) -> QuestionKind {
+        QuestionKind::MultipleChoice {
+            choices: choices.iter().map(|choice| choice.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn choices_are_trimmed_and_checked() {
+        assert_eq!(choices(&[" a ", "b"]).validate(), Ok(choices(&["a", "b"])));
+        assert!(choices(&["a"]).validate().is_err());
+        assert!(choices(&["a", " "]).validate().is_err());
+        assert!(choices(&["Paris", "paris"]).validate().is_err());
+        assert!(choices(&["a"; MAX_CHOICES + 1]).validate().is_err());
+    }
+
+    #[test]
+    fn units_are_trimmed_and_checked() {
+        let unit = |unit: &str| QuestionKind::Numeric {
+            unit: Some(unit.to_string()),
+        };
+        assert_eq!(unit(" m/s ").validate(), Ok(unit("m/s")));
+        assert_eq!(
+            unit("  ").validate(),
+            Ok(QuestionKind::Numeric { unit: None })
+        );
+        assert!(unit(&"m".repeat(MAX_UNIT_LENGTH + 1)).validate().is_err());
+        assert_eq!(
+            QuestionKind::TrueFalse.validate(),
+            Ok(QuestionKind::TrueFalse)
+        );
+    }
+
+    #[test]
+    fn choice_keys_are_sorted_and_in_range() {
+        let kind = choices(&["a", "b", "c"]);
+        let key = |correct: Vec<usize>| AnswerKey::MultipleChoice { correct };
+        assert_eq!(key(vec![2, 0, 2]).validate(&kind), Ok(key(vec![0, 2])));
+        assert!(key(vec![]).validate(&kind).is_err());
+        assert!(key(vec![3]).validate(&kind).is_err());
+    }
+
+    #[test]
+    fn numeric_keys_are_finite() {
+        let kind = QuestionKind::Numeric { unit: None };
+        let key = |value: f64, tolerance: f64| AnswerKey::Numeric { value, tolerance };
+        assert_eq!(key(9.81, 0.01).validate(&kind), Ok(key(9.81, 0.01)));
+        assert!(key(f64::NAN, 0.0).validate(&kind).is_err());
+        assert!(key(1.0, f64::INFINITY).validate(&kind).is_err());
+        assert!(key(1.0, -0.1).validate(&kind).is_err());
+    }
+
+    #[test]
+    fn keys_must_fit_the_kind() {
+        let key = AnswerKey::TrueFalse { correct: true };
+        assert_eq!(
+            key.clone().validate(&QuestionKind::TrueFalse),
+            Ok(key.clone())
+        );
+        assert!(key.clone().validate(&QuestionKind::FreeText).is_err());
+        assert!(key.validate(&choices(&["a", "b"])).is_err());
+    }
+}
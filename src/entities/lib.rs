@@ -1,8 +1,3 @@
-pub use axum::{
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    Json,
-};
 pub use serde::{Deserialize, Serialize};
 pub use sqlx::{postgres::PgRow, Row};
-pub use utoipa::ToSchema;
+pub use utoipa::{IntoParams, ToSchema};
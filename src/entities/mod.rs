@@ -1,3 +1,16 @@
 pub mod answer;
+pub mod bulk;
+pub mod closure;
+pub mod comment;
+pub mod deck;
+pub mod kind;
 pub mod lib;
 pub mod question;
+pub mod quiz;
+pub mod review;
+pub mod revision;
+pub mod search;
+pub mod tag;
+pub mod token;
+pub mod user;
+pub mod vote;
@@ -1,4 +1,14 @@
-use crate::entities::lib::*;
+use crate::{
+    entities::{
+        answer::Answer,
+        closure::Closure,
+        kind::{AnswerKey, QuestionKind},
+        lib::*,
+    },
+    markdown,
+    negotiate::Tabular,
+};
+use time::OffsetDateTime;
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Question {
@@ -7,11 +17,73 @@ pub struct Question {
     pub id: Option<i32>,
     #[schema(example = "Title")]
     pub title: String,
+    /// The body, in Markdown
     #[schema(example = "Content!")]
     pub content: String,
+    /// The body rendered to sanitized HTML. Ignored on writes.
+    #[schema(example = "<p>Content!</p>\n")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
     #[schema(example = r#"["history", "math"]"#)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
+    /// How the question is answered: free text if left out when asking, unchanged if left out
+    /// when editing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<QuestionKind>,
+    /// The user who asked the question. Ignored on writes.
+    #[schema(example = 1)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<i32>,
+    /// Upvotes minus downvotes. Ignored on writes.
+    #[schema(example = 3)]
+    #[serde(default)]
+    pub score: i32,
+    /// The logged in caller's vote, 1 for up and -1 for down. Ignored on writes.
+    #[schema(example = 1)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub my_vote: Option<i16>,
+    /// When the question was asked. Ignored on writes.
+    #[schema(value_type = Option<String>, example = "2024-05-01T12:00:00Z")]
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    /// When the question was last edited. Ignored on writes.
+    #[schema(value_type = Option<String>, example = "2024-05-02T12:00:00Z")]
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+    /// When the question was last edited or one of its answers was written. Ignored on writes.
+    #[schema(value_type = Option<String>, example = "2024-05-03T12:00:00Z")]
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_activity_at: Option<OffsetDateTime>,
+    /// When the question was moved to the trash, only set on trashed questions. Ignored on
+    /// writes.
+    #[schema(value_type = Option<String>, example = "2024-05-04T12:00:00Z")]
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deleted_at: Option<OffsetDateTime>,
+    /// Why and by whom the question was closed, only set on closed questions. Ignored on
+    /// writes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed: Option<Closure>,
+    /// The accepted answer, if one has been marked. Ignored on writes.
+    #[schema(value_type = Option<Answer>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_answer: Option<Answer>,
 }
 
 impl From<PgRow> for Question {
@@ -25,21 +97,84 @@ impl From<PgRow> for Question {
         let content: String = single_row.get("content");
         tracing::trace!(content);
 
-        let tags = match single_row.try_get::<Vec<String>, _>("tags") {
-            Ok(val) => Some(val),
-            Err(_) => None,
+        let content_html = single_row
+            .try_get::<Option<String>, _>("content_html")
+            .ok()
+            .flatten();
+
+        let tags = single_row.try_get::<Vec<String>, _>("tags").ok();
+
+        let kind = single_row
+            .try_get::<sqlx::types::Json<QuestionKind>, _>("kind")
+            .ok()
+            .map(|kind| kind.0);
+
+        let author_id = single_row
+            .try_get::<Option<i32>, _>("author_id")
+            .ok()
+            .flatten();
+
+        let score: i32 = single_row.get("score");
+        tracing::trace!(score);
+
+        let time = |column: &str| {
+            single_row
+                .try_get::<Option<OffsetDateTime>, _>(column)
+                .ok()
+                .flatten()
         };
 
+        let closed = Closure::from_row(&single_row);
+
+        let accepted_answer = single_row
+            .try_get::<Option<i32>, _>("accepted_answer_id")
+            .ok()
+            .flatten()
+            .map(|answer_id| Answer {
+                id: Some(answer_id),
+                answer: single_row.get("accepted_answer"),
+                answer_html: single_row.get("accepted_answer_html"),
+                key: single_row
+                    .try_get::<Option<sqlx::types::Json<AnswerKey>>, _>("accepted_answer_key")
+                    .ok()
+                    .flatten()
+                    .map(|key| key.0),
+                question_id: id,
+                accepted: true,
+                author_id: single_row.get("accepted_answer_author_id"),
+                score: single_row.get("accepted_answer_score"),
+                created_at: time("accepted_answer_created_at"),
+                updated_at: time("accepted_answer_updated_at"),
+                deleted_at: time("accepted_answer_deleted_at"),
+                my_vote: None,
+            });
+
         Self {
             id,
             title,
             content,
+            content_html,
             tags,
+            kind,
+            author_id,
+            score,
+            my_vote: None,
+            created_at: time("created_at"),
+            updated_at: time("updated_at"),
+            last_activity_at: time("last_activity_at"),
+            deleted_at: time("deleted_at"),
+            closed,
+            accepted_answer,
         }
     }
 }
 
 impl Question {
+    /// Renders the Markdown content to HTML, see [`markdown::render`].
+    pub fn render(&mut self) {
+        self.content_html = Some(markdown::render(&self.content));
+    }
+
     /// Creates a new `Question` instance.
     ///
     /// # Parameters
@@ -64,19 +199,53 @@ impl Question {
             id,
             title,
             content,
+            content_html: None,
             tags,
+            kind: None,
+            author_id: None,
+            score: 0,
+            my_vote: None,
+            created_at: None,
+            updated_at: None,
+            last_activity_at: None,
+            deleted_at: None,
+            closed: None,
+            accepted_answer: None,
         }
     }
 }
 
-impl IntoResponse for &Question {
-    /// Converts a `&Question` into an HTTP response.
-    ///
-    /// # Returns
-    ///
-    /// A `Response` object with a status code of 200 OK and a JSON body containing the question data.
-    fn into_response(self) -> Response {
-        tracing::info!("{:?}", &self);
-        (StatusCode::OK, Json(&self)).into_response()
+impl Tabular for Question {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "content",
+        "tags",
+        "author_id",
+        "score",
+        "accepted_answer_id",
+    ];
+
+    /// The tags are joined with commas, like in the `tags` filter.
+    fn row(&self) -> Vec<String> {
+        let optional = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+        vec![
+            optional(self.id),
+            self.title.clone(),
+            self.content.clone(),
+            self.tags.as_deref().unwrap_or_default().join(","),
+            optional(self.author_id),
+            self.score.to_string(),
+            optional(self.accepted_answer.as_ref().and_then(|answer| answer.id)),
+        ]
     }
 }
+
+/// How to add a question
+#[derive(Debug, Clone, Copy, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AddQuestionQuery {
+    /// Reject the question if its title is nearly the same as an existing question's
+    #[serde(default)]
+    pub strict: bool,
+}
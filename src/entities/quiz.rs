@@ -0,0 +1,95 @@
+use crate::entities::{
+    answer::Answer, kind::SubmittedAnswer, lib::*, question::Question, review::Review,
+};
+use time::OffsetDateTime;
+
+/// Which questions to practice on
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct QuizRequest {
+    /// Only serve questions with one of these tags, any answered question if empty
+    #[schema(example = r#"["rust", "math"]"#)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// A user's round of practice, with its score so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct QuizSession {
+    #[schema(example = 3)]
+    pub id: i32,
+    /// The user practicing
+    #[schema(example = 1)]
+    pub user_id: i32,
+    /// The tags questions are drawn from, any if empty
+    #[schema(example = r#"["rust", "math"]"#)]
+    pub tags: Vec<String>,
+    /// Questions served so far
+    #[schema(example = 4)]
+    pub served: i64,
+    /// Served questions that were answered
+    #[schema(example = 3)]
+    pub answered: i64,
+    /// Answers graded correct
+    #[schema(example = 2)]
+    pub correct: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub created_at: OffsetDateTime,
+}
+
+impl From<PgRow> for QuizSession {
+    fn from(single_row: PgRow) -> Self {
+        let id: i32 = single_row.get("id");
+        tracing::trace!(id);
+
+        Self {
+            id,
+            user_id: single_row.get("user_id"),
+            tags: single_row.get("tags"),
+            served: single_row.get("served"),
+            answered: single_row.get("answered"),
+            correct: single_row.get("correct"),
+            created_at: single_row.get("created_at"),
+        }
+    }
+}
+
+/// A question served in a quiz, without its answers
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuizQuestion {
+    #[schema(example = 3)]
+    pub session_id: i32,
+    pub question: Question,
+    /// Questions left to serve after this one
+    #[schema(example = 6)]
+    pub remaining: i64,
+}
+
+/// An answer to a served question
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct QuizSubmission {
+    #[schema(example = 5)]
+    pub question_id: i32,
+    /// The picked choices, true or false, a number or text, by the kind of question. Text
+    /// can also name a choice, true or false, or a number followed by its unit.
+    #[schema(example = "Paris")]
+    pub answer: SubmittedAnswer,
+}
+
+/// How a submitted answer was graded
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuizGrade {
+    #[schema(example = 5)]
+    pub question_id: i32,
+    #[schema(example = true)]
+    pub correct: bool,
+    /// How close the submission came to the answer, from 0 to 1
+    #[schema(example = 0.9)]
+    pub similarity: f32,
+    /// The answer graded against: the accepted answer, else the best scored one
+    pub answer: Answer,
+    /// The session's score including this answer
+    pub session: QuizSession,
+    /// The question's spaced repetition schedule after this answer
+    pub review: Review,
+}
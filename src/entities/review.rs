@@ -0,0 +1,82 @@
+use crate::{
+    entities::{lib::*, question::Question},
+    scheduling::Card,
+};
+use time::OffsetDateTime;
+
+/// Where a user stands with a question in spaced repetition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Review {
+    #[schema(example = 1)]
+    pub user_id: i32,
+    #[schema(example = 5)]
+    pub question_id: i32,
+    /// How fast the interval grows with each successful review, 1.3 at least
+    #[schema(example = 2.5)]
+    pub ease: f32,
+    /// Days between the last review and the next one
+    #[schema(example = 6)]
+    pub interval: i32,
+    /// Successful reviews in a row
+    #[schema(example = 2)]
+    pub repetitions: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub reviewed_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-07T12:00:00Z")]
+    pub due_at: OffsetDateTime,
+}
+
+impl Review {
+    /// The review's schedule, as [`scheduling`](crate::scheduling) works on it.
+    pub fn card(&self) -> Card {
+        Card {
+            ease: self.ease,
+            interval: self.interval,
+            repetitions: self.repetitions,
+        }
+    }
+}
+
+impl From<PgRow> for Review {
+    fn from(single_row: PgRow) -> Self {
+        Self::from(&single_row)
+    }
+}
+
+/// Reads the review columns of a row that also holds other columns, such as its question's.
+impl From<&PgRow> for Review {
+    fn from(single_row: &PgRow) -> Self {
+        let question_id: i32 = single_row.get("question_id");
+        tracing::trace!(question_id);
+
+        Self {
+            user_id: single_row.get("user_id"),
+            question_id,
+            ease: single_row.get("ease"),
+            interval: single_row.get("interval_days"),
+            repetitions: single_row.get("repetitions"),
+            reviewed_at: single_row.get("reviewed_at"),
+            due_at: single_row.get("due_at"),
+        }
+    }
+}
+
+/// Which due reviews to list
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DueQuery {
+    /// Only return questions with this tag
+    pub tag: Option<String>,
+    /// Most reviews to return, 20 by default
+    #[param(minimum = 1, maximum = 100)]
+    pub limit: Option<i64>,
+}
+
+/// A question due for review, without its answers
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DueCard {
+    pub question: Question,
+    pub review: Review,
+}
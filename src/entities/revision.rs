@@ -0,0 +1,311 @@
+use crate::entities::{answer::Answer, lib::*, question::Question};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+/// A snapshot of a question or answer as it was after an edit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Revision {
+    /// Numbered from 1 for each question or answer
+    #[schema(example = 2)]
+    pub revision: i32,
+    #[schema(example = 5)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer_id: Option<i32>,
+    /// The user who made this revision
+    #[schema(example = 1)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<i32>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub created_at: OffsetDateTime,
+    /// The question's title, missing for answers
+    #[schema(example = "Title")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The question's content or the answer's text
+    #[schema(example = "Content!")]
+    pub content: String,
+    #[schema(example = r#"["history", "math"]"#)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+impl From<PgRow> for Revision {
+    fn from(single_row: PgRow) -> Self {
+        let revision: i32 = single_row.get("revision");
+        tracing::trace!(revision);
+
+        Self {
+            revision,
+            question_id: single_row.get("question_id"),
+            answer_id: single_row.get("answer_id"),
+            author_id: single_row.get("author_id"),
+            created_at: single_row.get("created_at"),
+            title: single_row.get("title"),
+            content: single_row.get("content"),
+            tags: single_row.get("tags"),
+        }
+    }
+}
+
+impl Revision {
+    /// Snapshots a question as a revision by its author, made now.
+    ///
+    /// The revision number is left at 0 for the store to fill in.
+    pub fn of_question(question_id: i32, question: &Question) -> Self {
+        Self {
+            revision: 0,
+            question_id: Some(question_id),
+            answer_id: None,
+            author_id: question.author_id,
+            created_at: OffsetDateTime::now_utc(),
+            title: Some(question.title.clone()),
+            content: question.content.clone(),
+            tags: question.tags.clone().filter(|tags| !tags.is_empty()),
+        }
+    }
+
+    /// Snapshots an answer as a revision by its author, made now.
+    ///
+    /// The revision number is left at 0 for the store to fill in.
+    pub fn of_answer(answer_id: i32, answer: &Answer) -> Self {
+        Self {
+            revision: 0,
+            question_id: None,
+            answer_id: Some(answer_id),
+            author_id: answer.author_id,
+            created_at: OffsetDateTime::now_utc(),
+            title: None,
+            content: answer.answer.clone(),
+            tags: None,
+        }
+    }
+}
+
+/// The two revisions to compare
+#[derive(Debug, Clone, Copy, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DiffQuery {
+    /// The older revision
+    pub from: i32,
+    /// The newer revision
+    pub to: i32,
+}
+
+/// What happened to a line between two revisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A line of a diff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    #[schema(example = "Content!")]
+    pub text: String,
+}
+
+/// The differences between two revisions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RevisionDiff {
+    #[schema(example = 1)]
+    pub from: i32,
+    #[schema(example = 2)]
+    pub to: i32,
+    /// Line diff of the title, empty for answers
+    pub title: Vec<DiffLine>,
+    /// Line diff of the content
+    pub content: Vec<DiffLine>,
+    #[schema(example = r#"["math"]"#)]
+    pub tags_added: Vec<String>,
+    #[schema(example = r#"["history"]"#)]
+    pub tags_removed: Vec<String>,
+}
+
+/// Most lines of a title or content that can be diffed, keeping the table of common
+/// subsequences at a few megabytes
+pub const MAX_DIFF_LINES: usize = 1000;
+
+/// Diffs two texts line by line, using their longest common subsequence of lines.
+///
+/// # Returns
+///
+/// The lines of the diff, or an error if either text has more than [`MAX_DIFF_LINES`] lines.
+fn diff_lines(old: &str, new: &str) -> Result<Vec<DiffLine>, String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    if old.len().max(new.len()) > MAX_DIFF_LINES {
+        return Err(format!(
+            "revisions of more than {MAX_DIFF_LINES} lines can't be diffed"
+        ));
+    }
+
+    // Lines are compared by number, so long lines are only read once
+    let mut numbers: HashMap<&str, usize> = HashMap::new();
+    for text in old.iter().chain(new.iter()) {
+        let next = numbers.len();
+        numbers.entry(text).or_insert(next);
+    }
+    let old_ids: Vec<usize> = old.iter().map(|text| numbers[text]).collect();
+    let new_ids: Vec<usize> = new.iter().map(|text| numbers[text]).collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old_ids[i] == new_ids[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |op, text: &str| DiffLine {
+        op,
+        text: text.to_string(),
+    };
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old_ids[i] == new_ids[j] {
+            lines.push(line(DiffOp::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(line(DiffOp::Delete, old[i]));
+            i += 1;
+        } else {
+            lines.push(line(DiffOp::Insert, new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|text| line(DiffOp::Delete, text)));
+    lines.extend(new[j..].iter().map(|text| line(DiffOp::Insert, text)));
+    Ok(lines)
+}
+
+impl RevisionDiff {
+    /// Compares two revisions of the same question or answer.
+    ///
+    /// # Parameters
+    ///
+    /// * `from`: The older revision.
+    /// * `to`: The newer revision.
+    ///
+    /// # Returns
+    ///
+    /// The line diffs of the title and content, and the tags added and removed, or an error if
+    /// either revision is too long to diff.
+    pub fn between(from: &Revision, to: &Revision) -> Result<Self, String> {
+        let from_tags = from.tags.as_deref().unwrap_or_default();
+        let to_tags = to.tags.as_deref().unwrap_or_default();
+
+        Ok(Self {
+            from: from.revision,
+            to: to.revision,
+            title: diff_lines(
+                from.title.as_deref().unwrap_or_default(),
+                to.title.as_deref().unwrap_or_default(),
+            )?,
+            content: diff_lines(&from.content, &to.content)?,
+            tags_added: to_tags
+                .iter()
+                .filter(|tag| !from_tags.contains(tag))
+                .cloned()
+                .collect(),
+            tags_removed: from_tags
+                .iter()
+                .filter(|tag| !to_tags.contains(tag))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops(lines: &[DiffLine]) -> Vec<(DiffOp, &str)> {
+        lines
+            .iter()
+            .map(|line| (line.op, line.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_lines_are_equal() {
+        let lines = diff_lines("a\nb", "a\nb").unwrap();
+        assert_eq!(ops(&lines), [(DiffOp::Equal, "a"), (DiffOp::Equal, "b")]);
+    }
+
+    #[test]
+    fn changed_line_is_deleted_then_inserted() {
+        let lines = diff_lines("a\nb\nc", "a\nB\nc\nd").unwrap();
+        assert_eq!(
+            ops(&lines),
+            [
+                (DiffOp::Equal, "a"),
+                (DiffOp::Delete, "b"),
+                (DiffOp::Insert, "B"),
+                (DiffOp::Equal, "c"),
+                (DiffOp::Insert, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_texts() {
+        assert!(diff_lines("", "").unwrap().is_empty());
+        assert_eq!(ops(&diff_lines("", "a").unwrap()), [(DiffOp::Insert, "a")]);
+        assert_eq!(ops(&diff_lines("a", "").unwrap()), [(DiffOp::Delete, "a")]);
+    }
+
+    #[test]
+    fn longest_common_lines_are_kept() {
+        let lines = diff_lines("x\na\nb\nc", "a\nb\nc\nx").unwrap();
+        let equal: Vec<&str> = lines
+            .iter()
+            .filter(|line| line.op == DiffOp::Equal)
+            .map(|line| line.text.as_str())
+            .collect();
+        assert_eq!(equal, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn long_texts_are_refused() {
+        let long = "line\n".repeat(MAX_DIFF_LINES + 1);
+        assert!(diff_lines(&long, "a").is_err());
+        assert!(diff_lines("a", &long).is_err());
+
+        let longest = "line\n".repeat(MAX_DIFF_LINES);
+        assert_eq!(
+            diff_lines(&longest, &longest).unwrap().len(),
+            MAX_DIFF_LINES
+        );
+    }
+
+    #[test]
+    fn tags_added_and_removed() {
+        let mut from = Revision::of_answer(1, &Answer::new(None, "old", Some(1)));
+        from.revision = 1;
+        from.tags = Some(vec!["a".to_string(), "b".to_string()]);
+        let mut to = from.clone();
+        to.revision = 2;
+        to.content = "new".to_string();
+        to.tags = Some(vec!["b".to_string(), "c".to_string()]);
+
+        let diff = RevisionDiff::between(&from, &to).unwrap();
+        assert_eq!((diff.from, diff.to), (1, 2));
+        assert!(diff.title.is_empty());
+        assert_eq!(diff.tags_added, ["c"]);
+        assert_eq!(diff.tags_removed, ["a"]);
+    }
+}
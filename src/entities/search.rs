@@ -0,0 +1,108 @@
+use crate::entities::lib::*;
+
+/// Query parameters of a full-text search
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SearchQuery {
+    /// Search terms, `websearch_to_tsquery` syntax ("quoted phrases", `-excluded`, `or`)
+    #[schema(example = "borrow checker")]
+    pub q: String,
+    /// Only return questions with this tag
+    #[schema(example = "rust")]
+    pub tag: Option<String>,
+}
+
+/// A question matching a search, with highlighted snippets of the matches. The snippets are
+/// HTML: the text is escaped and only the `<b>` tags around matches are markup.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SearchResult {
+    #[schema(example = 5)]
+    pub question_id: i32,
+    #[schema(example = "Title")]
+    pub title: String,
+    #[schema(example = r#"["history", "math"]"#)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Relevance of the match, higher is better
+    #[schema(example = 0.6)]
+    pub rank: f32,
+    /// The title with matching terms wrapped in `<b>` tags
+    #[schema(example = "How does the <b>borrow</b> <b>checker</b> work?")]
+    pub title_snippet: String,
+    /// Best matching fragment of the question's content
+    #[schema(example = "the <b>borrow</b> <b>checker</b> rejects my code")]
+    pub content_snippet: String,
+    /// Best matching fragment of the question's answers, if an answer matched
+    #[schema(example = "a <b>borrow</b> ends at its last use")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer_snippet: Option<String>,
+}
+
+impl From<PgRow> for SearchResult {
+    fn from(single_row: PgRow) -> Self {
+        let question_id: i32 = single_row.get("id");
+        tracing::trace!(question_id);
+
+        let tags = single_row
+            .try_get::<Vec<String>, _>("tags")
+            .ok()
+            .filter(|tags| !tags.is_empty());
+
+        Self {
+            question_id,
+            title: single_row.get("title"),
+            tags,
+            rank: single_row.get("rank"),
+            title_snippet: single_row.get("title_snippet"),
+            content_snippet: single_row.get("content_snippet"),
+            answer_snippet: single_row.get("answer_snippet"),
+        }
+    }
+}
+
+/// A title to find similar questions for
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SimilarQuery {
+    #[schema(example = "How does the borrow checker work?")]
+    pub title: String,
+    /// Most candidates to return, 5 by default and at most 20
+    #[schema(example = 5)]
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// A question whose title looks like another title
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SimilarQuestion {
+    #[schema(example = 5)]
+    pub id: i32,
+    #[schema(example = "How does the borrow checker work")]
+    pub title: String,
+    /// Trigram similarity of the titles, from 0 to 1 for identical titles
+    #[schema(example = 0.85)]
+    pub similarity: f32,
+}
+
+impl From<PgRow> for SimilarQuestion {
+    fn from(single_row: PgRow) -> Self {
+        let id: i32 = single_row.get("id");
+        tracing::trace!(id);
+
+        Self {
+            id,
+            title: single_row.get("title"),
+            similarity: single_row.get("similarity"),
+        }
+    }
+}
+
+/// A question rejected in strict mode, with the questions it duplicates
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicateTitle {
+    #[schema(example = "409 Conflict")]
+    pub status: String,
+    #[schema(example = "Title duplicates question 5")]
+    pub error: String,
+    /// The questions with a near-identical title
+    #[schema(example = "[5]")]
+    pub duplicates: Vec<i32>,
+}
@@ -0,0 +1,84 @@
+use crate::entities::lib::*;
+use time::OffsetDateTime;
+
+/// A tag along with how many questions use it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Tag {
+    #[schema(example = "javascript")]
+    pub name: String,
+    #[schema(example = "The language of the web")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// How many questions have the tag
+    #[schema(example = 12)]
+    pub questions: i64,
+    /// Names that are replaced by this tag when questions are written
+    #[schema(example = r#"["js"]"#)]
+    pub synonyms: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub created_at: OffsetDateTime,
+    /// When the description or the synonyms last changed
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-02T12:00:00Z")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl From<PgRow> for Tag {
+    fn from(single_row: PgRow) -> Self {
+        let name: String = single_row.get("name");
+        tracing::trace!(name);
+
+        Self {
+            name,
+            description: single_row.get("description"),
+            questions: single_row.get("questions"),
+            synonyms: single_row.get("synonyms"),
+            created_at: single_row.get("created_at"),
+            updated_at: single_row.get("updated_at"),
+        }
+    }
+}
+
+/// A new description for a tag
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct TagUpdate {
+    /// Left out or empty to remove the description
+    #[schema(example = "The language of the web")]
+    pub description: Option<String>,
+}
+
+/// A name to make a synonym of a tag
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct Synonym {
+    #[schema(example = "js")]
+    pub synonym: String,
+}
+
+/// The tag another tag is merged into
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct TagMerge {
+    #[schema(example = "javascript")]
+    pub into: String,
+}
+
+/// Normalizes a tag name the way it is stored: trimmed and lowercased.
+pub fn normalize_tag(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Normalizes the tags of a question, dropping empty and repeated ones.
+///
+/// # Returns
+///
+/// The tags in the order they were first given, or `None` if none are left.
+pub fn normalize_tags(tags: Option<Vec<String>>) -> Option<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.iter().flatten() {
+        let tag = normalize_tag(tag);
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    Some(normalized).filter(|tags| !tags.is_empty())
+}
@@ -0,0 +1,124 @@
+use crate::entities::lib::*;
+use std::{fmt, str::FromStr};
+use time::OffsetDateTime;
+
+/// What an API token may be used for. Each scope allows everything the scopes
+/// before it do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// `GET` requests
+    Read,
+    /// Requests that change questions and answers
+    Write,
+    /// Admin routes and managing API tokens
+    Admin,
+}
+
+impl Scope {
+    /// The name of the scope, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Scope::Read),
+            "write" => Ok(Scope::Write),
+            "admin" => Ok(Scope::Admin),
+            other => Err(format!("unknown scope {other}")),
+        }
+    }
+}
+
+/// A personal API token, without the secret itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApiToken {
+    #[schema(example = 1)]
+    pub id: i32,
+    #[schema(example = 1)]
+    pub user_id: i32,
+    #[schema(example = "bulk loader")]
+    pub name: String,
+    #[schema(example = r#"["read", "write"]"#)]
+    pub scopes: Vec<Scope>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = "2024-05-01T12:00:00Z")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, example = "2024-08-01T12:00:00Z")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>)]
+    pub last_used_at: Option<OffsetDateTime>,
+}
+
+impl From<PgRow> for ApiToken {
+    fn from(single_row: PgRow) -> Self {
+        let id: i32 = single_row.get("id");
+        tracing::trace!(id);
+
+        let user_id: i32 = single_row.get("user_id");
+        let name: String = single_row.get("name");
+        tracing::trace!(name);
+
+        let scopes: Vec<String> = single_row.get("scopes");
+        let scopes = scopes
+            .iter()
+            .filter_map(|scope| scope.parse().ok())
+            .collect();
+
+        Self {
+            id,
+            user_id,
+            name,
+            scopes,
+            created_at: single_row.get("created_at"),
+            expires_at: single_row.get("expires_at"),
+            last_used_at: single_row.get("last_used_at"),
+        }
+    }
+}
+
+/// A request for a new API token
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct TokenRequest {
+    #[schema(example = "bulk loader")]
+    pub name: String,
+    /// Defaults to `read` only
+    #[serde(default = "TokenRequest::default_scopes")]
+    #[schema(example = r#"["write"]"#)]
+    pub scopes: Vec<Scope>,
+    /// Days until the token expires, never when missing
+    #[schema(example = 90, minimum = 1, maximum = 3650)]
+    pub expires_in_days: Option<u32>,
+}
+
+impl TokenRequest {
+    fn default_scopes() -> Vec<Scope> {
+        vec![Scope::Read]
+    }
+}
+
+/// A newly created API token. The secret is only ever shown in this response.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NewApiToken {
+    #[schema(example = "qb_Zm9vYmFy...")]
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiToken,
+}
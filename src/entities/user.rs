@@ -0,0 +1,113 @@
+use crate::entities::lib::*;
+use std::{fmt, str::FromStr};
+
+/// What a user is allowed to do. Each role can do everything the roles
+/// before it can.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Can read, but not post
+    Reader,
+    /// Can post questions and answers, and edit or delete their own
+    #[default]
+    Member,
+    /// Can also edit or delete any question or answer
+    Moderator,
+    /// Can also manage users and tags
+    Admin,
+}
+
+impl Role {
+    /// The name of the role, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Reader => "reader",
+            Role::Member => "member",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reader" => Ok(Role::Reader),
+            "member" => Ok(Role::Member),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct User {
+    #[schema(example = 1)]
+    pub id: i32,
+    #[schema(example = "robert")]
+    pub username: String,
+    #[schema(example = "member")]
+    pub role: Role,
+}
+
+impl TryFrom<PgRow> for User {
+    type Error = sqlx::Error;
+
+    /// Reads a user from a row, failing on a role this version doesn't know rather than
+    /// handing out a different one.
+    fn try_from(single_row: PgRow) -> Result<Self, Self::Error> {
+        let id: i32 = single_row.get("id");
+        tracing::trace!(id);
+
+        let username: String = single_row.get("username");
+        tracing::trace!(username);
+
+        let role: String = single_row.get("role");
+        tracing::trace!(role);
+
+        Ok(Self {
+            id,
+            username,
+            role: role
+                .parse()
+                .map_err(|e: String| sqlx::Error::Decode(e.into()))?,
+        })
+    }
+}
+
+/// A new role for a user
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RoleChange {
+    #[schema(example = "moderator")]
+    pub role: Role,
+}
+
+/// Username and password sent to register or log in
+#[derive(Clone, Deserialize, ToSchema)]
+pub struct Credentials {
+    #[schema(example = "robert")]
+    pub username: String,
+    #[schema(example = "correct horse battery staple")]
+    pub password: String,
+}
+
+impl std::fmt::Debug for Credentials {
+    /// Formats the credentials without the password, so it never ends up in the logs.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
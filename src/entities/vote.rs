@@ -0,0 +1,45 @@
+use crate::entities::lib::*;
+
+/// What is being voted on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VoteTarget {
+    Question,
+    Answer,
+}
+
+impl VoteTarget {
+    /// The table holding the voted items and their `score`.
+    pub fn table(&self) -> &'static str {
+        match self {
+            VoteTarget::Question => "questions",
+            VoteTarget::Answer => "answers",
+        }
+    }
+
+    /// The table holding the votes.
+    pub fn votes_table(&self) -> &'static str {
+        match self {
+            VoteTarget::Question => "question_votes",
+            VoteTarget::Answer => "answer_votes",
+        }
+    }
+
+    /// The column of the votes table referencing the voted item.
+    pub fn votes_column(&self) -> &'static str {
+        match self {
+            VoteTarget::Question => "question_id",
+            VoteTarget::Answer => "answer_id",
+        }
+    }
+}
+
+/// The score of a question or answer after a vote
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Vote {
+    #[schema(example = 3)]
+    pub score: i32,
+    /// The caller's vote: 1 for up, -1 for down, missing after an undo
+    #[schema(example = 1)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub my_vote: Option<i16>,
+}
@@ -0,0 +1,384 @@
+use crate::entities::{
+    answer::Answer,
+    kind::{AnswerKey, QuestionKind, SubmittedAnswer},
+};
+use std::collections::BTreeSet;
+
+/// Least similarity of a submission to the expected answer for it to count as correct
+pub const FUZZY_THRESHOLD: f32 = 0.8;
+
+/// Longest quiz submission, in characters
+pub const MAX_SUBMISSION_CHARS: usize = 1000;
+
+/// Most characters of the normalized submission and answer compared when grading text, so
+/// grading a long answer stays quick
+pub const MAX_GRADED_CHARS: usize = 2000;
+
+/// Words that don't change whether an answer is right
+const FILLER_WORDS: &[&str] = &["a", "an", "the"];
+
+/// How a submitted answer compares to the expected one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grade {
+    pub correct: bool,
+    /// From 0 for nothing in common to 1 for the same normalized text
+    pub similarity: f32,
+}
+
+/// Normalizes an answer for comparison: lowercased words of letters and digits, without
+/// Markdown or punctuation, articles dropped, separated by single spaces.
+pub fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !FILLER_WORDS.contains(&word.as_str()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// The number of single character insertions, deletions and substitutions turning `a` into
+/// `b`.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Grades a free-text answer, forgiving case, punctuation, articles and small typos.
+///
+/// # Parameters
+///
+/// * `submitted`: What the user answered.
+/// * `expected`: The stored answer.
+///
+/// # Returns
+///
+/// The similarity of the normalized texts, one minus their edit distance over the longer
+/// one's length, and whether it reaches [`FUZZY_THRESHOLD`]. Only the first
+/// [`MAX_GRADED_CHARS`] characters of each are compared.
+pub fn grade_text(submitted: &str, expected: &str) -> Grade {
+    let submitted: Vec<char> = normalize(submitted)
+        .chars()
+        .take(MAX_GRADED_CHARS)
+        .collect();
+    let expected: Vec<char> = normalize(expected).chars().take(MAX_GRADED_CHARS).collect();
+    let longest = submitted.len().max(expected.len());
+    let similarity = if longest == 0 {
+        1.0
+    } else {
+        1.0 - edit_distance(&submitted, &expected) as f32 / longest as f32
+    };
+
+    Grade {
+        correct: !submitted.is_empty() && similarity >= FUZZY_THRESHOLD,
+        similarity,
+    }
+}
+
+/// Grades picked choices: right if exactly the correct ones were picked, with the share of
+/// picked or correct choices that are both as the similarity.
+pub fn grade_choices(picked: &[usize], correct: &[usize]) -> Grade {
+    let picked: BTreeSet<&usize> = picked.iter().collect();
+    let correct: BTreeSet<&usize> = correct.iter().collect();
+    let all = picked.union(&correct).count();
+    let similarity = if all == 0 {
+        0.0
+    } else {
+        picked.intersection(&correct).count() as f32 / all as f32
+    };
+
+    Grade {
+        correct: picked == correct,
+        similarity,
+    }
+}
+
+/// Grades a number: right within `tolerance` of `value`.
+pub fn grade_number(number: f64, value: f64, tolerance: f64) -> Grade {
+    let correct = (number - value).abs() <= tolerance;
+    Grade {
+        correct,
+        similarity: if correct { 1.0 } else { 0.0 },
+    }
+}
+
+/// Reads the choice a text names: its text, or else its position, so choices that are
+/// numbers are picked by their text.
+fn parse_choice(text: &str, choices: &[String]) -> Option<usize> {
+    let text = text.trim();
+    let normalized = normalize(text);
+    choices
+        .iter()
+        .position(|choice| choice.trim().to_lowercase() == text.to_lowercase())
+        .or_else(|| {
+            choices
+                .iter()
+                .position(|choice| !normalized.is_empty() && normalize(choice) == normalized)
+        })
+        .or_else(|| {
+            text.parse::<usize>()
+                .ok()
+                .filter(|index| *index < choices.len())
+        })
+}
+
+/// Reads true or false from a text.
+fn parse_boolean(text: &str) -> Option<bool> {
+    match normalize(text).as_str() {
+        "true" | "yes" | "t" | "y" => Some(true),
+        "false" | "no" | "f" | "n" => Some(false),
+        _ => None,
+    }
+}
+
+/// Reads a number, followed by the question's unit if it has one, from a text.
+fn parse_number(text: &str, unit: Option<&str>) -> Option<f64> {
+    let text = text.trim();
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+        .unwrap_or(text.len());
+    let (number, rest) = text.split_at(end);
+    let rest = rest.trim();
+    let unit_matches = match unit {
+        Some(unit) => rest.is_empty() || rest.eq_ignore_ascii_case(unit),
+        None => rest.is_empty(),
+    };
+    number.parse::<f64>().ok().filter(|_| unit_matches)
+}
+
+/// Grades a quiz submission against the answer of a question, by the question's kind.
+///
+/// # Parameters
+///
+/// * `kind`: The kind of the question.
+/// * `answer`: The answer graded against. Its key is used for kinds other than free text,
+///   its text for free text or when it has no key.
+/// * `submitted`: What the user answered.
+///
+/// # Returns
+///
+/// The grade, or why the submission doesn't fit the kind of question.
+pub fn grade(
+    kind: &QuestionKind,
+    answer: &Answer,
+    submitted: &SubmittedAnswer,
+) -> Result<Grade, String> {
+    let unfit = || format!("that isn't an answer to a {} question", kind.name());
+    match (kind, &answer.key) {
+        (QuestionKind::MultipleChoice { choices }, Some(AnswerKey::MultipleChoice { correct })) => {
+            let picked = match submitted {
+                SubmittedAnswer::Choices(picked) => picked.clone(),
+                SubmittedAnswer::Number(number) if number.fract() == 0.0 && *number >= 0.0 => {
+                    vec![*number as usize]
+                }
+                SubmittedAnswer::Text(text) => vec![parse_choice(text, choices).ok_or_else(unfit)?],
+                _ => return Err(unfit()),
+            };
+            if picked.iter().any(|index| *index >= choices.len()) {
+                return Err(format!("choices are between 0 and {}", choices.len() - 1));
+            }
+            Ok(grade_choices(&picked, correct))
+        }
+        (QuestionKind::TrueFalse, Some(AnswerKey::TrueFalse { correct })) => {
+            let value = match submitted {
+                SubmittedAnswer::Boolean(value) => *value,
+                SubmittedAnswer::Text(text) => parse_boolean(text).ok_or_else(unfit)?,
+                _ => return Err(unfit()),
+            };
+            Ok(Grade {
+                correct: value == *correct,
+                similarity: if value == *correct { 1.0 } else { 0.0 },
+            })
+        }
+        (QuestionKind::Numeric { unit }, Some(AnswerKey::Numeric { value, tolerance })) => {
+            let number = match submitted {
+                SubmittedAnswer::Number(number) => *number,
+                SubmittedAnswer::Text(text) => {
+                    parse_number(text, unit.as_deref()).ok_or_else(unfit)?
+                }
+                _ => return Err(unfit()),
+            };
+            Ok(grade_number(number, *value, *tolerance))
+        }
+        _ => Ok(grade_text(&submitted.to_text(), &answer.answer)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_drops_case_punctuation_and_articles() {
+        assert_eq!(normalize("The Eiffel Tower!"), "eiffel tower");
+        assert_eq!(normalize("  An apple, a day... "), "apple day");
+        assert_eq!(normalize("**Paris**\n\n`rust`"), "paris rust");
+        assert_eq!(normalize("Theory of a thing"), "theory of thing");
+        assert_eq!(normalize("?!"), "");
+    }
+
+    #[test]
+    fn same_answer_up_to_normalization_is_exact() {
+        let grade = grade_text("the PARIS.", "Paris");
+        assert!(grade.correct);
+        assert_eq!(grade.similarity, 1.0);
+    }
+
+    #[test]
+    fn small_typos_are_forgiven() {
+        let grade = grade_text("Gutenburg", "Gutenberg");
+        assert!(grade.correct);
+        assert!(grade.similarity < 1.0);
+        assert!((grade.similarity - 8.0 / 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn different_answers_are_wrong() {
+        let grade = grade_text("Lyon", "Paris");
+        assert!(!grade.correct);
+        assert!(grade.similarity < FUZZY_THRESHOLD);
+    }
+
+    #[test]
+    fn empty_submission_is_never_correct() {
+        let grade = grade_text("", "");
+        assert!(!grade.correct);
+        assert_eq!(grade.similarity, 1.0);
+
+        let grade = grade_text("the", "a");
+        assert!(!grade.correct);
+
+        let grade = grade_text("", "Paris");
+        assert!(!grade.correct);
+        assert_eq!(grade.similarity, 0.0);
+    }
+
+    #[test]
+    fn long_texts_are_compared_up_to_the_cap() {
+        let long = "word ".repeat(MAX_GRADED_CHARS);
+        let grade = grade_text(&long, &format!("{long} and more"));
+        assert!(grade.correct);
+        assert_eq!(grade.similarity, 1.0);
+    }
+
+    fn answer(text: &str, key: Option<AnswerKey>) -> Answer {
+        let mut answer = Answer::new(None, text, Some(1));
+        answer.key = key;
+        answer
+    }
+
+    fn text(text: &str) -> SubmittedAnswer {
+        SubmittedAnswer::Text(text.to_string())
+    }
+
+    #[test]
+    fn choices_are_picked_by_text_before_position() {
+        let choices: Vec<String> = ["1", "2", "3"].iter().map(|c| c.to_string()).collect();
+        assert_eq!(parse_choice("2", &choices), Some(1));
+
+        let choices: Vec<String> = ["Paris", "Lyon", "A"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(parse_choice(" the lyon! ", &choices), Some(1));
+        assert_eq!(parse_choice("a", &choices), Some(2));
+        assert_eq!(parse_choice("0", &choices), Some(0));
+        assert_eq!(parse_choice("3", &choices), None);
+        assert_eq!(parse_choice("Marseille", &choices), None);
+    }
+
+    #[test]
+    fn numbers_are_read_with_their_unit() {
+        assert_eq!(parse_number(" 9.81 ", None), Some(9.81));
+        assert_eq!(parse_number("-1e3", None), Some(-1000.0));
+        assert_eq!(parse_number("9.81 m/s²", Some("m/s²")), Some(9.81));
+        assert_eq!(parse_number("9.81 M/S²", Some("m/s²")), Some(9.81));
+        assert_eq!(parse_number("9.81", Some("m/s²")), Some(9.81));
+        assert_eq!(parse_number("9.81 km", Some("m")), None);
+        assert_eq!(parse_number("9.81 m", None), None);
+        assert_eq!(parse_number("nine", None), None);
+    }
+
+    #[test]
+    fn multiple_choice_grades() {
+        let kind = QuestionKind::MultipleChoice {
+            choices: ["1", "2", "3"].iter().map(|c| c.to_string()).collect(),
+        };
+        let key = answer("2", Some(AnswerKey::MultipleChoice { correct: vec![1] }));
+
+        assert!(grade(&kind, &key, &text("2")).unwrap().correct);
+        assert!(
+            grade(&kind, &key, &SubmittedAnswer::Number(1.0))
+                .unwrap()
+                .correct
+        );
+        assert!(
+            grade(&kind, &key, &SubmittedAnswer::Choices(vec![1]))
+                .unwrap()
+                .correct
+        );
+
+        let both = grade(&kind, &key, &SubmittedAnswer::Choices(vec![0, 1])).unwrap();
+        assert!(!both.correct);
+        assert_eq!(both.similarity, 0.5);
+
+        assert!(grade(&kind, &key, &SubmittedAnswer::Choices(vec![3])).is_err());
+        assert!(grade(&kind, &key, &SubmittedAnswer::Boolean(true)).is_err());
+        assert!(grade(&kind, &key, &text("seven")).is_err());
+    }
+
+    #[test]
+    fn true_false_grades() {
+        let key = answer("True", Some(AnswerKey::TrueFalse { correct: true }));
+        let kind = QuestionKind::TrueFalse;
+        assert!(
+            grade(&kind, &key, &SubmittedAnswer::Boolean(true))
+                .unwrap()
+                .correct
+        );
+        assert!(grade(&kind, &key, &text("Yes")).unwrap().correct);
+        assert!(!grade(&kind, &key, &text("no")).unwrap().correct);
+        assert!(grade(&kind, &key, &text("maybe")).is_err());
+    }
+
+    #[test]
+    fn numeric_grades_within_the_tolerance() {
+        let key = answer(
+            "9.81",
+            Some(AnswerKey::Numeric {
+                value: 9.81,
+                tolerance: 0.05,
+            }),
+        );
+        let kind = QuestionKind::Numeric {
+            unit: Some("m/s²".to_string()),
+        };
+        assert!(
+            grade(&kind, &key, &SubmittedAnswer::Number(9.8))
+                .unwrap()
+                .correct
+        );
+        assert!(grade(&kind, &key, &text("9.85 m/s²")).unwrap().correct);
+        assert!(!grade(&kind, &key, &text("9.9")).unwrap().correct);
+        assert!(grade(&kind, &key, &text("9.8 km")).is_err());
+    }
+
+    #[test]
+    fn answers_without_a_key_grade_as_text() {
+        let key = answer("Paris", None);
+        let kind = QuestionKind::TrueFalse;
+        assert!(grade(&kind, &key, &text("paris")).unwrap().correct);
+        assert!(
+            grade(&QuestionKind::FreeText, &key, &text("Pariss"))
+                .unwrap()
+                .correct
+        );
+    }
+}
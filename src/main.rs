@@ -1,15 +1,38 @@
+mod auth;
 mod config;
 mod controllers;
 mod db_config;
+mod deck;
 mod entities;
+mod grading;
+mod markdown;
 mod models;
+mod negotiate;
 mod pagination;
+mod purge;
 mod repositories;
+mod scheduling;
+mod views;
 
 use config::*;
 
 use crate::controllers::answer_controller::*;
+use crate::controllers::auth_controller::*;
+use crate::controllers::bulk_controller::*;
+use crate::controllers::closure_controller::*;
+use crate::controllers::comment_controller::*;
+use crate::controllers::deck_controller::*;
+use crate::controllers::page_controller::*;
 use crate::controllers::question_controller::*;
+use crate::controllers::quiz_controller::*;
+use crate::controllers::review_controller::*;
+use crate::controllers::revision_controller::*;
+use crate::controllers::search_controller::*;
+use crate::controllers::tag_controller::*;
+use crate::controllers::token_controller::*;
+use crate::controllers::trash_controller::*;
+use crate::controllers::user_controller::*;
+use crate::controllers::vote_controller::*;
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
@@ -61,18 +84,139 @@ async fn main() {
 
     // Connect to database
     let questionsbank = Arc::new(RwLock::new(QuestionBank::new().await.unwrap()));
+    if let Err(e) = seed_admin_from_env(&*questionsbank.read().await).await {
+        tracing::error!("Seeding the admin failed: {}", e);
+        std::process::exit(1);
+    }
+
+    // Purge the trash in the background
+    let retention = match purge::retention_from_env() {
+        Ok(retention) => retention,
+        Err(e) => {
+            tracing::error!("Reading the trash retention failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    tokio::spawn(purge::purge_periodically(questionsbank.clone(), retention));
 
     // routes with their handlers
     let apis = Router::new()
         .route("/questions", get(questions))
         .route("/questions/:id", get(get_question))
         .route("/questions/add", post(post_question))
+        .route("/questions/similar", post(similar_questions))
         .route("/questions/:id", delete(delete_question))
         .route("/questions/:id", put(update_question))
-        .route("/questions/:id/answer", get(get_answer))
-        .route("/questions/:id/answer", post(post_answer))
-        .route("/questions/:id/answer", delete(delete_answer))
-        .route("/questions/:id/answer", put(update_answer));
+        .route("/questions/:id/answers", get(get_answers))
+        .route("/questions/:id/answers", post(post_answer))
+        .route("/questions/:id/upvote", post(upvote_question))
+        .route("/questions/:id/downvote", post(downvote_question))
+        .route("/questions/:id/vote", delete(unvote_question))
+        .route("/questions/:id/revisions", get(get_question_revisions))
+        .route(
+            "/questions/:id/revisions/diff",
+            get(diff_question_revisions),
+        )
+        .route(
+            "/questions/:id/revisions/:revision/rollback",
+            post(rollback_question),
+        )
+        .route("/questions/:id/restore", post(restore_question))
+        .route("/questions/:id/close", post(close_question))
+        .route("/questions/:id/reopen", post(reopen_question))
+        .route("/questions/:id/closures", get(get_question_closures))
+        .route(
+            "/questions/:id/comments",
+            get(get_question_comments).post(post_question_comment),
+        )
+        .route(
+            "/questions/:id/comments/:comment_id",
+            get(get_question_comment)
+                .put(update_question_comment)
+                .delete(delete_question_comment),
+        )
+        .route("/answers/:answer_id", get(get_answer))
+        .route("/answers/:answer_id", delete(delete_answer))
+        .route("/answers/:answer_id", put(update_answer))
+        .route("/answers/:answer_id/accept", put(accept_answer))
+        .route("/answers/:answer_id/accept", delete(unaccept_answer))
+        .route("/answers/:answer_id/upvote", post(upvote_answer))
+        .route("/answers/:answer_id/downvote", post(downvote_answer))
+        .route("/answers/:answer_id/vote", delete(unvote_answer))
+        .route("/answers/:answer_id/revisions", get(get_answer_revisions))
+        .route(
+            "/answers/:answer_id/revisions/diff",
+            get(diff_answer_revisions),
+        )
+        .route(
+            "/answers/:answer_id/revisions/:revision/rollback",
+            post(rollback_answer),
+        )
+        .route("/answers/:answer_id/restore", post(restore_answer))
+        .route(
+            "/answers/:answer_id/comments",
+            get(get_answer_comments).post(post_answer_comment),
+        )
+        .route(
+            "/answers/:answer_id/comments/:comment_id",
+            get(get_answer_comment)
+                .put(update_answer_comment)
+                .delete(delete_answer_comment),
+        )
+        .route("/search", get(search))
+        .route("/quiz/sessions", post(start_quiz))
+        .route("/quiz/sessions/:id", get(get_quiz_session))
+        .route("/quiz/sessions/:id/next", get(next_quiz_question))
+        .route("/quiz/sessions/:id/answer", post(answer_quiz_question))
+        .route("/review/due", get(due_reviews))
+        .route("/deck/import", post(import_deck))
+        .route("/deck/export", get(export_deck))
+        .route("/import", post(import_questions))
+        .route("/export", get(export_questions))
+        .route("/trash/questions", get(get_trashed_questions))
+        .route("/trash/answers", get(get_trashed_answers))
+        .route("/auth/register", post(register))
+        .route("/auth/login", post(login))
+        .route("/auth/logout", post(logout))
+        .route("/auth/me", get(me))
+        .route("/users", get(get_users))
+        .route("/users/:user_id", delete(delete_user))
+        .route("/users/:user_id/role", put(set_user_role))
+        .route("/tags", get(get_tags))
+        .route("/tags/:name", get(get_tag))
+        .route("/tags/:name", put(update_tag))
+        .route("/tags/:name", delete(delete_tag))
+        .route("/tags/:name/synonyms", post(add_synonym))
+        .route("/tags/:name/synonyms/:synonym", delete(delete_synonym))
+        .route("/tags/:name/merge", post(merge_tag))
+        .route("/tokens", get(get_tokens))
+        .route("/tokens", post(create_token))
+        .route("/tokens/:token_id", delete(revoke_token));
+
+    let pages = Router::new()
+        .route("/", get(home))
+        .route("/highlight.css", get(highlight_css))
+        .route("/questions", get(questions))
+        .route(
+            "/questions/new",
+            get(new_question_page).post(create_question),
+        )
+        .route("/questions/:id", get(get_question))
+        .route(
+            "/questions/:id/edit",
+            get(edit_question_page).post(update_question_form),
+        )
+        .route("/questions/:id/answers", post(create_answer))
+        .route("/questions/:id/comments", post(create_question_comment))
+        .route("/answers/:answer_id/comments", post(create_answer_comment))
+        .route(
+            "/answers/:answer_id/edit",
+            get(edit_answer_page).post(update_answer_form),
+        )
+        .route("/tags", get(tags_page))
+        .route("/tags/:name", get(tag_page))
+        .route("/login", get(login_page).post(login_form))
+        .route("/logout", post(logout_form));
 
     // handy openai auto generated docs!
     let swagger_ui = SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi());
@@ -84,6 +228,7 @@ async fn main() {
     let rapidoc_ui2 = RapiDoc::new("/api-docs/openapi.json2").path("/rapidoc2");
 
     let app = Router::new()
+        .merge(pages)
         .nest("/api/v1", apis)
         .merge(swagger_ui)
         .merge(redoc_ui)
@@ -0,0 +1,187 @@
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use std::sync::OnceLock;
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+/// How highlighted tokens are marked up: `<span class="hl-keyword hl-rust">`
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// The theme the highlighting stylesheet is generated from
+const THEME: &str = "InspiredGitHub";
+
+/// The syntaxes known to the highlighter, loaded on first use
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// The HTML allowed in rendered bodies: ammonia's defaults, which drop scripts, styles and
+/// event handlers, plus the classes the highlighter puts on code
+fn sanitizer() -> &'static ammonia::Builder<'static> {
+    static SANITIZER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    SANITIZER.get_or_init(|| {
+        let mut builder = ammonia::Builder::default();
+        builder
+            .add_tag_attributes("pre", &["class"])
+            .add_tag_attributes("span", &["class"]);
+        builder
+    })
+}
+
+/// Renders a Markdown question or answer body to sanitized HTML.
+///
+/// Fenced code blocks are highlighted according to their language, see [`highlight_css`].
+/// Raw HTML in the source is kept only if the sanitizer allows it.
+///
+/// # Parameters
+///
+/// * `source`: The Markdown text.
+///
+/// # Returns
+///
+/// HTML that is safe to embed in a page.
+pub fn render(source: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut events = vec![];
+    let mut code_block: Option<(String, String)> = None;
+    for event in Parser::new_ext(source, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, code)) = code_block.take() {
+                    events.push(Event::Html(highlight(&language, &code).into()));
+                }
+            }
+            event => events.push(event),
+        }
+    }
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events.into_iter());
+    sanitizer().clean(&unsafe_html).to_string()
+}
+
+/// Highlights a code block, falling back to plain text for unknown languages.
+fn highlight(language: &str, code: &str) -> String {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        if generator
+            .parse_html_for_line_which_includes_newline(line)
+            .is_err()
+        {
+            let mut escaped = String::new();
+            html::push_html(&mut escaped, [Event::Text(code.into())].into_iter());
+            return format!(r#"<pre class="highlight"><code>{escaped}</code></pre>"#);
+        }
+    }
+    format!(
+        r#"<pre class="highlight"><code>{}</code></pre>"#,
+        generator.finalize()
+    )
+}
+
+/// The stylesheet for the classes of highlighted code.
+pub fn highlight_css() -> &'static str {
+    static CSS: OnceLock<String> = OnceLock::new();
+    CSS.get_or_init(|| {
+        let themes = ThemeSet::load_defaults();
+        themes
+            .themes
+            .get(THEME)
+            .and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_markdown() {
+        assert_eq!(
+            render("Some *emphasis* and `code`"),
+            "<p>Some <em>emphasis</em> and <code>code</code></p>\n"
+        );
+    }
+
+    #[test]
+    fn strips_scripts() {
+        let html = render("Hi <script>alert(1)</script>\n\n<script>alert(2)</script>");
+        assert!(!html.contains("<script"), "{html}");
+        assert!(!html.contains("alert"), "{html}");
+    }
+
+    #[test]
+    fn strips_event_handlers() {
+        let html = render(r#"<img src="x.png" onerror="alert(1)"> <b onclick="alert(2)">b</b>"#);
+        assert!(!html.contains("onerror"), "{html}");
+        assert!(!html.contains("onclick"), "{html}");
+        assert!(html.contains("<b>b</b>"), "{html}");
+    }
+
+    #[test]
+    fn strips_javascript_links() {
+        for source in [
+            "[click](javascript:alert(1))",
+            r#"<a href="javascript:alert(1)">click</a>"#,
+            r#"<a href="JaVaScRiPt:alert(1)">click</a>"#,
+        ] {
+            let html = render(source);
+            assert!(!html.to_lowercase().contains("javascript:"), "{html}");
+        }
+    }
+
+    #[test]
+    fn highlights_fenced_code() {
+        let html = render("```rust\nfn main() {}\n```");
+        assert!(
+            html.starts_with(r#"<pre class="highlight"><code>"#),
+            "{html}"
+        );
+        assert!(html.contains(r#"class="hl-"#), "{html}");
+        assert!(html.contains("hl-rust"), "{html}");
+    }
+
+    #[test]
+    fn escapes_code_of_unknown_languages() {
+        let html = render("```nosuchlanguage\n<script>alert(1)</script>\n```");
+        assert!(html.contains(r#"<pre class="highlight">"#), "{html}");
+        assert!(!html.contains("<script"), "{html}");
+        assert!(html.contains("&lt;script&gt;"), "{html}");
+    }
+
+    #[test]
+    fn keeps_highlight_classes_but_not_styles() {
+        let html = render(r#"<span class="hl-keyword">a</span><div style="color: red">b</div>"#);
+        assert!(html.contains(r#"<span class="hl-keyword">"#), "{html}");
+        assert!(!html.contains("style="), "{html}");
+    }
+}
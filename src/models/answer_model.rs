@@ -1,4 +1,108 @@
-use crate::{entities::answer::*, models::lib::*};
+use crate::{
+    entities::answer::*,
+    markdown,
+    models::{lib::*, revision_model},
+    pagination::{AnswerSort, Keyed, Order, Page, PageQuery, SortKey},
+};
+
+/// The ordering of a question's answers: by the sort key, then in the order they were added.
+///
+/// # Parameters
+///
+/// * `sort`: How the answers are sorted.
+pub fn order(sort: AnswerSort) -> Order {
+    let (name, key) = match sort {
+        AnswerSort::Accepted => ("accepted", "accepted"),
+        AnswerSort::Score => ("score", "score"),
+        AnswerSort::Newest => (
+            "newest",
+            "(EXTRACT(EPOCH FROM created_at) * 1000000)::bigint",
+        ),
+        AnswerSort::Updated => (
+            "updated",
+            "(EXTRACT(EPOCH FROM updated_at) * 1000000)::bigint",
+        ),
+    };
+    Order {
+        name,
+        key: Some(key),
+        key_desc: true,
+        id: "id",
+        id_desc: false,
+    }
+}
+
+/// Retrieves a page of the answers to a question.
+///
+/// # Parameters
+///
+/// * `question_id`: The ID of the question.
+/// * `page`: Which page to retrieve, by offset or by cursor.
+/// * `sort`: How the answers are sorted.
+///
+/// # Returns
+///
+/// The page of the question's answers, in [`order`].
+pub async fn get_all(
+    answers: &Pool<Postgres>,
+    question_id: i32,
+    page: &PageQuery,
+    sort: AnswerSort,
+) -> Result<Page<Answer>, Box<dyn Error>> {
+    let order = order(sort);
+    let row = sqlx::query(
+        r#"SELECT COUNT(*) FROM answers WHERE question_id = $1 AND deleted_at IS NULL;"#,
+    )
+    .bind(question_id)
+    .fetch_one(answers)
+    .await?;
+    let total_answers: i64 = row.get(0);
+
+    let mut query = QueryBuilder::new(
+        "SELECT id, answer, answer_html, key, question_id, accepted, author_id, score, created_at, \
+        updated_at",
+    );
+    if let Some(key) = order.key {
+        query.push(", ").push(key).push(" AS sort_key");
+    }
+    query.push(
+        r#"
+        FROM answers
+        WHERE deleted_at IS NULL AND question_id = "#,
+    );
+    query.push_bind(question_id);
+    order.push_keyset(&mut query, &page.position);
+    order.push_order_limit(&mut query, page);
+    let rows = query.build().fetch_all(answers).await?;
+
+    let mut answer_vec: Vec<Keyed<Answer>> = Vec::new();
+    for row in rows {
+        answer_vec.push(Keyed {
+            key: SortKey::from_row(&row),
+            id: row.get("id"),
+            item: <Answer as std::convert::From<PgRow>>::from(row),
+        });
+    }
+
+    Ok(page.page(&order, answer_vec, total_answers))
+}
+
+/// Checks whether a question has any answer, including answers in the trash.
+///
+/// # Parameters
+///
+/// * `question_id`: The ID of the question.
+pub async fn has_answers(
+    answers: &Pool<Postgres>,
+    question_id: i32,
+) -> Result<bool, Box<dyn Error>> {
+    let row = sqlx::query("SELECT EXISTS (SELECT 1 FROM answers WHERE question_id = $1)")
+        .bind(question_id)
+        .fetch_one(answers)
+        .await?;
+
+    Ok(row.get(0))
+}
 
 /// Retrieves an answer by its ID.
 ///
@@ -8,18 +112,20 @@ use crate::{entities::answer::*, models::lib::*};
 ///
 /// # Returns
 ///
-/// An instance of an answer with the specified question ID, or a `QuestionBankErr` error if the answer does not exist.
+/// The answer with the specified ID, or an error if the answer does not exist.
 pub async fn get(answers: &Pool<Postgres>, index: i32) -> Result<Answer, Box<dyn Error>> {
     let answer = sqlx::query(
         r#"
-        SELECT id, answer, question_id
+        SELECT id, answer, answer_html, key, question_id, accepted, author_id, score, created_at,
+            updated_at
         FROM answers
-        WHERE question_id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
     )
     .bind(index)
-    .fetch_one(answers)
-    .await?;
+    .fetch_optional(answers)
+    .await?
+    .ok_or_else(|| QuestionBankErr::AnswerDoesNotExist(index.to_string()))?;
 
     Ok(<Answer as std::convert::From<PgRow>>::from(answer))
 }
@@ -32,83 +138,235 @@ pub async fn get(answers: &Pool<Postgres>, index: i32) -> Result<Answer, Box<dyn
 ///
 /// # Returns
 ///
-/// A `Result` indicating whether the answer was added successfully.
-/// If the answer already exists, returns a `QuestionBankErr` error.
-/// TODO maybe overwrite the answer if it exists?
-pub async fn add(answers: &Pool<Postgres>, answer: Answer) -> Result<(), Box<dyn Error>> {
-    let answer_to_insert =
-        sqlx::query(r#"INSERT INTO answers (answer, question_id) VALUES ($1, $2) RETURNING id"#)
-            .bind(answer.answer)
-            .bind(answer.question_id)
-            .fetch_one(answers)
-            .await?;
+/// The ID of the new answer.
+/// If the question does not exist, returns an error.
+pub async fn add(answers: &Pool<Postgres>, answer: Answer) -> Result<i32, Box<dyn Error>> {
+    let mut tx = answers.begin().await?;
+    let answer_id = insert(&mut tx, answer).await?;
+    tx.commit().await?;
+
+    Ok(answer_id)
+}
+
+/// Writes a new answer and its first revision within a transaction, see [`add`].
+///
+/// # Returns
+///
+/// The ID of the new answer.
+pub async fn insert(
+    tx: &mut Transaction<'_, Postgres>,
+    answer: Answer,
+) -> Result<i32, Box<dyn Error>> {
+    let answer_to_insert = sqlx::query(
+        r#"
+        INSERT INTO answers (answer, answer_html, question_id, author_id, key)
+        SELECT $1, $2, id, $4, $5 FROM questions
+        WHERE id = $3 AND deleted_at IS NULL
+        RETURNING id"#,
+    )
+    .bind(&answer.answer)
+    .bind(markdown::render(&answer.answer))
+    .bind(answer.question_id)
+    .bind(answer.author_id)
+    .bind(answer.key.map(sqlx::types::Json))
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| {
+        QuestionBankErr::DoesNotExist(
+            answer
+                .question_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        )
+    })?;
 
-    let question_id: i32 = answer_to_insert.get(0);
+    let answer_id: i32 = answer_to_insert.get(0);
+    revision_model::record_answer(&mut **tx, answer_id, answer.author_id, &answer.answer).await?;
+    touch_question(tx, answer.question_id).await?;
     tracing::debug!(
-        "ID of the question the answer was added to: {}",
-        question_id
+        "Added answer {} to question {:?}",
+        answer_id,
+        answer.question_id
     );
 
+    Ok(answer_id)
+}
+
+/// Moves the last activity time of the question an answer belongs to to now.
+async fn touch_question(
+    tx: &mut Transaction<'_, Postgres>,
+    question_id: Option<i32>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE questions SET last_activity_at = now() WHERE id = $1")
+        .bind(question_id)
+        .execute(&mut **tx)
+        .await?;
     Ok(())
 }
 
-/// Removes an answer by associated with a question
+/// Moves an answer to the trash, see [`crate::models::trash_model`].
 ///
 /// # Parameters
 ///
-/// * `index`: The ID of the question.
+/// * `index`: The ID of the answer.
 ///
 /// # Returns
 ///
 /// A `Result` indicating whether the answer was removed successfully.
 /// If the answer does not exist, returns a `QuestionBankErr` error.
-/// TODO need to look into what is expected here
 pub async fn delete(answers: &Pool<Postgres>, index: i32) -> Result<(), Box<dyn Error>> {
-    sqlx::query(
+    let result = sqlx::query(
         r#"
-        DELETE FROM answers
-        WHERE question_id = $1
-        ;"#,
+        UPDATE answers SET deleted_at = now()
+        WHERE id = $1 AND deleted_at IS NULL;"#,
     )
     .bind(index)
     .execute(answers)
     .await?;
 
+    if result.rows_affected() == 0 {
+        return Err(Box::new(QuestionBankErr::AnswerDoesNotExist(
+            index.to_string(),
+        )));
+    }
+
     Ok(())
 }
 
-/// Updates an answer associated with a question.
+/// Updates the text of an answer.
 ///
 /// # Parameters
 ///
-/// * `index`: The ID of the question to update.
+/// * `index`: The ID of the answer to update.
 /// * `answer`: The updated `Answer` instance.
+/// * `editor_id`: The user making the change, recorded in the new revision.
 ///
 /// # Returns
 ///
-/// A `Result` indicating whether the question's answer was updated successfully.
-/// If the question does not exist or is unprocessable, returns a `QuestionBankErr` error.
-/// If successful, returns a `StatusCode` of 200.
+/// The updated answer, or a `QuestionBankErr` error if the answer does not exist.
 pub async fn update(
     answers: &Pool<Postgres>,
     index: i32,
     answer: Answer,
+    editor_id: Option<i32>,
 ) -> Result<Answer, Box<dyn Error>> {
+    let key = answer.key;
     let answer = answer.answer;
 
     let mut answer_to_update = get(answers, index).await?;
     answer_to_update.answer.clone_from(&answer);
+    answer_to_update.render();
+    if key.is_some() {
+        answer_to_update.key = key;
+    }
+
+    let mut tx = answers.begin().await?;
+    let updated = sqlx::query(
+        r#"
+        UPDATE answers
+        SET answer = $1, answer_html = $2, key = $4, updated_at = now()
+        WHERE id = $3
+        RETURNING updated_at;"#,
+    )
+    .bind(&answer)
+    .bind(&answer_to_update.answer_html)
+    .bind(index)
+    .bind(answer_to_update.key.as_ref().map(sqlx::types::Json))
+    .fetch_one(&mut *tx)
+    .await?;
+    answer_to_update.updated_at = updated.get("updated_at");
+
+    revision_model::record_answer(&mut *tx, index, editor_id, &answer).await?;
+    touch_question(&mut tx, answer_to_update.question_id).await?;
+    tx.commit().await?;
+
+    Ok(answer_to_update)
+}
+
+/// Marks an answer as the accepted answer of its question.
+///
+/// Any other answer to the same question stops being accepted.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the answer to accept.
+///
+/// # Returns
+///
+/// The accepted answer, or a `QuestionBankErr` error if the answer does not exist.
+pub async fn accept(answers: &Pool<Postgres>, index: i32) -> Result<Answer, Box<dyn Error>> {
+    let mut answer_to_accept = get(answers, index).await?;
+
+    let mut tx = answers.begin().await?;
+    sqlx::query(
+        r#"
+        UPDATE answers
+        SET accepted = false
+        WHERE question_id = $1 AND accepted AND id <> $2;"#,
+    )
+    .bind(answer_to_accept.question_id)
+    .bind(index)
+    .execute(&mut *tx)
+    .await?;
 
     sqlx::query(
         r#"
         UPDATE answers
-        SET answer = $1
-        WHERE question_id = $2;"#,
+        SET accepted = true
+        WHERE id = $1;"#,
+    )
+    .bind(index)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    answer_to_accept.accepted = true;
+    Ok(answer_to_accept)
+}
+
+/// Removes the accepted mark from an answer.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the answer.
+///
+/// # Returns
+///
+/// The updated answer, or a `QuestionBankErr` error if the answer does not exist.
+pub async fn unaccept(answers: &Pool<Postgres>, index: i32) -> Result<Answer, Box<dyn Error>> {
+    let mut answer_to_update = get(answers, index).await?;
+
+    sqlx::query(
+        r#"
+        UPDATE answers
+        SET accepted = false
+        WHERE id = $1;"#,
     )
-    .bind(answer)
     .bind(index)
     .execute(answers)
     .await?;
 
+    answer_to_update.accepted = false;
     Ok(answer_to_update)
 }
+
+/// Renders the HTML of the answers stored before Markdown was rendered on write.
+///
+/// # Returns
+///
+/// The number of answers rendered.
+pub async fn render_missing(answers: &Pool<Postgres>) -> Result<u64, Box<dyn Error>> {
+    let rows = sqlx::query(r#"SELECT id, answer FROM answers WHERE answer_html IS NULL"#)
+        .fetch_all(answers)
+        .await?;
+    for row in &rows {
+        let answer: String = row.get("answer");
+        sqlx::query(r#"UPDATE answers SET answer_html = $1 WHERE id = $2"#)
+            .bind(markdown::render(&answer))
+            .bind(row.get::<i32, _>("id"))
+            .execute(answers)
+            .await?;
+    }
+
+    Ok(rows.len() as u64)
+}
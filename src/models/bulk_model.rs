@@ -0,0 +1,42 @@
+use crate::{
+    entities::bulk::BulkQuestion,
+    models::{answer_model, lib::*, question_model},
+};
+
+/// Adds a batch of questions along with their answers, all or none.
+///
+/// Every question and answer is written like [`question_model::add`] and
+/// [`answer_model::add`] would, within one transaction.
+///
+/// # Parameters
+///
+/// * `author_id`: The user importing the questions.
+/// * `questions`: The validated questions.
+///
+/// # Returns
+///
+/// The IDs of the added questions, in the order of the batch.
+pub async fn insert(
+    questions: &Pool<Postgres>,
+    author_id: i32,
+    batch: &[BulkQuestion],
+) -> Result<Vec<i32>, Box<dyn Error>> {
+    let mut tx = questions.begin().await?;
+    let mut question_ids = Vec::with_capacity(batch.len());
+    for bulk in batch {
+        let question_id = question_model::insert(&mut tx, bulk.question(author_id)).await?;
+        question_ids.push(question_id);
+        for (answer, accepted) in bulk.answers(author_id, question_id) {
+            let answer_id = answer_model::insert(&mut tx, answer).await?;
+            if accepted {
+                sqlx::query("UPDATE answers SET accepted = true WHERE id = $1")
+                    .bind(answer_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    Ok(question_ids)
+}
@@ -0,0 +1,187 @@
+use crate::{
+    entities::{closure::*, question::Question},
+    models::{lib::*, question_model},
+};
+use time::OffsetDateTime;
+
+/// Columns of a `ClosureEvent`
+const CLOSURE_COLUMNS: &str =
+    "question_id, action, reason, note, duplicate_of, user_id, created_at";
+
+/// Locks a live question for a close or reopen.
+///
+/// # Returns
+///
+/// When the question was closed, if it is, or a `QuestionBankErr` error if the question does
+/// not exist.
+async fn lock(
+    tx: &mut Transaction<'_, Postgres>,
+    index: i32,
+) -> Result<Option<OffsetDateTime>, Box<dyn Error>> {
+    let row = sqlx::query(
+        r#"SELECT closed_at FROM questions WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"#,
+    )
+    .bind(index)
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
+
+    Ok(row.get("closed_at"))
+}
+
+/// Stores a close or reopen in the question's history.
+async fn record(
+    tx: &mut Transaction<'_, Postgres>,
+    index: i32,
+    action: ClosureAction,
+    reason: Option<CloseReason>,
+    note: &Option<String>,
+    duplicate_of: Option<i32>,
+    user_id: Option<i32>,
+) -> Result<(), Box<dyn Error>> {
+    sqlx::query(
+        r#"
+        INSERT INTO question_closures (question_id, action, reason, note, duplicate_of, user_id)
+        VALUES ($1, $2, $3, $4, $5, $6)"#,
+    )
+    .bind(index)
+    .bind(action.as_str())
+    .bind(reason.as_ref().map(CloseReason::as_str))
+    .bind(note)
+    .bind(duplicate_of)
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Closes a question.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the question.
+/// * `request`: Why the question is closed.
+/// * `user_id`: The moderator closing it.
+///
+/// # Returns
+///
+/// The closed question, or a `QuestionBankErr` error if the question does not exist or is
+/// already closed.
+pub async fn close(
+    questions: &Pool<Postgres>,
+    index: i32,
+    request: &CloseRequest,
+    user_id: Option<i32>,
+) -> Result<Question, Box<dyn Error>> {
+    let mut tx = questions.begin().await?;
+    if lock(&mut tx, index).await?.is_some() {
+        return Err(Box::new(QuestionBankErr::Closed(format!(
+            "Question {index}"
+        ))));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE questions
+        SET closed_at = now(), close_reason = $2, close_note = $3, duplicate_of = $4,
+            closed_by = $5
+        WHERE id = $1"#,
+    )
+    .bind(index)
+    .bind(request.reason.as_str())
+    .bind(&request.note)
+    .bind(request.duplicate_of)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    record(
+        &mut tx,
+        index,
+        ClosureAction::Close,
+        Some(request.reason),
+        &request.note,
+        request.duplicate_of,
+        user_id,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(question_model::get(questions, index).await?.remove(0))
+}
+
+/// Reopens a closed question.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the question.
+/// * `note`: Why the question is reopened.
+/// * `user_id`: The moderator reopening it.
+///
+/// # Returns
+///
+/// The reopened question, or a `QuestionBankErr` error if the question does not exist or
+/// isn't closed.
+pub async fn reopen(
+    questions: &Pool<Postgres>,
+    index: i32,
+    note: Option<String>,
+    user_id: Option<i32>,
+) -> Result<Question, Box<dyn Error>> {
+    let mut tx = questions.begin().await?;
+    if lock(&mut tx, index).await?.is_none() {
+        return Err(Box::new(QuestionBankErr::NotClosed(format!(
+            "Question {index}"
+        ))));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE questions
+        SET closed_at = NULL, close_reason = NULL, close_note = NULL, duplicate_of = NULL,
+            closed_by = NULL
+        WHERE id = $1"#,
+    )
+    .bind(index)
+    .execute(&mut *tx)
+    .await?;
+    record(
+        &mut tx,
+        index,
+        ClosureAction::Reopen,
+        None,
+        &note,
+        None,
+        user_id,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(question_model::get(questions, index).await?.remove(0))
+}
+
+/// Retrieves every time a question was closed or reopened.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the question.
+///
+/// # Returns
+///
+/// The question's closes and reopens, oldest first.
+pub async fn get_history(
+    questions: &Pool<Postgres>,
+    index: i32,
+) -> Result<Vec<ClosureEvent>, Box<dyn Error>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {CLOSURE_COLUMNS} FROM question_closures WHERE question_id = $1 ORDER BY id"
+    ))
+    .bind(index)
+    .fetch_all(questions)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(<ClosureEvent as std::convert::From<PgRow>>::from)
+        .collect())
+}
@@ -0,0 +1,214 @@
+use crate::{entities::comment::*, models::lib::*};
+use std::collections::HashMap;
+
+/// Columns of a `Comment`
+const COMMENT_COLUMNS: &str =
+    "id, question_id, answer_id, parent_id, author_id, body, created_at, updated_at";
+
+/// Retrieves the comments on a question or answer.
+///
+/// # Parameters
+///
+/// * `target`: Whether the comments are on a question or an answer.
+/// * `target_id`: The ID of the question or answer.
+///
+/// # Returns
+///
+/// The comments, oldest first.
+pub async fn get_all(
+    comments: &Pool<Postgres>,
+    target: CommentTarget,
+    target_id: i32,
+) -> Result<Vec<Comment>, Box<dyn Error>> {
+    let column = target.column();
+    let rows = sqlx::query(&format!(
+        "SELECT {COMMENT_COLUMNS} FROM comments WHERE {column} = $1 ORDER BY id"
+    ))
+    .bind(target_id)
+    .fetch_all(comments)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(<Comment as std::convert::From<PgRow>>::from)
+        .collect())
+}
+
+/// Retrieves the comments on several answers at once.
+///
+/// # Parameters
+///
+/// * `answer_ids`: The IDs of the answers.
+///
+/// # Returns
+///
+/// The comments by answer ID, oldest first. Answers without comments are left out.
+pub async fn get_for_answers(
+    comments: &Pool<Postgres>,
+    answer_ids: &[i32],
+) -> Result<HashMap<i32, Vec<Comment>>, Box<dyn Error>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {COMMENT_COLUMNS} FROM comments WHERE answer_id = ANY($1) ORDER BY id"
+    ))
+    .bind(answer_ids)
+    .fetch_all(comments)
+    .await?;
+
+    let mut by_answer: HashMap<i32, Vec<Comment>> = HashMap::new();
+    for comment in rows
+        .into_iter()
+        .map(<Comment as std::convert::From<PgRow>>::from)
+    {
+        if let Some(answer_id) = comment.answer_id {
+            by_answer.entry(answer_id).or_default().push(comment);
+        }
+    }
+
+    Ok(by_answer)
+}
+
+/// Retrieves a comment on a question or answer.
+///
+/// # Parameters
+///
+/// * `target`: Whether the comment is on a question or an answer.
+/// * `target_id`: The ID of the question or answer.
+/// * `index`: The ID of the comment.
+///
+/// # Returns
+///
+/// The comment, or a `QuestionBankErr` error if there is no such comment on the item.
+pub async fn get(
+    comments: &Pool<Postgres>,
+    target: CommentTarget,
+    target_id: i32,
+    index: i32,
+) -> Result<Comment, Box<dyn Error>> {
+    let column = target.column();
+    let row = sqlx::query(&format!(
+        "SELECT {COMMENT_COLUMNS} FROM comments WHERE {column} = $1 AND id = $2"
+    ))
+    .bind(target_id)
+    .bind(index)
+    .fetch_optional(comments)
+    .await?
+    .ok_or_else(|| QuestionBankErr::CommentDoesNotExist(index.to_string()))?;
+
+    Ok(<Comment as std::convert::From<PgRow>>::from(row))
+}
+
+/// Adds a comment to a question or answer.
+///
+/// # Parameters
+///
+/// * `target`: Whether the comment is on a question or an answer.
+/// * `target_id`: The ID of the question or answer.
+/// * `author_id`: The user who wrote the comment.
+/// * `comment`: The comment's text and the comment it replies to.
+///
+/// # Returns
+///
+/// The new comment, or a `QuestionBankErr` error if it replies to a comment that isn't on
+/// the same item.
+pub async fn add(
+    comments: &Pool<Postgres>,
+    target: CommentTarget,
+    target_id: i32,
+    author_id: Option<i32>,
+    comment: &CommentRequest,
+) -> Result<Comment, Box<dyn Error>> {
+    let column = target.column();
+    let row = sqlx::query(&format!(
+        r#"
+        INSERT INTO comments ({column}, parent_id, author_id, body)
+        SELECT $1, $2, $3, $4
+        WHERE $2::integer IS NULL
+            OR EXISTS (SELECT 1 FROM comments WHERE id = $2 AND {column} = $1)
+        RETURNING {COMMENT_COLUMNS}"#
+    ))
+    .bind(target_id)
+    .bind(comment.parent_id)
+    .bind(author_id)
+    .bind(comment.body.trim())
+    .fetch_optional(comments)
+    .await?
+    .ok_or_else(|| {
+        QuestionBankErr::InvalidInput(format!(
+            "comment {} isn't on this {}",
+            comment.parent_id.unwrap_or_default(),
+            target.name()
+        ))
+    })?;
+
+    Ok(<Comment as std::convert::From<PgRow>>::from(row))
+}
+
+/// Changes the text of a comment.
+///
+/// # Parameters
+///
+/// * `target`: Whether the comment is on a question or an answer.
+/// * `target_id`: The ID of the question or answer.
+/// * `index`: The ID of the comment.
+/// * `body`: The new text.
+///
+/// # Returns
+///
+/// The edited comment, or a `QuestionBankErr` error if there is no such comment on the item.
+pub async fn update(
+    comments: &Pool<Postgres>,
+    target: CommentTarget,
+    target_id: i32,
+    index: i32,
+    body: &str,
+) -> Result<Comment, Box<dyn Error>> {
+    let column = target.column();
+    let row = sqlx::query(&format!(
+        r#"
+        UPDATE comments SET body = $3, updated_at = now()
+        WHERE {column} = $1 AND id = $2
+        RETURNING {COMMENT_COLUMNS}"#
+    ))
+    .bind(target_id)
+    .bind(index)
+    .bind(body.trim())
+    .fetch_optional(comments)
+    .await?
+    .ok_or_else(|| QuestionBankErr::CommentDoesNotExist(index.to_string()))?;
+
+    Ok(<Comment as std::convert::From<PgRow>>::from(row))
+}
+
+/// Deletes a comment along with the replies to it.
+///
+/// # Parameters
+///
+/// * `target`: Whether the comment is on a question or an answer.
+/// * `target_id`: The ID of the question or answer.
+/// * `index`: The ID of the comment.
+///
+/// # Returns
+///
+/// A `QuestionBankErr` error if there is no such comment on the item.
+pub async fn delete(
+    comments: &Pool<Postgres>,
+    target: CommentTarget,
+    target_id: i32,
+    index: i32,
+) -> Result<(), Box<dyn Error>> {
+    let column = target.column();
+    let result = sqlx::query(&format!(
+        "DELETE FROM comments WHERE {column} = $1 AND id = $2"
+    ))
+    .bind(target_id)
+    .bind(index)
+    .execute(comments)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(Box::new(QuestionBankErr::CommentDoesNotExist(
+            index.to_string(),
+        )));
+    }
+
+    Ok(())
+}
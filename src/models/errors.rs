@@ -9,8 +9,50 @@ pub enum QuestionBankErr {
     IoError(String),
     #[error("Question {0} doesn't exist")]
     DoesNotExist(String),
-    #[error("Invalid query parameter values")]
+    #[error("Invalid query parameter values: {0}")]
     PaginationInvalid(String),
+    #[error("Answer {0} doesn't exist")]
+    AnswerDoesNotExist(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+    #[error("User {0} doesn't exist")]
+    UserDoesNotExist(String),
+    #[error("Tag {0} doesn't exist")]
+    TagDoesNotExist(String),
+    #[error("Synonym {0} doesn't exist")]
+    SynonymDoesNotExist(String),
+    #[error("Revision {0} doesn't exist")]
+    RevisionDoesNotExist(String),
+    #[error("API token {0} doesn't exist")]
+    TokenDoesNotExist(String),
+    #[error("Comment {0} doesn't exist")]
+    CommentDoesNotExist(String),
+    #[error("Quiz session {0} doesn't exist")]
+    QuizSessionDoesNotExist(String),
+    #[error("Not logged in")]
+    Unauthorized,
+    #[error("Wrong username or password")]
+    InvalidCredentials,
+    #[error("Invalid or expired API token")]
+    InvalidToken,
+    #[error("Not allowed: {0}")]
+    Forbidden(String),
+    #[error("{0} isn't in the trash")]
+    NotInTrash(String),
+    #[error("{0} is in the trash")]
+    InTrash(String),
+    #[error("{0} is closed")]
+    Closed(String),
+    #[error("{0} isn't closed")]
+    NotClosed(String),
+    #[error("Title duplicates {0}")]
+    DuplicateTitle(String),
+    #[error("User {0} is the last admin")]
+    LastAdmin(String),
+    #[error("Not acceptable: this resource is available as {0}")]
+    NotAcceptable(String),
 }
 
 impl From<std::io::Error> for QuestionBankErr {
@@ -1,6 +1,6 @@
 pub use crate::models::errors::*;
 pub use axum::{http::StatusCode, response::Response, Json};
-pub use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+pub use sqlx::{postgres::PgRow, Pool, Postgres, QueryBuilder, Row, Transaction};
 pub use std::error::Error;
 pub use utoipa::{
     openapi::{ObjectBuilder, RefOr, Schema, SchemaType},
@@ -1,4 +1,16 @@
 pub mod answer_model;
+pub mod bulk_model;
+pub mod closure_model;
+pub mod comment_model;
 pub mod errors;
 pub mod lib;
 pub mod question_model;
+pub mod quiz_model;
+pub mod review_model;
+pub mod revision_model;
+pub mod search_model;
+pub mod tag_model;
+pub mod token_model;
+pub mod trash_model;
+pub mod user_model;
+pub mod vote_model;
@@ -1,56 +1,176 @@
 use crate::{
-    entities::{question, question::Question},
-    models::lib::*,
+    entities::question::Question,
+    markdown,
+    models::{lib::*, revision_model, tag_model},
+    pagination::{Keyed, Order, Page, PageQuery, QuestionFilter, QuestionSort, SortKey, TagMatch},
 };
+use time::OffsetDateTime;
 
-/// Retrieves a paginated list of questions from the question bank.
+/// Columns of a `Question` selected from [`QUESTION_TABLES`], grouped by `q.id, a.id`
+pub const QUESTION_COLUMNS: &str = r#"
+    q.id, q.title, q.content, q.content_html, ARRAY_AGG(t.name) AS tags, q.kind,
+    q.author_id, q.score, q.created_at, q.updated_at, q.last_activity_at, q.deleted_at,
+    q.closed_at, q.close_reason, q.close_note, q.duplicate_of, q.closed_by,
+    a.id AS accepted_answer_id, a.answer AS accepted_answer,
+    a.answer_html AS accepted_answer_html, a.key AS accepted_answer_key,
+    a.author_id AS accepted_answer_author_id,
+    a.score AS accepted_answer_score, a.created_at AS accepted_answer_created_at,
+    a.updated_at AS accepted_answer_updated_at, a.deleted_at AS accepted_answer_deleted_at"#;
+
+/// Questions `q` with their tags `t` and accepted answer `a`. The accepted answer of a
+/// trashed question is the one trashed along with it.
+pub const QUESTION_TABLES: &str = r#"
+    FROM questions q
+    LEFT JOIN question_tags qt ON q.id = qt.question_id
+    LEFT JOIN tags t ON qt.tag_id = t.id
+    LEFT JOIN answers a ON a.question_id = q.id AND a.accepted
+        AND (a.deleted_at IS NULL OR a.deleted_at = q.deleted_at)"#;
+
+/// Appends the `WHERE` clause for `filter` to a query selecting from `questions q`.
+fn push_filter(query: &mut QueryBuilder<'_, Postgres>, filter: &QuestionFilter) {
+    query.push(" WHERE q.deleted_at IS NULL");
+
+    if !filter.tags.is_empty() {
+        query.push(
+            r#" AND (
+            SELECT COUNT(DISTINCT ft.name) FROM question_tags fqt
+            JOIN tags ft ON fqt.tag_id = ft.id
+            WHERE fqt.question_id = q.id AND ft.name = ANY("#,
+        );
+        query.push_bind(filter.tags.clone());
+        query.push(")) ");
+        match filter.tag_match {
+            TagMatch::Any => query.push(">= 1"),
+            TagMatch::All => query.push("= ").push_bind(filter.tags.len() as i64),
+        };
+    }
+
+    if !filter.exclude_tags.is_empty() {
+        query.push(
+            r#" AND NOT EXISTS (
+            SELECT 1 FROM question_tags fqt
+            JOIN tags ft ON fqt.tag_id = ft.id
+            WHERE fqt.question_id = q.id AND ft.name = ANY("#,
+        );
+        query.push_bind(filter.exclude_tags.clone());
+        query.push("))");
+    }
+
+    let time = match filter.sort {
+        QuestionSort::Updated => "q.updated_at",
+        QuestionSort::Activity => "q.last_activity_at",
+        _ => "q.created_at",
+    };
+    if let Some(since) = filter.since {
+        query.push(" AND ").push(time).push(" >= ").push_bind(since);
+    }
+    if let Some(until) = filter.until {
+        query.push(" AND ").push(time).push(" < ").push_bind(until);
+    }
+
+    match filter.closed {
+        Some(true) => query.push(" AND q.closed_at IS NOT NULL"),
+        Some(false) => query.push(" AND q.closed_at IS NULL"),
+        None => query,
+    };
+
+    match filter.answered {
+        Some(true) => query.push(
+            " AND EXISTS (SELECT 1 FROM answers fa \
+                WHERE fa.question_id = q.id AND fa.deleted_at IS NULL)",
+        ),
+        Some(false) => query.push(
+            " AND NOT EXISTS (SELECT 1 FROM answers fa \
+                WHERE fa.question_id = q.id AND fa.deleted_at IS NULL)",
+        ),
+        None => query,
+    };
+}
+
+/// The ordering of the question list for a `QuestionSort`.
+pub fn order(sort: QuestionSort) -> Order {
+    let (name, key, key_desc, id_desc) = match sort {
+        QuestionSort::Newest => ("newest", None, false, true),
+        QuestionSort::Oldest => ("oldest", None, false, false),
+        QuestionSort::Answers => (
+            "answers",
+            Some(
+                "(SELECT COUNT(*) FROM answers sa \
+                WHERE sa.question_id = q.id AND sa.deleted_at IS NULL)",
+            ),
+            true,
+            false,
+        ),
+        // Byte order, like the in-memory backend, rather than the database's collation
+        QuestionSort::Title => ("title", Some(r#"q.title COLLATE "C""#), false, false),
+        QuestionSort::Score => ("score", Some("q.score"), true, false),
+        QuestionSort::Updated => (
+            "updated",
+            Some("(EXTRACT(EPOCH FROM q.updated_at) * 1000000)::bigint"),
+            true,
+            true,
+        ),
+        QuestionSort::Activity => (
+            "activity",
+            Some("(EXTRACT(EPOCH FROM q.last_activity_at) * 1000000)::bigint"),
+            true,
+            true,
+        ),
+    };
+    Order {
+        name,
+        key,
+        key_desc,
+        id: "q.id",
+        id_desc,
+    }
+}
+
+/// Retrieves a page of questions from the question bank.
 ///
 /// # Parameters
 ///
-/// * `page`: The page number to retrieve (starts at 1)
-/// * `limit`: The number of questions to retrieve per page.
+/// * `page`: Which page to retrieve, by offset or by cursor.
+/// * `filter`: Which questions to include and how to order them.
 ///
 /// # Returns
 ///
-/// A vector of Question's
-/// If the pagination parameters are invalid, returns a `QuestionBankErr` error.
+/// The page of Question's, along with the total number of matching questions.
 pub async fn paginated_get(
     questions: &Pool<Postgres>,
-    page: i32,
-    limit: i32,
-) -> Result<Vec<question::Question>, Box<dyn Error>> {
-    let row = sqlx::query(r#"SELECT COUNT(*) FROM questions;"#)
-        .fetch_one(questions)
-        .await?;
+    page: &PageQuery,
+    filter: &QuestionFilter,
+) -> Result<Page<Question>, Box<dyn Error>> {
+    let order = order(filter.sort);
+
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM questions q");
+    push_filter(&mut count_query, filter);
+    let row = count_query.build().fetch_one(questions).await?;
     let total_questions: i64 = row.get(0);
-    let start_index = (page - 1) * limit;
-    if (start_index as i64) > total_questions {
-        return Err(Box::new(QuestionBankErr::PaginationInvalid(
-            "Invalid query parameter values".to_string(),
-        )));
+
+    let mut query = QueryBuilder::new("SELECT ");
+    query.push(QUESTION_COLUMNS);
+    if let Some(key) = order.key {
+        query.push(", ").push(key).push(" AS sort_key");
     }
+    query.push(QUESTION_TABLES);
+    push_filter(&mut query, filter);
+    order.push_keyset(&mut query, &page.position);
+    query.push(" GROUP BY q.id, a.id");
+    order.push_order_limit(&mut query, page);
 
-    let questions = sqlx::query(
-        r#"
-        SELECT q.id, q.title, q.content, ARRAY_AGG(t.name) AS tags
-        FROM questions q
-        LEFT JOIN question_tags qt ON q.id = qt.question_id
-        LEFT JOIN tags t ON qt.tag_id = t.id
-        GROUP BY q.id, q.title, q.content
-        ORDER BY q.id
-        LIMIT $1 OFFSET $2"#,
-    )
-    .bind(limit)
-    .bind(start_index)
-    .fetch_all(questions)
-    .await?;
+    let questions = query.build().fetch_all(questions).await?;
 
-    let mut question_vec: Vec<Question> = Vec::new();
+    let mut question_vec: Vec<Keyed<Question>> = Vec::new();
     for row in questions {
-        question_vec.push(<Question as std::convert::From<PgRow>>::from(row));
+        question_vec.push(Keyed {
+            key: SortKey::from_row(&row),
+            id: row.get("id"),
+            item: <Question as std::convert::From<PgRow>>::from(row),
+        });
     }
 
-    Ok(question_vec)
+    Ok(page.page(&order, question_vec, total_questions))
 }
 
 /// Retrieves a question by its ID.
@@ -64,69 +184,125 @@ pub async fn paginated_get(
 /// A reference to the `Question` instance with the specified ID, or a `QuestionBankErr` error if the question does not exist.
 pub async fn get(questions: &Pool<Postgres>, index: i32) -> Result<Vec<Question>, Box<dyn Error>> {
     let mut question_vec = vec![];
-    let question = sqlx::query(
-        r#"
-        SELECT q.id, q.title, q.content, ARRAY_AGG(t.name) AS tags
-        FROM questions q
-        LEFT JOIN question_tags qt ON q.id = qt.question_id
-        LEFT JOIN tags t ON qt.tag_id = t.id
-        WHERE q.id = $1
-        GROUP BY q.id, q.title, q.content;
-        "#,
-    )
+    let question = sqlx::query(&format!(
+        "SELECT {QUESTION_COLUMNS} {QUESTION_TABLES} \
+        WHERE q.id = $1 AND q.deleted_at IS NULL GROUP BY q.id, a.id"
+    ))
     .bind(index)
-    .fetch_one(questions)
-    .await?;
+    .fetch_optional(questions)
+    .await?
+    .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
 
     question_vec.push(<Question as std::convert::From<PgRow>>::from(question));
     Ok(question_vec)
 }
 
+/// Replaces the tags of a question, creating the tags that don't exist yet.
+///
+/// # Parameters
+///
+/// * `question_id`: The ID of the question.
+/// * `tags`: The question's tags, normalized and with synonyms replaced.
+async fn set_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    question_id: i32,
+    tags: &[String],
+) -> Result<(), Box<dyn Error>> {
+    sqlx::query(r#"DELETE FROM question_tags WHERE question_id = $1"#)
+        .bind(question_id)
+        .execute(&mut **tx)
+        .await?;
+
+    if tags.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO tags (name)
+        SELECT * FROM UNNEST($1::text[])
+        ON CONFLICT (name) DO NOTHING"#,
+    )
+    .bind(tags)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO question_tags (question_id, tag_id)
+        SELECT $1, id FROM tags
+        WHERE name = ANY($2)"#,
+    )
+    .bind(question_id)
+    .bind(tags)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
 /// Adds a new question.
 ///
+/// The question, its tags and its first revision are written in one transaction.
+///
 /// # Parameters
 ///
 /// * `question`: The `Question` to add to the question bank.
 ///
 /// # Returns
 ///
-/// A `Result` indicating whether the question was added successfully.
+/// The ID of the new question.
 /// If the question already exists, returns a `QuestionBankErr` error.
-pub async fn add(questions: &Pool<Postgres>, question: Question) -> Result<(), Box<dyn Error>> {
-    let question_to_insert =
-        sqlx::query(r#"INSERT INTO questions (title, content) VALUES ($1, $2) RETURNING id"#)
-            .bind(question.title)
-            .bind(question.content)
-            .fetch_one(questions)
-            .await?;
+pub async fn add(questions: &Pool<Postgres>, question: Question) -> Result<i32, Box<dyn Error>> {
+    let mut tx = questions.begin().await?;
+    let question_id = insert(&mut tx, question).await?;
+    tx.commit().await?;
 
-    let question_id: i32 = question_to_insert.get(0);
+    Ok(question_id)
+}
 
-    let mut tag_id_vec: Vec<i32> = Vec::new();
-    if question.tags.is_some() {
-        let mut tag_id;
-        for tag in &question.tags.clone().unwrap() {
-            tag_id = sqlx::query(r#"INSERT INTO tags (name) VALUES ($1) RETURNING id"#)
-                .bind(tag)
-                .fetch_one(questions)
-                .await?;
-
-            tag_id_vec.push(tag_id.get(0));
-        }
-    }
+/// Writes a new question, its tags and its first revision within a transaction, see [`add`].
+///
+/// # Returns
+///
+/// The ID of the new question.
+pub async fn insert(
+    tx: &mut Transaction<'_, Postgres>,
+    question: Question,
+) -> Result<i32, Box<dyn Error>> {
+    let tags = tag_model::canonical_tags(&mut **tx, question.tags)
+        .await?
+        .unwrap_or_default();
+    let question_to_insert = sqlx::query(
+        r#"
+        INSERT INTO questions (title, content, content_html, author_id, kind)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id"#,
+    )
+    .bind(&question.title)
+    .bind(&question.content)
+    .bind(markdown::render(&question.content))
+    .bind(question.author_id)
+    .bind(sqlx::types::Json(question.kind.unwrap_or_default()))
+    .fetch_one(&mut **tx)
+    .await?;
 
-    for tag_id in tag_id_vec {
-        sqlx::query(r#"INSERT INTO question_tags (question_id, tag_id) VALUES ($1, $2);"#)
-            .bind(question_id)
-            .bind(tag_id)
-            .execute(questions)
-            .await?;
-    }
+    let question_id: i32 = question_to_insert.get(0);
+    set_tags(tx, question_id, &tags).await?;
+    revision_model::record_question(
+        &mut **tx,
+        question_id,
+        question.author_id,
+        &question.title,
+        &question.content,
+        Some(&tags),
+    )
+    .await?;
 
-    Ok(())
+    Ok(question_id)
 }
 
-/// Removes a question by its ID.
+/// Moves a question to the trash along with its answers, see [`crate::models::trash_model`].
 ///
 /// # Parameters
 ///
@@ -137,28 +313,43 @@ pub async fn add(questions: &Pool<Postgres>, question: Question) -> Result<(), B
 /// A `Result` indicating whether the question was removed successfully.
 /// If the question does not exist, returns a `QuestionBankErr` error.
 pub async fn delete(questions: &Pool<Postgres>, index: i32) -> Result<(), Box<dyn Error>> {
+    let mut tx = questions.begin().await?;
+    let deleted = sqlx::query(
+        r#"
+        UPDATE questions SET deleted_at = now()
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING deleted_at"#,
+    )
+    .bind(index)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
+
+    // The answers share the question's deletion time, which tells them apart from
+    // answers trashed before and lets them be restored with the question
     sqlx::query(
         r#"
-        DELETE FROM questions
-        WHERE id IN (
-          SELECT question_id FROM question_tags
-          WHERE question_id = $1
-        );
-        "#,
+        UPDATE answers SET deleted_at = $2
+        WHERE question_id = $1 AND deleted_at IS NULL"#,
     )
     .bind(index)
-    .execute(questions)
+    .bind(deleted.get::<OffsetDateTime, _>("deleted_at"))
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     Ok(())
 }
 
 /// Updates a question by its ID.
 ///
+/// The question, its tags and the new revision are written in one transaction.
+///
 /// # Parameters
 ///
 /// * `index`: The ID of the question to update.
 /// * `question`: The updated `Question` instance.
+/// * `editor_id`: The user making the change, recorded in the new revision.
 ///
 /// # Returns
 ///
@@ -169,57 +360,74 @@ pub async fn update(
     questions: &Pool<Postgres>,
     index: i32,
     question: Question,
+    editor_id: Option<i32>,
 ) -> Result<Vec<Question>, Box<dyn Error>> {
     let title = question.title;
     let content = question.content;
-    let tags = question.tags;
 
     let mut question_to_update = get(questions, index).await?;
+    let mut tx = questions.begin().await?;
+    let tags = tag_model::canonical_tags(&mut *tx, question.tags).await?;
     question_to_update[0].title.clone_from(&title);
     question_to_update[0].content.clone_from(&content);
-    question_to_update[0].tags = tags;
+    question_to_update[0].render();
+    question_to_update[0].tags.clone_from(&tags);
+    if question.kind.is_some() {
+        question_to_update[0].kind.clone_from(&question.kind);
+    }
 
-    sqlx::query(
+    let updated = sqlx::query(
         r#"
         UPDATE questions
-        SET title = $1, content = $2
-        WHERE id = $3;"#,
+        SET title = $1, content = $2, content_html = $3, kind = $5, updated_at = now(),
+            last_activity_at = now()
+        WHERE id = $4
+        RETURNING updated_at;"#,
     )
-    .bind(title)
-    .bind(content)
+    .bind(&title)
+    .bind(&content)
+    .bind(&question_to_update[0].content_html)
     .bind(index)
-    .execute(questions)
+    .bind(sqlx::types::Json(
+        &question_to_update[0].kind.clone().unwrap_or_default(),
+    ))
+    .fetch_one(&mut *tx)
     .await?;
+    question_to_update[0].updated_at = updated.get("updated_at");
+    question_to_update[0].last_activity_at = question_to_update[0].updated_at;
 
-    sqlx::query(
-        r#"
-        DELETE FROM question_tags
-        WHERE question_id = $1;
-        "#,
+    set_tags(&mut tx, index, tags.as_deref().unwrap_or_default()).await?;
+    revision_model::record_question(
+        &mut *tx,
+        index,
+        editor_id,
+        &title,
+        &content,
+        tags.as_deref(),
     )
-    .bind(question_to_update[0].id)
-    .execute(questions)
     .await?;
-
-    let mut tag_id_vec: Vec<i32> = Vec::new();
-    let mut tag_id;
-    if let Some(ref tags_to_add) = question_to_update[0].tags {
-        for tag in tags_to_add {
-            tag_id = sqlx::query(r#"INSERT INTO tags (name) VALUES ($1) RETURNING id"#)
-                .bind(tag)
-                .fetch_one(questions)
-                .await?;
-
-            tag_id_vec.push(tag_id.get(0));
-        }
-        for tag_id in tag_id_vec {
-            sqlx::query(r#"INSERT INTO question_tags (question_id, tag_id) VALUES ($1, $2);"#)
-                .bind(index)
-                .bind(tag_id)
-                .execute(questions)
-                .await?;
-        }
-    };
+    tx.commit().await?;
 
     Ok(question_to_update)
 }
+
+/// Renders the HTML of the questions stored before Markdown was rendered on write.
+///
+/// # Returns
+///
+/// The number of questions rendered.
+pub async fn render_missing(questions: &Pool<Postgres>) -> Result<u64, Box<dyn Error>> {
+    let rows = sqlx::query(r#"SELECT id, content FROM questions WHERE content_html IS NULL"#)
+        .fetch_all(questions)
+        .await?;
+    for row in &rows {
+        let content: String = row.get("content");
+        sqlx::query(r#"UPDATE questions SET content_html = $1 WHERE id = $2"#)
+            .bind(markdown::render(&content))
+            .bind(row.get::<i32, _>("id"))
+            .execute(questions)
+            .await?;
+    }
+
+    Ok(rows.len() as u64)
+}
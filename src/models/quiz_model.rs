@@ -0,0 +1,241 @@
+use crate::{
+    entities::{answer::Answer, quiz::*},
+    models::lib::*,
+};
+use std::collections::HashMap;
+
+/// A quiz session with its score, `$1` being its ID
+const SESSION_QUERY: &str = r#"
+    SELECT s.id, s.user_id, s.tags, s.created_at,
+        COUNT(i.question_id) AS served,
+        COUNT(i.answered_at) AS answered,
+        COUNT(*) FILTER (WHERE i.correct) AS correct
+    FROM quiz_sessions s
+    LEFT JOIN quiz_items i ON i.session_id = s.id
+    WHERE s.id = $1
+    GROUP BY s.id"#;
+
+/// Starts a quiz session.
+///
+/// # Parameters
+///
+/// * `user_id`: The user practicing.
+/// * `tags`: The normalized tags to draw questions from, any if empty.
+///
+/// # Returns
+///
+/// The new session.
+pub async fn start(
+    quizzes: &Pool<Postgres>,
+    user_id: i32,
+    tags: &[String],
+) -> Result<QuizSession, Box<dyn Error>> {
+    let row =
+        sqlx::query(r#"INSERT INTO quiz_sessions (user_id, tags) VALUES ($1, $2) RETURNING id"#)
+            .bind(user_id)
+            .bind(tags)
+            .fetch_one(quizzes)
+            .await?;
+
+    get(quizzes, row.get("id")).await
+}
+
+/// Retrieves a quiz session.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the session.
+///
+/// # Returns
+///
+/// The session with its score, or a `QuestionBankErr` error if it does not exist.
+pub async fn get(quizzes: &Pool<Postgres>, index: i32) -> Result<QuizSession, Box<dyn Error>> {
+    let row = sqlx::query(SESSION_QUERY)
+        .bind(index)
+        .fetch_optional(quizzes)
+        .await?
+        .ok_or_else(|| QuestionBankErr::QuizSessionDoesNotExist(index.to_string()))?;
+
+    Ok(<QuizSession as std::convert::From<PgRow>>::from(row))
+}
+
+/// Lists the questions a quiz session can still serve: live questions with at least one
+/// answer and one of the session's tags, that the session hasn't served yet.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the session.
+///
+/// # Returns
+///
+/// The IDs of the questions, in ascending order.
+pub async fn unseen(quizzes: &Pool<Postgres>, index: i32) -> Result<Vec<i32>, Box<dyn Error>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT q.id
+        FROM questions q, quiz_sessions s
+        WHERE s.id = $1 AND q.deleted_at IS NULL
+            AND EXISTS (
+                SELECT 1 FROM answers a WHERE a.question_id = q.id AND a.deleted_at IS NULL
+            )
+            AND (cardinality(s.tags) = 0 OR EXISTS (
+                SELECT 1 FROM question_tags qt
+                JOIN tags t ON qt.tag_id = t.id
+                WHERE qt.question_id = q.id AND t.name = ANY(s.tags)
+            ))
+            AND NOT EXISTS (
+                SELECT 1 FROM quiz_items i WHERE i.session_id = s.id AND i.question_id = q.id
+            )
+        ORDER BY q.id"#,
+    )
+    .bind(index)
+    .fetch_all(quizzes)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.get("id")).collect())
+}
+
+/// Records that a quiz session served a question.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the session.
+/// * `question_id`: The served question.
+pub async fn serve(
+    quizzes: &Pool<Postgres>,
+    index: i32,
+    question_id: i32,
+) -> Result<(), Box<dyn Error>> {
+    sqlx::query(r#"INSERT INTO quiz_items (session_id, question_id) VALUES ($1, $2)"#)
+        .bind(index)
+        .bind(question_id)
+        .execute(quizzes)
+        .await?;
+
+    Ok(())
+}
+
+/// Retrieves the answer quiz submissions are graded against.
+///
+/// # Parameters
+///
+/// * `question_id`: The ID of the question.
+///
+/// # Returns
+///
+/// The question's accepted answer, else its best scored and then oldest answer, or a
+/// `QuestionBankErr` error if it has no answers.
+pub async fn correct_answer(
+    quizzes: &Pool<Postgres>,
+    question_id: i32,
+) -> Result<Answer, Box<dyn Error>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, answer, answer_html, key, question_id, accepted, author_id, score, created_at,
+            updated_at
+        FROM answers
+        WHERE question_id = $1 AND deleted_at IS NULL
+        ORDER BY accepted DESC, score DESC, id
+        LIMIT 1"#,
+    )
+    .bind(question_id)
+    .fetch_optional(quizzes)
+    .await?
+    .ok_or_else(|| QuestionBankErr::AnswerDoesNotExist(format!("for question {question_id}")))?;
+
+    Ok(<Answer as std::convert::From<PgRow>>::from(row))
+}
+
+/// Retrieves the answers submissions to several questions are graded against, in one query.
+///
+/// # Parameters
+///
+/// * `question_ids`: The IDs of the questions.
+///
+/// # Returns
+///
+/// The answer of each question that has one, as picked by [`correct_answer`], by question ID.
+pub async fn correct_answers(
+    quizzes: &Pool<Postgres>,
+    question_ids: &[i32],
+) -> Result<HashMap<i32, Answer>, Box<dyn Error>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (question_id) id, answer, answer_html, key, question_id, accepted,
+            author_id, score, created_at, updated_at
+        FROM answers
+        WHERE question_id = ANY($1) AND deleted_at IS NULL
+        ORDER BY question_id, accepted DESC, score DESC, id"#,
+    )
+    .bind(question_ids)
+    .fetch_all(quizzes)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(<Answer as std::convert::From<PgRow>>::from)
+        .filter_map(|answer| Some((answer.question_id?, answer)))
+        .collect())
+}
+
+/// Records the graded answer to a served question.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the session.
+/// * `question_id`: The answered question.
+/// * `answer`: What the user answered.
+/// * `correct`: Whether the answer was graded correct.
+/// * `similarity`: How close it came to the correct answer.
+///
+/// # Returns
+///
+/// The session with its new score, or a `QuestionBankErr` error if the session didn't serve
+/// the question or it was already answered.
+pub async fn grade(
+    quizzes: &Pool<Postgres>,
+    index: i32,
+    question_id: i32,
+    answer: &str,
+    correct: bool,
+    similarity: f32,
+) -> Result<QuizSession, Box<dyn Error>> {
+    let mut tx = quizzes.begin().await?;
+    let row = sqlx::query(
+        r#"
+        SELECT answered_at IS NOT NULL AS answered FROM quiz_items
+        WHERE session_id = $1 AND question_id = $2
+        FOR UPDATE"#,
+    )
+    .bind(index)
+    .bind(question_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        QuestionBankErr::InvalidInput(format!(
+            "question {question_id} wasn't served in this session"
+        ))
+    })?;
+    if row.get::<bool, _>("answered") {
+        return Err(Box::new(QuestionBankErr::AlreadyExists(format!(
+            "An answer to question {question_id}"
+        ))));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE quiz_items
+        SET answer = $3, correct = $4, similarity = $5, answered_at = now()
+        WHERE session_id = $1 AND question_id = $2"#,
+    )
+    .bind(index)
+    .bind(question_id)
+    .bind(answer)
+    .bind(correct)
+    .bind(similarity)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    get(quizzes, index).await
+}
@@ -0,0 +1,127 @@
+use crate::{
+    entities::{
+        question::Question,
+        review::{DueCard, Review},
+    },
+    models::{
+        lib::*,
+        question_model::{QUESTION_COLUMNS, QUESTION_TABLES},
+    },
+    scheduling::Card,
+};
+
+/// Retrieves where a user stands with a question.
+///
+/// # Parameters
+///
+/// * `user_id`: The user reviewing.
+/// * `question_id`: The question reviewed.
+///
+/// # Returns
+///
+/// The review, or `None` if the user never answered the question in a quiz.
+pub async fn get(
+    reviews: &Pool<Postgres>,
+    user_id: i32,
+    question_id: i32,
+) -> Result<Option<Review>, Box<dyn Error>> {
+    let row = sqlx::query(
+        r#"
+        SELECT user_id, question_id, ease, interval_days, repetitions, reviewed_at, due_at
+        FROM reviews
+        WHERE user_id = $1 AND question_id = $2"#,
+    )
+    .bind(user_id)
+    .bind(question_id)
+    .fetch_optional(reviews)
+    .await?;
+
+    Ok(row.map(<Review as std::convert::From<PgRow>>::from))
+}
+
+/// Stores the schedule of a question reviewed now.
+///
+/// # Parameters
+///
+/// * `user_id`: The user reviewing.
+/// * `question_id`: The question reviewed.
+/// * `card`: The schedule after the review.
+///
+/// # Returns
+///
+/// The review, due `card.interval` days from now.
+pub async fn save(
+    reviews: &Pool<Postgres>,
+    user_id: i32,
+    question_id: i32,
+    card: &Card,
+) -> Result<Review, Box<dyn Error>> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO reviews (user_id, question_id, ease, interval_days, repetitions, due_at)
+        VALUES ($1, $2, $3, $4, $5, now() + make_interval(days => $4))
+        ON CONFLICT (user_id, question_id) DO UPDATE
+        SET ease = EXCLUDED.ease, interval_days = EXCLUDED.interval_days,
+            repetitions = EXCLUDED.repetitions, reviewed_at = EXCLUDED.reviewed_at,
+            due_at = EXCLUDED.due_at
+        RETURNING user_id, question_id, ease, interval_days, repetitions, reviewed_at, due_at"#,
+    )
+    .bind(user_id)
+    .bind(question_id)
+    .bind(card.ease)
+    .bind(card.interval)
+    .bind(card.repetitions)
+    .fetch_one(reviews)
+    .await?;
+
+    Ok(<Review as std::convert::From<PgRow>>::from(row))
+}
+
+/// Lists the reviews of a user that are due, on live questions, along with the questions.
+///
+/// # Parameters
+///
+/// * `user_id`: The user reviewing.
+/// * `tag`: Only list questions with this normalized tag.
+/// * `limit`: The most reviews to list.
+///
+/// # Returns
+///
+/// The due reviews with their questions, longest overdue first.
+pub async fn due(
+    reviews: &Pool<Postgres>,
+    user_id: i32,
+    tag: Option<&str>,
+    limit: i64,
+) -> Result<Vec<DueCard>, Box<dyn Error>> {
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {QUESTION_COLUMNS},
+            r.user_id, r.question_id, r.ease, r.interval_days, r.repetitions, r.reviewed_at,
+            r.due_at
+        {QUESTION_TABLES}
+        JOIN reviews r ON r.question_id = q.id
+        WHERE r.user_id = $1 AND r.due_at <= now() AND q.deleted_at IS NULL
+            AND ($2::TEXT IS NULL OR EXISTS (
+                SELECT 1 FROM question_tags fqt
+                JOIN tags ft ON fqt.tag_id = ft.id
+                WHERE fqt.question_id = q.id AND ft.name = $2
+            ))
+        GROUP BY q.id, a.id, r.user_id, r.question_id
+        ORDER BY r.due_at, r.question_id
+        LIMIT $3"#
+    ))
+    .bind(user_id)
+    .bind(tag)
+    .bind(limit)
+    .fetch_all(reviews)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| DueCard {
+            review: <Review as std::convert::From<&PgRow>>::from(&row),
+            question: <Question as std::convert::From<PgRow>>::from(row),
+        })
+        .collect())
+}
@@ -0,0 +1,180 @@
+use crate::{entities::revision::*, models::lib::*};
+use sqlx::Executor;
+
+/// Columns of a `Revision`
+const REVISION_COLUMNS: &str =
+    "revision, question_id, answer_id, author_id, created_at, title, content, tags";
+
+/// Stores a snapshot of a question as its next revision.
+///
+/// # Parameters
+///
+/// * `question_id`: The ID of the question.
+/// * `author_id`: The user who made the change.
+/// * `title`, `content`, `tags`: The question as it is now.
+pub async fn record_question<'e, E>(
+    revisions: E,
+    question_id: i32,
+    author_id: Option<i32>,
+    title: &str,
+    content: &str,
+    tags: Option<&[String]>,
+) -> Result<(), Box<dyn Error>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO revisions (question_id, revision, author_id, title, content, tags)
+        SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3, $4, $5
+        FROM revisions
+        WHERE question_id = $1"#,
+    )
+    .bind(question_id)
+    .bind(author_id)
+    .bind(title)
+    .bind(content)
+    .bind(tags.filter(|tags| !tags.is_empty()))
+    .execute(revisions)
+    .await?;
+
+    Ok(())
+}
+
+/// Stores a snapshot of an answer as its next revision.
+///
+/// # Parameters
+///
+/// * `answer_id`: The ID of the answer.
+/// * `author_id`: The user who made the change.
+/// * `answer`: The answer's text as it is now.
+pub async fn record_answer<'e, E>(
+    revisions: E,
+    answer_id: i32,
+    author_id: Option<i32>,
+    answer: &str,
+) -> Result<(), Box<dyn Error>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO revisions (answer_id, revision, author_id, content)
+        SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3
+        FROM revisions
+        WHERE answer_id = $1"#,
+    )
+    .bind(answer_id)
+    .bind(author_id)
+    .bind(answer)
+    .execute(revisions)
+    .await?;
+
+    Ok(())
+}
+
+/// Retrieves every revision of a question or answer, oldest first.
+async fn get_all(
+    revisions: &Pool<Postgres>,
+    column: &str,
+    index: i32,
+) -> Result<Vec<Revision>, Box<dyn Error>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {REVISION_COLUMNS} FROM revisions WHERE {column} = $1 ORDER BY revision"
+    ))
+    .bind(index)
+    .fetch_all(revisions)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(<Revision as std::convert::From<PgRow>>::from)
+        .collect())
+}
+
+/// Retrieves one revision of a question or answer.
+async fn get(
+    revisions: &Pool<Postgres>,
+    column: &str,
+    index: i32,
+    revision: i32,
+) -> Result<Revision, Box<dyn Error>> {
+    let row = sqlx::query(&format!(
+        "SELECT {REVISION_COLUMNS} FROM revisions WHERE {column} = $1 AND revision = $2"
+    ))
+    .bind(index)
+    .bind(revision)
+    .fetch_optional(revisions)
+    .await?
+    .ok_or_else(|| QuestionBankErr::RevisionDoesNotExist(revision.to_string()))?;
+
+    Ok(<Revision as std::convert::From<PgRow>>::from(row))
+}
+
+/// Retrieves the revisions of a question.
+///
+/// # Parameters
+///
+/// * `question_id`: The ID of the question.
+///
+/// # Returns
+///
+/// The question's revisions, oldest first.
+pub async fn get_question_revisions(
+    revisions: &Pool<Postgres>,
+    question_id: i32,
+) -> Result<Vec<Revision>, Box<dyn Error>> {
+    get_all(revisions, "question_id", question_id).await
+}
+
+/// Retrieves the revisions of an answer.
+///
+/// # Parameters
+///
+/// * `answer_id`: The ID of the answer.
+///
+/// # Returns
+///
+/// The answer's revisions, oldest first.
+pub async fn get_answer_revisions(
+    revisions: &Pool<Postgres>,
+    answer_id: i32,
+) -> Result<Vec<Revision>, Box<dyn Error>> {
+    get_all(revisions, "answer_id", answer_id).await
+}
+
+/// Retrieves a revision of a question.
+///
+/// # Parameters
+///
+/// * `question_id`: The ID of the question.
+/// * `revision`: The number of the revision.
+///
+/// # Returns
+///
+/// The revision, or a `QuestionBankErr` error if the question has no such revision.
+pub async fn get_question_revision(
+    revisions: &Pool<Postgres>,
+    question_id: i32,
+    revision: i32,
+) -> Result<Revision, Box<dyn Error>> {
+    get(revisions, "question_id", question_id, revision).await
+}
+
+/// Retrieves a revision of an answer.
+///
+/// # Parameters
+///
+/// * `answer_id`: The ID of the answer.
+/// * `revision`: The number of the revision.
+///
+/// # Returns
+///
+/// The revision, or a `QuestionBankErr` error if the answer has no such revision.
+pub async fn get_answer_revision(
+    revisions: &Pool<Postgres>,
+    answer_id: i32,
+    revision: i32,
+) -> Result<Revision, Box<dyn Error>> {
+    get(revisions, "answer_id", answer_id, revision).await
+}
@@ -0,0 +1,161 @@
+use crate::{
+    entities::search::*,
+    models::lib::*,
+    pagination::{Keyed, Order, Page, PageQuery, SortKey},
+};
+
+/// The ordering of search results: best match first.
+pub const ORDER: Order = Order {
+    name: "rank",
+    key: Some("s.rank"),
+    key_desc: true,
+    id: "s.id",
+    id_desc: false,
+};
+
+/// Least trigram similarity of a title to be suggested as a duplicate, `pg_trgm`'s default
+/// `similarity_threshold`
+pub const SIMILARITY_THRESHOLD: f32 = 0.3;
+
+/// `column` with `&`, `<` and `>` escaped for HTML. `ts_headline` copies text through as is,
+/// tags included, so its input is escaped and only the `<b>` tags it adds are markup.
+fn escaped(column: &str) -> String {
+    format!("replace(replace(replace({column}, '&', '&amp;'), '<', '&lt;'), '>', '&gt;')")
+}
+
+/// Appends the search itself as a subquery named `s`.
+fn push_search(query: &mut QueryBuilder<'_, Postgres>, search: &SearchQuery) {
+    // Answers count for less than the question itself, only the best matching
+    // answer of each question is used for the rank and the snippet.
+    query.push(
+        r#"(
+        WITH query AS (
+            SELECT websearch_to_tsquery('english', "#,
+    );
+    query.push_bind(search.q.clone());
+    query.push(
+        r#") AS tsq
+        ),
+        answer_matches AS (
+            SELECT DISTINCT ON (a.question_id)
+                a.question_id, a.answer, ts_rank(a.search, query.tsq) AS rank
+            FROM answers a, query
+            WHERE a.search @@ query.tsq AND a.deleted_at IS NULL
+            ORDER BY a.question_id, rank DESC
+        )
+        SELECT q.id, q.title,
+            ARRAY(
+                SELECT t.name FROM question_tags qt
+                JOIN tags t ON qt.tag_id = t.id
+                WHERE qt.question_id = q.id
+                ORDER BY t.name
+            ) AS tags,
+            (ts_rank(q.search, query.tsq) + COALESCE(am.rank, 0) / 2)::real AS rank,"#,
+    );
+    query.push(format!(
+        r#"
+            ts_headline('english', {}, query.tsq, 'HighlightAll=true') AS title_snippet,
+            ts_headline('english', {}, query.tsq, 'MaxFragments=2') AS content_snippet,
+            CASE WHEN am.answer IS NULL THEN NULL
+                ELSE ts_headline('english', {}, query.tsq, 'MaxFragments=2')
+            END AS answer_snippet"#,
+        escaped("q.title"),
+        escaped("q.content"),
+        escaped("am.answer"),
+    ));
+    query.push(
+        r#"
+        FROM questions q
+        CROSS JOIN query
+        LEFT JOIN answer_matches am ON am.question_id = q.id
+        WHERE q.deleted_at IS NULL
+            AND (q.search @@ query.tsq OR am.question_id IS NOT NULL)"#,
+    );
+    if let Some(tag) = &search.tag {
+        query.push(
+            r#"
+            AND EXISTS (
+                SELECT 1 FROM question_tags qt
+                JOIN tags t ON qt.tag_id = t.id
+                WHERE qt.question_id = q.id AND t.name = "#,
+        );
+        query.push_bind(tag.clone()).push(")");
+    }
+    query.push(") s");
+}
+
+/// Runs a ranked full-text search over questions and their answers.
+///
+/// # Parameters
+///
+/// * `query`: The search terms and optional tag filter.
+/// * `page`: Which page to retrieve, by offset or by cursor.
+///
+/// # Returns
+///
+/// The page of `SearchResult`s, best match first.
+pub async fn search(
+    questions: &Pool<Postgres>,
+    query: &SearchQuery,
+    page: &PageQuery,
+) -> Result<Page<SearchResult>, Box<dyn Error>> {
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM ");
+    push_search(&mut count_query, query);
+    let row = count_query.build().fetch_one(questions).await?;
+    let total_results: i64 = row.get(0);
+
+    let mut search_query = QueryBuilder::new("SELECT s.*, s.rank AS sort_key FROM ");
+    push_search(&mut search_query, query);
+    search_query.push(" WHERE TRUE");
+    ORDER.push_keyset(&mut search_query, &page.position);
+    ORDER.push_order_limit(&mut search_query, page);
+    let rows = search_query.build().fetch_all(questions).await?;
+
+    let mut result_vec: Vec<Keyed<SearchResult>> = Vec::new();
+    for row in rows {
+        result_vec.push(Keyed {
+            key: SortKey::from_row(&row),
+            id: row.get("id"),
+            item: <SearchResult as std::convert::From<PgRow>>::from(row),
+        });
+    }
+
+    Ok(page.page(&ORDER, result_vec, total_results))
+}
+
+/// Finds live questions with a title similar to `title`, using `pg_trgm` trigram similarity.
+///
+/// # Parameters
+///
+/// * `title`: The title to compare with.
+/// * `limit`: Most questions to return.
+///
+/// # Returns
+///
+/// The questions at least [`SIMILARITY_THRESHOLD`] similar, most similar first.
+pub async fn similar(
+    questions: &Pool<Postgres>,
+    title: &str,
+    limit: i64,
+) -> Result<Vec<SimilarQuestion>, Box<dyn Error>> {
+    // `%` uses the trigram index with the default threshold, the explicit comparison keeps the
+    // threshold in step with the in-memory backend.
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, similarity(title, $1) AS similarity
+        FROM questions
+        WHERE deleted_at IS NULL AND title % $1 AND similarity(title, $1) >= $2
+        ORDER BY similarity DESC, id
+        LIMIT $3"#,
+    )
+    .bind(title)
+    .bind(SIMILARITY_THRESHOLD)
+    .bind(limit)
+    .fetch_all(questions)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(<SimilarQuestion as std::convert::From<PgRow>>::from)
+        .collect())
+}
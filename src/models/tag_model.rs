@@ -0,0 +1,410 @@
+use crate::{
+    entities::tag::{normalize_tags, Tag},
+    models::lib::*,
+    pagination::{Keyed, Order, Page, PageQuery, SortKey, TagSort},
+};
+use sqlx::Executor;
+
+/// Columns of a `Tag` selected from `tags t`
+const TAG_COLUMNS: &str = r#"
+    t.id, t.name, t.description, t.created_at, t.updated_at,
+    (SELECT COUNT(*) FROM question_tags qt JOIN questions q ON qt.question_id = q.id
+     WHERE qt.tag_id = t.id AND q.deleted_at IS NULL) AS questions,
+    ARRAY(SELECT s.name FROM tag_synonyms s WHERE s.tag_id = t.id ORDER BY s.name) AS synonyms"#;
+
+/// The ordering of the tag list for `sort`, then by when the tags were created.
+pub fn order(sort: TagSort) -> Order {
+    let (name, key, key_desc) = match sort {
+        TagSort::Popular => (
+            "popular",
+            "(SELECT COUNT(*) FROM question_tags qt JOIN questions q ON qt.question_id = q.id \
+            WHERE qt.tag_id = t.id AND q.deleted_at IS NULL)",
+            true,
+        ),
+        // Byte order, like the in-memory backend, rather than the database's collation
+        TagSort::Name => ("name", r#"t.name COLLATE "C""#, false),
+        TagSort::Newest => (
+            "newest",
+            "(EXTRACT(EPOCH FROM t.created_at) * 1000000)::bigint",
+            true,
+        ),
+    };
+    Order {
+        name,
+        key: Some(key),
+        key_desc,
+        id: "t.id",
+        id_desc: false,
+    }
+}
+
+/// Retrieves a page of tags.
+///
+/// # Parameters
+///
+/// * `page`: Which page to retrieve, by offset or by cursor.
+/// * `sort`: How to order the tags.
+///
+/// # Returns
+///
+/// The page of tags with their usage counts.
+pub async fn get_all(
+    tags: &Pool<Postgres>,
+    page: &PageQuery,
+    sort: TagSort,
+) -> Result<Page<Tag>, Box<dyn Error>> {
+    let row = sqlx::query(r#"SELECT COUNT(*) FROM tags;"#)
+        .fetch_one(tags)
+        .await?;
+    let total_tags: i64 = row.get(0);
+
+    let order = order(sort);
+    let mut query = QueryBuilder::new("SELECT ");
+    query.push(TAG_COLUMNS);
+    if let Some(key) = order.key {
+        query.push(", ").push(key).push(" AS sort_key");
+    }
+    query.push(" FROM tags t WHERE TRUE");
+    order.push_keyset(&mut query, &page.position);
+    order.push_order_limit(&mut query, page);
+    let rows = query.build().fetch_all(tags).await?;
+
+    let mut tag_vec: Vec<Keyed<Tag>> = Vec::new();
+    for row in rows {
+        tag_vec.push(Keyed {
+            key: SortKey::from_row(&row),
+            id: row.get("id"),
+            item: <Tag as std::convert::From<PgRow>>::from(row),
+        });
+    }
+
+    Ok(page.page(&order, tag_vec, total_tags))
+}
+
+/// Retrieves a tag by its name.
+///
+/// # Parameters
+///
+/// * `name`: The normalized name of the tag.
+///
+/// # Returns
+///
+/// The tag, or a `QuestionBankErr` error if the tag does not exist.
+pub async fn get(tags: &Pool<Postgres>, name: &str) -> Result<Tag, Box<dyn Error>> {
+    let row = sqlx::query(&format!(
+        "SELECT {TAG_COLUMNS} FROM tags t WHERE t.name = $1"
+    ))
+    .bind(name)
+    .fetch_optional(tags)
+    .await?
+    .ok_or_else(|| QuestionBankErr::TagDoesNotExist(name.to_string()))?;
+
+    Ok(<Tag as std::convert::From<PgRow>>::from(row))
+}
+
+/// Sets or removes the description of a tag.
+///
+/// # Parameters
+///
+/// * `name`: The normalized name of the tag.
+/// * `description`: The new description, `None` to remove it.
+///
+/// # Returns
+///
+/// The updated tag, or a `QuestionBankErr` error if the tag does not exist.
+pub async fn set_description(
+    tags: &Pool<Postgres>,
+    name: &str,
+    description: Option<&str>,
+) -> Result<Tag, Box<dyn Error>> {
+    let result =
+        sqlx::query(r#"UPDATE tags SET description = $1, updated_at = now() WHERE name = $2"#)
+            .bind(description)
+            .bind(name)
+            .execute(tags)
+            .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Box::new(QuestionBankErr::TagDoesNotExist(name.to_string())));
+    }
+
+    get(tags, name).await
+}
+
+/// Locks a tag for the rest of the transaction, if it exists.
+///
+/// # Returns
+///
+/// The ID of the tag, or `None` if there is no such tag.
+async fn find_tag(
+    tx: &mut Transaction<'_, Postgres>,
+    name: &str,
+) -> Result<Option<i32>, Box<dyn Error>> {
+    let row = sqlx::query(r#"SELECT id FROM tags WHERE name = $1 FOR UPDATE"#)
+        .bind(name)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    Ok(row.map(|row| row.get(0)))
+}
+
+/// Locks a tag for the rest of the transaction.
+///
+/// # Returns
+///
+/// The ID of the tag, or a `QuestionBankErr` error if the tag does not exist.
+async fn lock_tag(tx: &mut Transaction<'_, Postgres>, name: &str) -> Result<i32, Box<dyn Error>> {
+    find_tag(tx, name)
+        .await?
+        .ok_or_else(|| QuestionBankErr::TagDoesNotExist(name.to_string()).into())
+}
+
+/// Moves the last update time of a tag to now.
+async fn touch_tag(tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<(), Box<dyn Error>> {
+    sqlx::query(r#"UPDATE tags SET updated_at = now() WHERE id = $1"#)
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Moves the questions and synonyms of one tag to another, then removes it and
+/// keeps its name as a synonym of the other.
+async fn merge_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    source_id: i32,
+    source: &str,
+    target_id: i32,
+) -> Result<(), Box<dyn Error>> {
+    sqlx::query(
+        r#"
+        INSERT INTO question_tags (question_id, tag_id)
+        SELECT question_id, $2 FROM question_tags
+        WHERE tag_id = $1
+        ON CONFLICT DO NOTHING"#,
+    )
+    .bind(source_id)
+    .bind(target_id)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(r#"UPDATE tag_synonyms SET tag_id = $2 WHERE tag_id = $1"#)
+        .bind(source_id)
+        .bind(target_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(r#"DELETE FROM tags WHERE id = $1"#)
+        .bind(source_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(r#"INSERT INTO tag_synonyms (name, tag_id) VALUES ($1, $2)"#)
+        .bind(source)
+        .bind(target_id)
+        .execute(&mut **tx)
+        .await?;
+
+    touch_tag(tx, target_id).await
+}
+
+/// Merges one tag into another: every question with `source` gets `into`
+/// instead, and `source` becomes a synonym of `into`.
+///
+/// # Parameters
+///
+/// * `source`: The normalized name of the tag to merge away.
+/// * `into`: The normalized name of the tag to keep.
+///
+/// # Returns
+///
+/// The tag that was kept, or a `QuestionBankErr` error if either tag does not exist.
+pub async fn merge(tags: &Pool<Postgres>, source: &str, into: &str) -> Result<Tag, Box<dyn Error>> {
+    if source == into {
+        return Err(Box::new(QuestionBankErr::InvalidInput(
+            "can't merge a tag into itself".to_string(),
+        )));
+    }
+
+    let mut tx = tags.begin().await?;
+    let source_id = lock_tag(&mut tx, source).await?;
+    let target_id = lock_tag(&mut tx, into).await?;
+    merge_tags(&mut tx, source_id, source, target_id).await?;
+    tx.commit().await?;
+
+    get(tags, into).await
+}
+
+/// Makes `synonym` a synonym of a tag. If `synonym` is a tag itself, it is
+/// merged into the tag first.
+///
+/// # Parameters
+///
+/// * `name`: The normalized name of the tag.
+/// * `synonym`: The normalized name that should be replaced by the tag.
+///
+/// # Returns
+///
+/// The updated tag, or a `QuestionBankErr` error if the tag does not exist or
+/// `synonym` is already a synonym.
+pub async fn add_synonym(
+    tags: &Pool<Postgres>,
+    name: &str,
+    synonym: &str,
+) -> Result<Tag, Box<dyn Error>> {
+    if name == synonym {
+        return Err(Box::new(QuestionBankErr::InvalidInput(
+            "a tag can't be its own synonym".to_string(),
+        )));
+    }
+
+    let mut tx = tags.begin().await?;
+    let target_id = lock_tag(&mut tx, name).await?;
+    let taken = sqlx::query(r#"SELECT 1 FROM tag_synonyms WHERE name = $1"#)
+        .bind(synonym)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if taken.is_some() {
+        return Err(Box::new(QuestionBankErr::AlreadyExists(format!(
+            "Synonym {synonym}"
+        ))));
+    }
+
+    let source_id = find_tag(&mut tx, synonym).await?;
+    match source_id {
+        Some(source_id) => merge_tags(&mut tx, source_id, synonym, target_id).await?,
+        None => {
+            sqlx::query(r#"INSERT INTO tag_synonyms (name, tag_id) VALUES ($1, $2)"#)
+                .bind(synonym)
+                .bind(target_id)
+                .execute(&mut *tx)
+                .await?;
+            touch_tag(&mut tx, target_id).await?;
+        }
+    }
+    tx.commit().await?;
+
+    get(tags, name).await
+}
+
+/// Removes a synonym of a tag.
+///
+/// # Parameters
+///
+/// * `name`: The normalized name of the tag.
+/// * `synonym`: The synonym to remove.
+///
+/// # Returns
+///
+/// The updated tag, or a `QuestionBankErr` error if the tag or the synonym does not exist.
+pub async fn delete_synonym(
+    tags: &Pool<Postgres>,
+    name: &str,
+    synonym: &str,
+) -> Result<Tag, Box<dyn Error>> {
+    let result = sqlx::query(
+        r#"
+        WITH removed AS (
+            DELETE FROM tag_synonyms s
+            USING tags t
+            WHERE s.tag_id = t.id AND t.name = $1 AND s.name = $2
+            RETURNING s.tag_id
+        )
+        UPDATE tags SET updated_at = now() WHERE id IN (SELECT tag_id FROM removed)"#,
+    )
+    .bind(name)
+    .bind(synonym)
+    .execute(tags)
+    .await?;
+
+    let tag = get(tags, name).await?;
+    if result.rows_affected() == 0 {
+        return Err(Box::new(QuestionBankErr::SynonymDoesNotExist(
+            synonym.to_string(),
+        )));
+    }
+
+    Ok(tag)
+}
+
+/// Normalizes the tags of a question and replaces synonyms by their tag.
+///
+/// # Parameters
+///
+/// * `tags`: The tags as given.
+///
+/// # Returns
+///
+/// The tags to store, in the order they were first given, or `None` if none are left.
+pub async fn canonical_tags<'e, E>(
+    tags: E,
+    names: Option<Vec<String>>,
+) -> Result<Option<Vec<String>>, Box<dyn Error>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let Some(names) = normalize_tags(names) else {
+        return Ok(None);
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT COALESCE(t.name, n.name) AS name
+        FROM UNNEST($1::text[]) WITH ORDINALITY AS n(name, position)
+        LEFT JOIN tag_synonyms s ON s.name = n.name
+        LEFT JOIN tags t ON t.id = s.tag_id
+        ORDER BY n.position"#,
+    )
+    .bind(&names)
+    .fetch_all(tags)
+    .await?;
+
+    Ok(normalize_tags(Some(
+        rows.iter().map(|row| row.get("name")).collect(),
+    )))
+}
+
+/// Removes the tags no question has anymore, along with their description and synonyms.
+/// Only purging the trash does this, so a tag keeps its details while it is briefly unused.
+pub async fn delete_orphans<'e, E>(tags: E) -> Result<(), Box<dyn Error>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        r#"
+        DELETE FROM tags t
+        WHERE NOT EXISTS (SELECT 1 FROM question_tags qt WHERE qt.tag_id = t.id)"#,
+    )
+    .execute(tags)
+    .await?;
+
+    Ok(())
+}
+
+/// Removes a tag from every question that has it.
+///
+/// # Parameters
+///
+/// * `name`: The name of the tag.
+///
+/// # Returns
+///
+/// A `Result` indicating whether the tag was removed successfully.
+/// If the tag does not exist, returns a `QuestionBankErr` error.
+pub async fn delete(tags: &Pool<Postgres>, name: &str) -> Result<(), Box<dyn Error>> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM tags
+        WHERE name = $1
+        ;"#,
+    )
+    .bind(name)
+    .execute(tags)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Box::new(QuestionBankErr::TagDoesNotExist(name.to_string())));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use crate::{
+    entities::{token::*, user::User},
+    models::lib::*,
+};
+use time::OffsetDateTime;
+
+/// Columns of an `ApiToken`
+const TOKEN_COLUMNS: &str = "id, user_id, name, scopes, created_at, expires_at, last_used_at";
+
+/// Stores a new API token.
+///
+/// # Parameters
+///
+/// * `user_id`: The ID of the user the token acts as.
+/// * `name`: A name to tell the user's tokens apart.
+/// * `token_hash`: The hash of the token handed to the user.
+/// * `scopes`: What the token may be used for.
+/// * `expires_at`: When the token stops being valid, never if `None`.
+///
+/// # Returns
+///
+/// The new `ApiToken`.
+pub async fn create(
+    tokens: &Pool<Postgres>,
+    user_id: i32,
+    name: &str,
+    token_hash: &str,
+    scopes: &[Scope],
+    expires_at: Option<OffsetDateTime>,
+) -> Result<ApiToken, Box<dyn Error>> {
+    let scopes: Vec<&str> = scopes.iter().map(Scope::as_str).collect();
+    let token = sqlx::query(&format!(
+        r#"
+        INSERT INTO api_tokens (user_id, name, token_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {TOKEN_COLUMNS}"#
+    ))
+    .bind(user_id)
+    .bind(name)
+    .bind(token_hash)
+    .bind(scopes)
+    .bind(expires_at)
+    .fetch_one(tokens)
+    .await?;
+
+    Ok(<ApiToken as std::convert::From<PgRow>>::from(token))
+}
+
+/// Retrieves a user's API tokens.
+///
+/// # Parameters
+///
+/// * `user_id`: The ID of the user.
+///
+/// # Returns
+///
+/// The user's tokens, oldest first.
+pub async fn get_all(
+    tokens: &Pool<Postgres>,
+    user_id: i32,
+) -> Result<Vec<ApiToken>, Box<dyn Error>> {
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {TOKEN_COLUMNS}
+        FROM api_tokens
+        WHERE user_id = $1
+        ORDER BY id"#
+    ))
+    .bind(user_id)
+    .fetch_all(tokens)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(<ApiToken as std::convert::From<PgRow>>::from)
+        .collect())
+}
+
+/// Revokes one of a user's API tokens.
+///
+/// # Parameters
+///
+/// * `user_id`: The ID of the user owning the token.
+/// * `index`: The ID of the token.
+///
+/// # Returns
+///
+/// A `Result` indicating whether the token was revoked successfully.
+/// If the user has no such token, returns a `QuestionBankErr` error.
+pub async fn delete(
+    tokens: &Pool<Postgres>,
+    user_id: i32,
+    index: i32,
+) -> Result<(), Box<dyn Error>> {
+    let result = sqlx::query(r#"DELETE FROM api_tokens WHERE id = $1 AND user_id = $2"#)
+        .bind(index)
+        .bind(user_id)
+        .execute(tokens)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Box::new(QuestionBankErr::TokenDoesNotExist(
+            index.to_string(),
+        )));
+    }
+
+    Ok(())
+}
+
+/// Retrieves the user an API token acts as, and records that the token was used.
+///
+/// # Parameters
+///
+/// * `token_hash`: The hash of the token.
+///
+/// # Returns
+///
+/// The `User` and the token's scopes, or `None` if the token does not exist or has expired.
+pub async fn token_user(
+    tokens: &Pool<Postgres>,
+    token_hash: &str,
+) -> Result<Option<(User, Vec<Scope>)>, Box<dyn Error>> {
+    let row = sqlx::query(
+        r#"
+        WITH used AS (
+            UPDATE api_tokens
+            SET last_used_at = now()
+            WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > now())
+            RETURNING user_id, scopes
+        )
+        SELECT u.id, u.username, u.role, used.scopes
+        FROM used
+        JOIN users u ON used.user_id = u.id"#,
+    )
+    .bind(token_hash)
+    .fetch_optional(tokens)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let scopes: Vec<String> = row.get("scopes");
+    let scopes = scopes
+        .iter()
+        .filter_map(|scope| scope.parse().ok())
+        .collect();
+    Ok(Some((User::try_from(row)?, scopes)))
+}
@@ -0,0 +1,234 @@
+use crate::{
+    entities::{answer::Answer, question::Question},
+    models::{
+        answer_model,
+        lib::*,
+        question_model::{self, QUESTION_COLUMNS, QUESTION_TABLES},
+        tag_model,
+    },
+    pagination::{Keyed, Order, Page, PageQuery, SortKey},
+};
+use time::OffsetDateTime;
+
+/// The ordering of trashed questions: the most recently deleted first.
+pub const QUESTION_ORDER: Order = Order {
+    name: "deleted",
+    key: Some("(EXTRACT(EPOCH FROM q.deleted_at) * 1000000)::bigint"),
+    key_desc: true,
+    id: "q.id",
+    id_desc: true,
+};
+
+/// The ordering of trashed answers: the most recently deleted first.
+pub const ANSWER_ORDER: Order = Order {
+    name: "deleted",
+    key: Some("(EXTRACT(EPOCH FROM deleted_at) * 1000000)::bigint"),
+    key_desc: true,
+    id: "id",
+    id_desc: true,
+};
+
+/// Retrieves a page of the questions in the trash.
+///
+/// # Parameters
+///
+/// * `page`: Which page to retrieve, by offset or by cursor.
+///
+/// # Returns
+///
+/// The page of trashed questions, in [`QUESTION_ORDER`].
+pub async fn get_questions(
+    questions: &Pool<Postgres>,
+    page: &PageQuery,
+) -> Result<Page<Question>, Box<dyn Error>> {
+    let row = sqlx::query(r#"SELECT COUNT(*) FROM questions WHERE deleted_at IS NOT NULL"#)
+        .fetch_one(questions)
+        .await?;
+    let total_questions: i64 = row.get(0);
+
+    let mut query = QueryBuilder::new("SELECT ");
+    query.push(QUESTION_COLUMNS);
+    if let Some(key) = QUESTION_ORDER.key {
+        query.push(", ").push(key).push(" AS sort_key");
+    }
+    query.push(QUESTION_TABLES);
+    query.push(" WHERE q.deleted_at IS NOT NULL");
+    QUESTION_ORDER.push_keyset(&mut query, &page.position);
+    query.push(" GROUP BY q.id, a.id");
+    QUESTION_ORDER.push_order_limit(&mut query, page);
+    let rows = query.build().fetch_all(questions).await?;
+
+    let mut question_vec: Vec<Keyed<Question>> = Vec::new();
+    for row in rows {
+        question_vec.push(Keyed {
+            key: SortKey::from_row(&row),
+            id: row.get("id"),
+            item: <Question as std::convert::From<PgRow>>::from(row),
+        });
+    }
+
+    Ok(page.page(&QUESTION_ORDER, question_vec, total_questions))
+}
+
+/// Retrieves a page of the answers that were deleted on their own. Answers deleted along
+/// with their question are restored with it and only show up with the question.
+///
+/// # Parameters
+///
+/// * `page`: Which page to retrieve, by offset or by cursor.
+///
+/// # Returns
+///
+/// The page of trashed answers, in [`ANSWER_ORDER`].
+pub async fn get_answers(
+    answers: &Pool<Postgres>,
+    page: &PageQuery,
+) -> Result<Page<Answer>, Box<dyn Error>> {
+    const TRASHED: &str = r#"
+        FROM answers
+        WHERE deleted_at IS NOT NULL
+            AND question_id IN (SELECT id FROM questions WHERE deleted_at IS NULL)"#;
+
+    let row = sqlx::query(&format!("SELECT COUNT(*) {TRASHED}"))
+        .fetch_one(answers)
+        .await?;
+    let total_answers: i64 = row.get(0);
+
+    let mut query = QueryBuilder::new(
+        "SELECT id, answer, answer_html, key, question_id, accepted, author_id, score, created_at, \
+        updated_at, deleted_at",
+    );
+    if let Some(key) = ANSWER_ORDER.key {
+        query.push(", ").push(key).push(" AS sort_key");
+    }
+    query.push(TRASHED);
+    ANSWER_ORDER.push_keyset(&mut query, &page.position);
+    ANSWER_ORDER.push_order_limit(&mut query, page);
+    let rows = query.build().fetch_all(answers).await?;
+
+    let mut answer_vec: Vec<Keyed<Answer>> = Vec::new();
+    for row in rows {
+        answer_vec.push(Keyed {
+            key: SortKey::from_row(&row),
+            id: row.get("id"),
+            item: <Answer as std::convert::From<PgRow>>::from(row),
+        });
+    }
+
+    Ok(page.page(&ANSWER_ORDER, answer_vec, total_answers))
+}
+
+/// Takes a question out of the trash, along with the answers deleted with it.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the question.
+///
+/// # Returns
+///
+/// The restored question, or a `QuestionBankErr` error if the question does not exist or
+/// isn't in the trash.
+pub async fn restore_question(
+    questions: &Pool<Postgres>,
+    index: i32,
+) -> Result<Question, Box<dyn Error>> {
+    let mut tx = questions.begin().await?;
+    let row = sqlx::query(r#"SELECT deleted_at FROM questions WHERE id = $1 FOR UPDATE"#)
+        .bind(index)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
+    let deleted_at: OffsetDateTime = row
+        .get::<Option<OffsetDateTime>, _>("deleted_at")
+        .ok_or_else(|| QuestionBankErr::NotInTrash(format!("Question {index}")))?;
+
+    sqlx::query(r#"UPDATE questions SET deleted_at = NULL WHERE id = $1"#)
+        .bind(index)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        UPDATE answers SET deleted_at = NULL
+        WHERE question_id = $1 AND deleted_at = $2"#,
+    )
+    .bind(index)
+    .bind(deleted_at)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(question_model::get(questions, index).await?.remove(0))
+}
+
+/// Takes an answer out of the trash.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the answer.
+///
+/// # Returns
+///
+/// The restored answer, or a `QuestionBankErr` error if the answer does not exist, isn't in
+/// the trash or its question is.
+pub async fn restore_answer(
+    answers: &Pool<Postgres>,
+    index: i32,
+) -> Result<Answer, Box<dyn Error>> {
+    let mut tx = answers.begin().await?;
+    let row = sqlx::query(
+        r#"
+        SELECT a.question_id, a.deleted_at, q.deleted_at AS question_deleted_at
+        FROM answers a
+        JOIN questions q ON a.question_id = q.id
+        WHERE a.id = $1
+        FOR UPDATE OF a"#,
+    )
+    .bind(index)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| QuestionBankErr::AnswerDoesNotExist(index.to_string()))?;
+    if row.get::<Option<OffsetDateTime>, _>("deleted_at").is_none() {
+        return Err(Box::new(QuestionBankErr::NotInTrash(format!(
+            "Answer {index}"
+        ))));
+    }
+    if row
+        .get::<Option<OffsetDateTime>, _>("question_deleted_at")
+        .is_some()
+    {
+        let question_id: i32 = row.get("question_id");
+        return Err(Box::new(QuestionBankErr::InTrash(format!(
+            "Question {question_id}"
+        ))));
+    }
+
+    sqlx::query(r#"UPDATE answers SET deleted_at = NULL WHERE id = $1"#)
+        .bind(index)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    answer_model::get(answers, index).await
+}
+
+/// Removes the questions and answers that were moved to the trash before `before` for good,
+/// along with their revisions, votes and comments and the tags no question has anymore.
+///
+/// # Returns
+///
+/// The number of questions and answers removed.
+pub async fn purge(pool: &Pool<Postgres>, before: OffsetDateTime) -> Result<u64, Box<dyn Error>> {
+    let mut tx = pool.begin().await?;
+    let answers = sqlx::query(r#"DELETE FROM answers WHERE deleted_at < $1"#)
+        .bind(before)
+        .execute(&mut *tx)
+        .await?;
+    let questions = sqlx::query(r#"DELETE FROM questions WHERE deleted_at < $1"#)
+        .bind(before)
+        .execute(&mut *tx)
+        .await?;
+    tag_model::delete_orphans(&mut *tx).await?;
+    tx.commit().await?;
+
+    Ok(answers.rows_affected() + questions.rows_affected())
+}
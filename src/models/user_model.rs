@@ -0,0 +1,295 @@
+use crate::{
+    entities::user::*,
+    models::lib::*,
+    pagination::{Keyed, Order, Page, PageQuery, SortKey},
+};
+use time::OffsetDateTime;
+
+/// The ordering of the user list: in the order they registered.
+pub const ORDER: Order = Order {
+    name: "registered",
+    key: None,
+    key_desc: false,
+    id: "id",
+    id_desc: false,
+};
+
+/// Postgres error code of a unique constraint violation
+const UNIQUE_VIOLATION: &str = "23505";
+
+/// Adds a new member.
+///
+/// # Parameters
+///
+/// * `username`: The user's unique name.
+/// * `password_hash`: The argon2 PHC string of the user's password.
+///
+/// # Returns
+///
+/// The new `User`, or a `QuestionBankErr` error if the username is taken.
+pub async fn create(
+    users: &Pool<Postgres>,
+    username: &str,
+    password_hash: &str,
+) -> Result<User, Box<dyn Error>> {
+    let user = sqlx::query(
+        r#"
+        INSERT INTO users (username, password_hash, role)
+        VALUES ($1, $2, 'member')
+        RETURNING id, username, role"#,
+    )
+    .bind(username)
+    .bind(password_hash)
+    .fetch_one(users)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.code().as_deref() == Some(UNIQUE_VIOLATION) => {
+            Box::new(QuestionBankErr::AlreadyExists(format!("User {username}"))) as Box<dyn Error>
+        }
+        e => Box::new(e),
+    })?;
+
+    Ok(User::try_from(user)?)
+}
+
+/// Retrieves a user and their password hash by username.
+///
+/// # Parameters
+///
+/// * `username`: The name of the user.
+///
+/// # Returns
+///
+/// The `User` and their password hash, or `None` if there is no such user.
+pub async fn get_credentials(
+    users: &Pool<Postgres>,
+    username: &str,
+) -> Result<Option<(User, String)>, Box<dyn Error>> {
+    let user = sqlx::query(
+        r#"
+        SELECT id, username, role, password_hash
+        FROM users
+        WHERE username = $1"#,
+    )
+    .bind(username)
+    .fetch_optional(users)
+    .await?;
+
+    let Some(row) = user else {
+        return Ok(None);
+    };
+    let password_hash: String = row.get("password_hash");
+    Ok(Some((User::try_from(row)?, password_hash)))
+}
+
+/// Stores a new login session.
+///
+/// # Parameters
+///
+/// * `user_id`: The ID of the user who logged in.
+/// * `token_hash`: The hash of the session token handed to the client.
+/// * `expires_at`: When the session stops being valid.
+pub async fn create_session(
+    users: &Pool<Postgres>,
+    user_id: i32,
+    token_hash: &str,
+    expires_at: OffsetDateTime,
+) -> Result<(), Box<dyn Error>> {
+    sqlx::query(r#"INSERT INTO sessions (token_hash, user_id, expires_at) VALUES ($1, $2, $3)"#)
+        .bind(token_hash)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(users)
+        .await?;
+
+    // Clean up the user's expired sessions while we are at it
+    sqlx::query(r#"DELETE FROM sessions WHERE user_id = $1 AND expires_at <= now()"#)
+        .bind(user_id)
+        .execute(users)
+        .await?;
+
+    Ok(())
+}
+
+/// Retrieves the user a session belongs to.
+///
+/// # Parameters
+///
+/// * `token_hash`: The hash of the session token.
+///
+/// # Returns
+///
+/// The logged in `User`, or `None` if the session does not exist or has expired.
+pub async fn session_user(
+    users: &Pool<Postgres>,
+    token_hash: &str,
+) -> Result<Option<User>, Box<dyn Error>> {
+    let user = sqlx::query(
+        r#"
+        SELECT u.id, u.username, u.role
+        FROM sessions s
+        JOIN users u ON s.user_id = u.id
+        WHERE s.token_hash = $1 AND s.expires_at > now()"#,
+    )
+    .bind(token_hash)
+    .fetch_optional(users)
+    .await?;
+
+    Ok(user.map(User::try_from).transpose()?)
+}
+
+/// Removes a login session.
+///
+/// # Parameters
+///
+/// * `token_hash`: The hash of the session token.
+pub async fn delete_session(
+    users: &Pool<Postgres>,
+    token_hash: &str,
+) -> Result<(), Box<dyn Error>> {
+    sqlx::query(r#"DELETE FROM sessions WHERE token_hash = $1"#)
+        .bind(token_hash)
+        .execute(users)
+        .await?;
+
+    Ok(())
+}
+
+/// Retrieves a page of users.
+///
+/// # Parameters
+///
+/// * `page`: Which page to retrieve, by offset or by cursor.
+///
+/// # Returns
+///
+/// The page of users, in [`ORDER`].
+pub async fn get_all(
+    users: &Pool<Postgres>,
+    page: &PageQuery,
+) -> Result<Page<User>, Box<dyn Error>> {
+    let row = sqlx::query(r#"SELECT COUNT(*) FROM users;"#)
+        .fetch_one(users)
+        .await?;
+    let total_users: i64 = row.get(0);
+
+    let mut query = QueryBuilder::new("SELECT id, username, role FROM users WHERE TRUE");
+    ORDER.push_keyset(&mut query, &page.position);
+    ORDER.push_order_limit(&mut query, page);
+    let rows = query.build().fetch_all(users).await?;
+
+    let mut user_vec: Vec<Keyed<User>> = Vec::new();
+    for row in rows {
+        user_vec.push(Keyed {
+            key: SortKey::Null,
+            id: row.get("id"),
+            item: User::try_from(row)?,
+        });
+    }
+
+    Ok(page.page(&ORDER, user_vec, total_users))
+}
+
+/// Fails if `index` is the only admin, so that there is always one left to manage users.
+/// The admins are locked until the transaction ends, so two admins can't demote each other
+/// at once.
+async fn check_not_last_admin(
+    tx: &mut Transaction<'_, Postgres>,
+    index: i32,
+) -> Result<(), Box<dyn Error>> {
+    let admins: Vec<i32> =
+        sqlx::query_scalar(r#"SELECT id FROM users WHERE role = 'admin' FOR UPDATE"#)
+            .fetch_all(&mut **tx)
+            .await?;
+    if admins == [index] {
+        return Err(Box::new(QuestionBankErr::LastAdmin(index.to_string())));
+    }
+
+    Ok(())
+}
+
+/// Changes the role of a user.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the user.
+/// * `role`: The user's new role.
+///
+/// # Returns
+///
+/// The updated user, or a `QuestionBankErr` error if the user does not exist or is the
+/// last admin and would lose the role.
+pub async fn set_role(
+    users: &Pool<Postgres>,
+    index: i32,
+    role: Role,
+) -> Result<User, Box<dyn Error>> {
+    let mut tx = users.begin().await?;
+    if role != Role::Admin {
+        check_not_last_admin(&mut tx, index).await?;
+    }
+    let user = sqlx::query(
+        r#"
+        UPDATE users
+        SET role = $1
+        WHERE id = $2
+        RETURNING id, username, role"#,
+    )
+    .bind(role.as_str())
+    .bind(index)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| QuestionBankErr::UserDoesNotExist(index.to_string()))?;
+    tx.commit().await?;
+
+    Ok(User::try_from(user)?)
+}
+
+/// Removes a user along with their sessions and votes. Their questions and answers are
+/// kept without an author.
+///
+/// # Parameters
+///
+/// * `index`: The ID of the user.
+///
+/// # Returns
+///
+/// A `Result` indicating whether the user was removed successfully.
+/// If the user does not exist or is the last admin, returns a `QuestionBankErr` error.
+pub async fn delete(users: &Pool<Postgres>, index: i32) -> Result<(), Box<dyn Error>> {
+    let mut tx = users.begin().await?;
+    check_not_last_admin(&mut tx, index).await?;
+    // The votes go with the user, take them out of the scores first
+    sqlx::query(
+        r#"
+        UPDATE questions q SET score = q.score - v.value
+        FROM question_votes v
+        WHERE v.question_id = q.id AND v.user_id = $1"#,
+    )
+    .bind(index)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        UPDATE answers a SET score = a.score - v.value
+        FROM answer_votes v
+        WHERE v.answer_id = a.id AND v.user_id = $1"#,
+    )
+    .bind(index)
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query(r#"DELETE FROM users WHERE id = $1"#)
+        .bind(index)
+        .execute(&mut *tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Box::new(QuestionBankErr::UserDoesNotExist(
+            index.to_string(),
+        )));
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::{entities::vote::*, models::lib::*};
+use std::collections::HashMap;
+
+/// Sets, changes or removes a user's vote on a question or answer, and updates its score.
+///
+/// # Parameters
+///
+/// * `target`: Whether a question or an answer is voted on.
+/// * `index`: The ID of the question or answer.
+/// * `user_id`: The ID of the voting user.
+/// * `value`: 1 for an upvote, -1 for a downvote, `None` to undo the user's vote.
+///
+/// # Returns
+///
+/// The new score and the user's vote, or a `QuestionBankErr` error if the item does not exist.
+pub async fn set_vote(
+    votes: &Pool<Postgres>,
+    target: VoteTarget,
+    index: i32,
+    user_id: i32,
+    value: Option<i16>,
+) -> Result<Vote, Box<dyn Error>> {
+    let table = target.table();
+    let votes_table = target.votes_table();
+    let column = target.votes_column();
+
+    let mut tx = votes.begin().await?;
+    // Locking the item serializes concurrent votes on it
+    sqlx::query(&format!(
+        "SELECT id FROM {table} WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+    ))
+    .bind(index)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| match target {
+        VoteTarget::Question => QuestionBankErr::DoesNotExist(index.to_string()),
+        VoteTarget::Answer => QuestionBankErr::AnswerDoesNotExist(index.to_string()),
+    })?;
+
+    let previous: Option<i16> = sqlx::query(&format!(
+        "SELECT value FROM {votes_table} WHERE {column} = $1 AND user_id = $2"
+    ))
+    .bind(index)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .map(|row| row.get("value"));
+
+    match value {
+        Some(value) => {
+            sqlx::query(&format!(
+                r#"
+                INSERT INTO {votes_table} ({column}, user_id, value) VALUES ($1, $2, $3)
+                ON CONFLICT ({column}, user_id) DO UPDATE SET value = EXCLUDED.value"#
+            ))
+            .bind(index)
+            .bind(user_id)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+        }
+        None => {
+            sqlx::query(&format!(
+                "DELETE FROM {votes_table} WHERE {column} = $1 AND user_id = $2"
+            ))
+            .bind(index)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    let change = i32::from(value.unwrap_or(0)) - i32::from(previous.unwrap_or(0));
+    let row = sqlx::query(&format!(
+        "UPDATE {table} SET score = score + $1 WHERE id = $2 RETURNING score"
+    ))
+    .bind(change)
+    .bind(index)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Vote {
+        score: row.get("score"),
+        my_vote: value,
+    })
+}
+
+/// Retrieves a user's votes on some questions or answers.
+///
+/// # Parameters
+///
+/// * `target`: Whether the votes are on questions or on answers.
+/// * `user_id`: The ID of the user.
+/// * `ids`: The IDs of the questions or answers.
+///
+/// # Returns
+///
+/// The user's vote by question or answer ID, for the ones they voted on.
+pub async fn user_votes(
+    votes: &Pool<Postgres>,
+    target: VoteTarget,
+    user_id: i32,
+    ids: &[i32],
+) -> Result<HashMap<i32, i16>, Box<dyn Error>> {
+    let column = target.votes_column();
+    let rows = sqlx::query(&format!(
+        "SELECT {column} AS id, value FROM {} WHERE user_id = $1 AND {column} = ANY($2)",
+        target.votes_table()
+    ))
+    .bind(user_id)
+    .bind(ids)
+    .fetch_all(votes)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("id"), row.get("value")))
+        .collect())
+}
@@ -0,0 +1,399 @@
+use crate::{
+    auth::CurrentUser,
+    models::errors::{QuestionBankErr, QuestionBankError},
+    pagination::Page,
+    views::pages::{Account, ErrorPage},
+};
+use askama_axum::IntoResponse;
+use axum::{
+    async_trait,
+    body::Body,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::Response,
+    Json,
+};
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use std::{convert::Infallible, error::Error, io};
+
+/// A representation a route can respond with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Html,
+    Csv,
+    NdJson,
+}
+
+impl Format {
+    /// The media type of the representation.
+    pub fn media_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Html => "text/html",
+            Format::Csv => "text/csv",
+            Format::NdJson => "application/x-ndjson",
+        }
+    }
+}
+
+/// The representations of a single item
+pub const ITEM_FORMATS: &[Format] = &[Format::Json, Format::NdJson];
+
+/// The representations of a list of items
+pub const LIST_FORMATS: &[Format] = &[Format::Json, Format::Csv, Format::NdJson];
+
+/// The representations of a question, which also has a web page
+pub const PAGE_FORMATS: &[Format] = &[Format::Json, Format::Html, Format::NdJson];
+
+/// The representations of the question list, which also has a web page
+pub const PAGE_LIST_FORMATS: &[Format] = &[Format::Json, Format::Html, Format::Csv, Format::NdJson];
+
+/// Items that can be written as the rows of a CSV file
+pub trait Tabular {
+    /// The header row
+    const COLUMNS: &'static [&'static str];
+
+    /// The fields of the item, in the order of [`Tabular::COLUMNS`]
+    fn row(&self) -> Vec<String>;
+}
+
+/// One media range of an `Accept` header, such as `text/*;q=0.5`
+#[derive(Debug, Clone)]
+struct MediaRange {
+    kind: String,
+    subtype: String,
+    /// The `q` parameter, in thousandths
+    quality: u16,
+}
+
+impl MediaRange {
+    /// Parses a media range, ignoring the parameters other than `q`.
+    fn parse(range: &str) -> Option<Self> {
+        let mut parts = range.split(';');
+        let (kind, subtype) = parts.next()?.trim().split_once('/')?;
+        let mut quality = 1000;
+        for param in parts {
+            if let Some((name, value)) = param.split_once('=') {
+                if name.trim().eq_ignore_ascii_case("q") {
+                    let q: f32 = value.trim().parse().ok()?;
+                    quality = (q.clamp(0.0, 1.0) * 1000.0).round() as u16;
+                }
+            }
+        }
+        Some(Self {
+            kind: kind.trim().to_ascii_lowercase(),
+            subtype: subtype.trim().to_ascii_lowercase(),
+            quality,
+        })
+    }
+
+    /// How specifically the range names `media_type`: 3 for an exact match, 2 for `type/*`,
+    /// 1 for `*/*` and `None` if it doesn't match at all.
+    fn specificity(&self, media_type: &str) -> Option<u8> {
+        let (kind, subtype) = media_type.split_once('/')?;
+        match (self.kind.as_str(), self.subtype.as_str()) {
+            ("*", "*") => Some(1),
+            (k, "*") if k == kind => Some(2),
+            (k, s) if k == kind && s == subtype => Some(3),
+            _ => None,
+        }
+    }
+}
+
+/// The media ranges the client accepts, from the `Accept` header
+#[derive(Debug, Clone, Default)]
+pub struct Accept(Vec<MediaRange>);
+
+impl Accept {
+    /// Parses the value of an `Accept` header, skipping malformed ranges.
+    pub fn parse(header: &str) -> Self {
+        Self(header.split(',').filter_map(MediaRange::parse).collect())
+    }
+
+    /// Picks the representation the client prefers among the ones a route offers.
+    ///
+    /// The quality of a format comes from the most specific range matching it. Ties go to
+    /// the format listed first in `offered`, so clients sending `*/*` or no `Accept` header
+    /// at all get the first one.
+    ///
+    /// # Parameters
+    ///
+    /// * `offered`: The formats of the route, the default first.
+    ///
+    /// # Returns
+    ///
+    /// The chosen format, or `NotAcceptable` if the client accepts none of them.
+    pub fn choose(&self, offered: &[Format]) -> Result<Format, QuestionBankErr> {
+        if self.0.is_empty() {
+            return Ok(offered[0]);
+        }
+
+        let mut best: Option<(Format, (u16, u8))> = None;
+        for &format in offered {
+            let quality = self
+                .0
+                .iter()
+                .filter_map(|range| {
+                    range
+                        .specificity(format.media_type())
+                        .map(|specificity| (specificity, range.quality))
+                })
+                .max()
+                .map(|(specificity, quality)| (quality, specificity));
+            if let Some(quality) = quality.filter(|(q, _)| *q > 0) {
+                let better = match best {
+                    Some((_, best)) => quality > best,
+                    None => true,
+                };
+                if better {
+                    best = Some((format, quality));
+                }
+            }
+        }
+
+        best.map(|(format, _)| format).ok_or_else(|| {
+            let types: Vec<&str> = offered.iter().map(|format| format.media_type()).collect();
+            QuestionBankErr::NotAcceptable(types.join(", "))
+        })
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Accept {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts
+            .headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",");
+        Ok(Self::parse(&accept))
+    }
+}
+
+/// Writes responses in the representation negotiated for the request, replacing plain
+/// `Json` responses on the routes that support several formats
+#[derive(Debug)]
+pub struct Responder {
+    pub format: Format,
+    user: Option<CurrentUser>,
+}
+
+impl Responder {
+    /// Negotiates the format of the response.
+    ///
+    /// # Parameters
+    ///
+    /// * `accept`: The `Accept` header of the request.
+    /// * `offered`: The formats of the route, the default first.
+    /// * `user`: The logged in user, shown on HTML error pages.
+    ///
+    /// # Returns
+    ///
+    /// The responder, or a `406 Not Acceptable` response.
+    pub fn negotiate(
+        accept: &Accept,
+        offered: &[Format],
+        user: &Option<CurrentUser>,
+    ) -> Result<Self, Box<Response>> {
+        match accept.choose(offered) {
+            Ok(format) => Ok(Self {
+                format,
+                user: user.clone(),
+            }),
+            Err(e) => Err(Box::new(QuestionBankError::response(
+                StatusCode::NOT_ACCEPTABLE,
+                Box::new(e),
+            ))),
+        }
+    }
+
+    /// Creates an error response: an error page for HTML and the JSON error otherwise.
+    pub fn error(&self, status: StatusCode, error: Box<dyn Error>) -> Response {
+        match self.format {
+            Format::Html => (
+                status,
+                ErrorPage::new(
+                    status,
+                    self.user.as_ref().map(Account::from),
+                    error.as_ref(),
+                ),
+            )
+                .into_response(),
+            _ => QuestionBankError::response(status, error),
+        }
+    }
+
+    /// Creates the response for a single item. HTML pages are rendered by the handlers, so
+    /// they fall back to JSON here.
+    pub fn item<T: Serialize + Tabular>(&self, item: &T) -> Response {
+        match self.format {
+            Format::Csv => self.csv(HeaderMap::new(), std::slice::from_ref(item)),
+            Format::NdJson => match ndjson_line(item) {
+                Ok(line) => self.body(HeaderMap::new(), line),
+                Err(e) => {
+                    QuestionBankError::response(StatusCode::INTERNAL_SERVER_ERROR, Box::new(e))
+                }
+            },
+            Format::Json | Format::Html => (StatusCode::OK, Json(item)).into_response(),
+        }
+    }
+
+    /// Creates the response for a list of items that isn't paginated.
+    pub fn items<T: Serialize + Tabular + Send + 'static>(&self, items: Vec<T>) -> Response {
+        match self.format {
+            Format::Csv => self.csv(HeaderMap::new(), &items),
+            Format::NdJson => self.ndjson(HeaderMap::new(), items),
+            Format::Json | Format::Html => (StatusCode::OK, Json(items)).into_response(),
+        }
+    }
+
+    /// Creates the response for a page of items, with the headers of [`Page::headers`].
+    ///
+    /// # Parameters
+    ///
+    /// * `page`: The page of items.
+    /// * `uri`: The URI of the request, used as the base of the links.
+    pub fn page<T: Serialize + Tabular + Send + 'static>(
+        &self,
+        page: Page<T>,
+        uri: &Uri,
+    ) -> Response {
+        match self.format {
+            Format::Csv => self.csv(page.headers(uri), &page.items),
+            Format::NdJson => self.ndjson(page.headers(uri), page.items),
+            Format::Json | Format::Html => page.response(uri),
+        }
+    }
+
+    /// Writes the items as CSV, with a header row.
+    fn csv<T: Tabular>(&self, headers: HeaderMap, items: &[T]) -> Response {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        let written = writer.write_record(T::COLUMNS).and_then(|()| {
+            items
+                .iter()
+                .try_for_each(|item| writer.write_record(item.row()))
+        });
+        match written
+            .map_err(Box::<dyn Error>::from)
+            .and_then(|()| writer.into_inner().map_err(|e| e.to_string().into()))
+        {
+            Ok(body) => self.body(headers, body),
+            Err(e) => QuestionBankError::response(StatusCode::INTERNAL_SERVER_ERROR, e),
+        }
+    }
+
+    /// Streams the items as newline delimited JSON, one item per line, serializing each
+    /// line as it is sent.
+    fn ndjson<T: Serialize + Send + 'static>(&self, headers: HeaderMap, items: Vec<T>) -> Response {
+        let lines = stream::iter(items).map(|item| ndjson_line(&item).map_err(io::Error::from));
+        self.body(headers, Body::from_stream(lines))
+    }
+
+    /// Sends `body` with the content type of the format.
+    fn body(&self, mut headers: HeaderMap, body: impl Into<Body>) -> Response {
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(self.format.media_type()),
+        );
+        (StatusCode::OK, headers, body.into()).into_response()
+    }
+}
+
+/// An item as a line of newline delimited JSON.
+fn ndjson_line<T: Serialize>(item: &T) -> Result<Vec<u8>, serde_json::Error> {
+    let mut line = serde_json::to_vec(item)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choose(header: &str, offered: &[Format]) -> Option<Format> {
+        Accept::parse(header).choose(offered).ok()
+    }
+
+    #[test]
+    fn parses_media_ranges_and_their_quality() {
+        let range = MediaRange::parse(" Text/HTML ; charset=utf-8; q=0.5").unwrap();
+        assert_eq!(
+            (range.kind.as_str(), range.subtype.as_str()),
+            ("text", "html")
+        );
+        assert_eq!(range.quality, 500);
+
+        assert_eq!(MediaRange::parse("text/csv;q=2").unwrap().quality, 1000);
+        assert_eq!(MediaRange::parse("text/csv;q=0.0015").unwrap().quality, 2);
+        assert!(MediaRange::parse("text/csv;q=high").is_none());
+        assert!(MediaRange::parse("json").is_none());
+        assert_eq!(Accept::parse("json, text/csv,,").0.len(), 1);
+    }
+
+    #[test]
+    fn wildcards_are_less_specific() {
+        let any = MediaRange::parse("*/*").unwrap();
+        let text = MediaRange::parse("text/*").unwrap();
+        let csv = MediaRange::parse("text/csv").unwrap();
+        assert_eq!(any.specificity("text/csv"), Some(1));
+        assert_eq!(text.specificity("text/csv"), Some(2));
+        assert_eq!(csv.specificity("text/csv"), Some(3));
+        assert_eq!(text.specificity("application/json"), None);
+        assert_eq!(csv.specificity("text/html"), None);
+    }
+
+    #[test]
+    fn defaults_to_the_first_format() {
+        assert_eq!(choose("", LIST_FORMATS), Some(Format::Json));
+        assert_eq!(choose("*/*", PAGE_FORMATS), Some(Format::Json));
+        assert_eq!(choose("text/*", PAGE_LIST_FORMATS), Some(Format::Html));
+    }
+
+    #[test]
+    fn picks_the_highest_quality() {
+        assert_eq!(
+            choose("application/json;q=0.5, text/csv", LIST_FORMATS),
+            Some(Format::Csv)
+        );
+        assert_eq!(
+            choose("text/html;q=0.9, */*;q=0.1", PAGE_FORMATS),
+            Some(Format::Html)
+        );
+        assert_eq!(
+            choose("application/x-ndjson", ITEM_FORMATS),
+            Some(Format::NdJson)
+        );
+    }
+
+    #[test]
+    fn the_most_specific_range_sets_the_quality() {
+        // `text/csv;q=0` rules out CSV even though `*/*` would accept it
+        assert_eq!(
+            choose("text/csv;q=0, */*;q=0.1", &[Format::Csv, Format::Json]),
+            Some(Format::Json)
+        );
+        assert_eq!(
+            choose("text/*;q=0.2, text/html", PAGE_LIST_FORMATS),
+            Some(Format::Html)
+        );
+    }
+
+    #[test]
+    fn nothing_acceptable_names_the_offered_formats() {
+        let error = Accept::parse("image/png, text/csv;q=0")
+            .choose(ITEM_FORMATS)
+            .unwrap_err();
+        assert!(matches!(error, QuestionBankErr::NotAcceptable(_)));
+        assert_eq!(
+            error.to_string(),
+            "Not acceptable: this resource is available as application/json, application/x-ndjson"
+        );
+        assert_eq!(choose("*/*;q=0", LIST_FORMATS), None);
+    }
+}
@@ -1,17 +1,892 @@
-use serde::Deserialize;
+use crate::{
+    entities::{question::Question, tag::normalize_tag},
+    models::errors::QuestionBankErr,
+    repositories::tag_repo::TagRepository,
+};
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{postgres::PgRow, Postgres, QueryBuilder, Row};
+use std::{cmp::Ordering, error::Error};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Deserialize, Default)]
+/// Largest page size a client may ask for
+pub const MAX_LIMIT: i32 = 100;
+
+/// Query parameters selecting a page of a list, either by page number (offset mode) or by
+/// an opaque cursor taken from a previous response (keyset mode)
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct Pagination {
-    #[serde(default = "default_page")]
-    pub page: i32,
+    /// Page number in offset mode, starting at 1
+    #[param(minimum = 1)]
+    pub page: Option<i32>,
+    /// Number of items per page
+    #[param(minimum = 1, maximum = 100)]
     #[serde(default = "default_limit")]
     pub limit: i32,
-}
-
-fn default_page() -> i32 {
-    1
+    /// Cursor to return the page after, from the `next` link of a previous page
+    pub after: Option<String>,
+    /// Cursor to return the page before, from the `prev` link of a previous page
+    pub before: Option<String>,
 }
 
 fn default_limit() -> i32 {
     10
 }
+
+impl Pagination {
+    /// Checks the parameters and decodes the cursor, if any.
+    ///
+    /// # Parameters
+    ///
+    /// * `order`: The ordering of the list being paginated, cursors from other orderings are
+    ///   rejected.
+    ///
+    /// # Returns
+    ///
+    /// The validated `PageQuery`, or a `QuestionBankErr` error if the parameters are out of
+    /// bounds or conflict with each other.
+    pub fn validate(&self, order: &Order) -> Result<PageQuery, QuestionBankErr> {
+        if !(1..=MAX_LIMIT).contains(&self.limit) {
+            return Err(QuestionBankErr::PaginationInvalid(format!(
+                "limit must be between 1 and {MAX_LIMIT}"
+            )));
+        }
+
+        let position = match (self.page, &self.after, &self.before) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) | (_, Some(_), Some(_)) => {
+                return Err(QuestionBankErr::PaginationInvalid(
+                    "only one of page, after and before can be used".to_string(),
+                ))
+            }
+            (_, Some(cursor), None) => Position::After(Cursor::decode(cursor, order)?),
+            (_, None, Some(cursor)) => Position::Before(Cursor::decode(cursor, order)?),
+            (page, None, None) => {
+                let page = page.unwrap_or(1);
+                if page < 1 {
+                    return Err(QuestionBankErr::PaginationInvalid(
+                        "page must be at least 1".to_string(),
+                    ));
+                }
+                Position::Offset((page as i64 - 1) * self.limit as i64)
+            }
+        };
+
+        Ok(PageQuery {
+            position,
+            limit: self.limit as i64,
+        })
+    }
+}
+
+/// Where a page starts
+#[derive(Debug, Clone, PartialEq)]
+pub enum Position {
+    /// Skip this many items (offset mode)
+    Offset(i64),
+    /// The items right after the cursor
+    After(Cursor),
+    /// The items right before the cursor
+    Before(Cursor),
+}
+
+/// A validated page request
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageQuery {
+    pub position: Position,
+    pub limit: i64,
+}
+
+/// How a list is ordered: by an optional sort key, then by id to break ties.
+///
+/// The SQL expressions are used by the Postgres backend to build keyset conditions, they
+/// have to select the same values the in-memory backend sorts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Order {
+    /// Name of the ordering, stored in cursors
+    pub name: &'static str,
+    /// SQL expression of the sort key, selected as `sort_key`
+    pub key: Option<&'static str>,
+    pub key_desc: bool,
+    /// SQL expression of the id
+    pub id: &'static str,
+    pub id_desc: bool,
+}
+
+impl Order {
+    /// Compares two (sort key, id) positions in this ordering.
+    pub fn compare(&self, a: (&SortKey, i32), b: (&SortKey, i32)) -> Ordering {
+        let key = a.0.compare(b.0);
+        let key = if self.key_desc { key.reverse() } else { key };
+        let id = if self.id_desc {
+            b.1.cmp(&a.1)
+        } else {
+            a.1.cmp(&b.1)
+        };
+        key.then(id)
+    }
+
+    /// Appends `AND <position is past the cursor>` to a query, in the direction of `position`.
+    pub fn push_keyset(&self, query: &mut QueryBuilder<'_, Postgres>, position: &Position) {
+        let (cursor, forward) = match position {
+            Position::Offset(_) => return,
+            Position::After(cursor) => (cursor, true),
+            Position::Before(cursor) => (cursor, false),
+        };
+        let op = |desc: bool| if desc != forward { " > " } else { " < " };
+
+        query.push(" AND (");
+        if let Some(key) = self.key {
+            query.push(key).push(op(self.key_desc));
+            cursor.key.push_bind(query);
+            query.push(" OR (").push(key).push(" = ");
+            cursor.key.push_bind(query);
+            query.push(" AND ");
+        }
+        query
+            .push(self.id)
+            .push(op(self.id_desc))
+            .push_bind(cursor.id);
+        if self.key.is_some() {
+            query.push(")");
+        }
+        query.push(")");
+    }
+
+    /// Appends the `ORDER BY`, `LIMIT` and `OFFSET` of a page to a query.
+    ///
+    /// Pages before a cursor are read backwards, and one extra row is read past cursor
+    /// pages to know whether there are more.
+    pub fn push_order_limit(&self, query: &mut QueryBuilder<'_, Postgres>, page: &PageQuery) {
+        let forward = !matches!(page.position, Position::Before(_));
+        let dir = |desc: bool| if desc == forward { " DESC" } else { " ASC" };
+
+        query.push(" ORDER BY ");
+        if let Some(key) = self.key {
+            query.push(key).push(dir(self.key_desc)).push(", ");
+        }
+        query.push(self.id).push(dir(self.id_desc));
+
+        match page.position {
+            Position::Offset(start) => {
+                query
+                    .push(" LIMIT ")
+                    .push_bind(page.limit)
+                    .push(" OFFSET ")
+                    .push_bind(start);
+            }
+            _ => {
+                query.push(" LIMIT ").push_bind(page.limit + 1);
+            }
+        }
+    }
+}
+
+/// The sort key value of an item, as stored in a cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SortKey {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl SortKey {
+    /// Reads the `sort_key` column of a row, `Null` if there is none.
+    pub fn from_row(row: &PgRow) -> Self {
+        if let Ok(key) = row.try_get::<bool, _>("sort_key") {
+            SortKey::Bool(key)
+        } else if let Ok(key) = row.try_get::<i64, _>("sort_key") {
+            SortKey::Int(key)
+        } else if let Ok(key) = row.try_get::<i32, _>("sort_key") {
+            SortKey::Int(key.into())
+        } else if let Ok(key) = row.try_get::<f32, _>("sort_key") {
+            SortKey::Float(key as f64)
+        } else if let Ok(key) = row.try_get::<String, _>("sort_key") {
+            SortKey::Text(key)
+        } else {
+            SortKey::Null
+        }
+    }
+
+    /// The sort key of a timestamp: its microseconds since the epoch, the precision Postgres
+    /// stores. The SQL key of a timestamp column is `(EXTRACT(EPOCH FROM column) * 1000000)::bigint`.
+    pub fn timestamp(time: OffsetDateTime) -> Self {
+        SortKey::Int((time.unix_timestamp_nanos() / 1000) as i64)
+    }
+
+    fn compare(&self, other: &SortKey) -> Ordering {
+        match (self, other) {
+            (SortKey::Bool(a), SortKey::Bool(b)) => a.cmp(b),
+            (SortKey::Int(a), SortKey::Int(b)) => a.cmp(b),
+            (SortKey::Float(a), SortKey::Float(b)) => a.total_cmp(b),
+            (SortKey::Text(a), SortKey::Text(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn push_bind(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        match self {
+            SortKey::Null => query.push("NULL"),
+            SortKey::Bool(key) => query.push_bind(*key),
+            SortKey::Int(key) => query.push_bind(*key),
+            SortKey::Float(key) => query.push_bind(*key),
+            SortKey::Text(key) => query.push_bind(key.clone()),
+        };
+    }
+}
+
+/// The position of an item in an ordered list, handed to clients as an opaque string
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    #[serde(rename = "o")]
+    pub order: String,
+    #[serde(rename = "k")]
+    pub key: SortKey,
+    #[serde(rename = "i")]
+    pub id: i32,
+}
+
+impl Cursor {
+    /// Encodes the cursor as URL safe base64 JSON.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Decodes a cursor made by `encode`.
+    ///
+    /// # Returns
+    ///
+    /// The cursor, or a `QuestionBankErr` error if it is malformed or belongs to another
+    /// ordering than `order`.
+    pub fn decode(cursor: &str, order: &Order) -> Result<Self, QuestionBankErr> {
+        let invalid = || QuestionBankErr::PaginationInvalid("invalid cursor".to_string());
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let cursor: Cursor = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        if cursor.order != order.name {
+            return Err(QuestionBankErr::PaginationInvalid(
+                "cursor belongs to a different sort order".to_string(),
+            ));
+        }
+        Ok(cursor)
+    }
+}
+
+/// An item along with its position in the list
+#[derive(Debug, Clone)]
+pub struct Keyed<T> {
+    pub key: SortKey,
+    pub id: i32,
+    pub item: T,
+}
+
+/// One page of a list
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items in the whole (filtered) list
+    pub total: i64,
+    /// Cursor for the following page, if there is one
+    pub next: Option<Cursor>,
+    /// Cursor for the preceding page, if there is one
+    pub prev: Option<Cursor>,
+}
+
+impl PageQuery {
+    /// Picks this page out of a fully sorted list, the way the Postgres backend reads it:
+    /// cursor pages come with one extra item and pages before a cursor come backwards.
+    pub fn slice<T>(&self, order: &Order, sorted: Vec<Keyed<T>>) -> Vec<Keyed<T>> {
+        let position = |cursor: &Cursor, item: &Keyed<T>| {
+            order.compare((&item.key, item.id), (&cursor.key, cursor.id))
+        };
+        match &self.position {
+            Position::Offset(start) => sorted
+                .into_iter()
+                .skip(*start as usize)
+                .take(self.limit as usize)
+                .collect(),
+            Position::After(cursor) => sorted
+                .into_iter()
+                .filter(|item| position(cursor, item) == Ordering::Greater)
+                .take(self.limit as usize + 1)
+                .collect(),
+            Position::Before(cursor) => sorted
+                .into_iter()
+                .rev()
+                .filter(|item| position(cursor, item) == Ordering::Less)
+                .take(self.limit as usize + 1)
+                .collect(),
+        }
+    }
+
+    /// Turns the rows read for this page into a `Page`.
+    ///
+    /// # Parameters
+    ///
+    /// * `order`: The ordering the rows were read in.
+    /// * `rows`: The rows as read by `push_order_limit` or `slice`.
+    /// * `total`: Number of items in the whole list.
+    pub fn page<T>(&self, order: &Order, mut rows: Vec<Keyed<T>>, total: i64) -> Page<T> {
+        let more = rows.len() as i64 > self.limit;
+        rows.truncate(self.limit as usize);
+        let (has_prev, has_next) = match self.position {
+            Position::Offset(start) => (start > 0, start + (rows.len() as i64) < total),
+            Position::After(_) => (true, more),
+            Position::Before(_) => {
+                rows.reverse();
+                (more, true)
+            }
+        };
+
+        let cursor = |item: &Keyed<T>| Cursor {
+            order: order.name.to_string(),
+            key: item.key.clone(),
+            id: item.id,
+        };
+        Page {
+            next: rows.last().filter(|_| has_next).map(cursor),
+            prev: rows.first().filter(|_| has_prev).map(cursor),
+            items: rows.into_iter().map(|row| row.item).collect(),
+            total,
+        }
+    }
+}
+
+impl<T> Page<T> {
+    /// The link to the following page, if there is one.
+    ///
+    /// # Parameters
+    ///
+    /// * `uri`: The URI of the request, used as the base of the link.
+    pub fn next_link(&self, uri: &Uri) -> Option<String> {
+        let next = self.next.as_ref()?.encode();
+        Some(page_link(uri, Some(("after", &next))))
+    }
+
+    /// The link to the preceding page, if there is one.
+    ///
+    /// # Parameters
+    ///
+    /// * `uri`: The URI of the request, used as the base of the link.
+    pub fn prev_link(&self, uri: &Uri) -> Option<String> {
+        let prev = self.prev.as_ref()?.encode();
+        Some(page_link(uri, Some(("before", &prev))))
+    }
+
+    /// The headers describing this page: the total in `X-Total-Count`, the cursors in
+    /// `X-Next-Cursor`/`X-Prev-Cursor` and navigation links in an RFC 5988 `Link` header.
+    ///
+    /// # Parameters
+    ///
+    /// * `uri`: The URI of the request, used as the base of the links.
+    pub fn headers(&self, uri: &Uri) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-total-count", HeaderValue::from(self.total));
+
+        let mut links = vec![format!(r#"<{}>; rel="first""#, page_link(uri, None))];
+        if let Some(next) = &self.next {
+            let next = next.encode();
+            links.push(format!(
+                r#"<{}>; rel="next""#,
+                page_link(uri, Some(("after", &next)))
+            ));
+            if let Ok(value) = HeaderValue::from_str(&next) {
+                headers.insert("x-next-cursor", value);
+            }
+        }
+        if let Some(prev) = &self.prev {
+            let prev = prev.encode();
+            links.push(format!(
+                r#"<{}>; rel="prev""#,
+                page_link(uri, Some(("before", &prev)))
+            ));
+            if let Ok(value) = HeaderValue::from_str(&prev) {
+                headers.insert("x-prev-cursor", value);
+            }
+        }
+        if let Ok(value) = HeaderValue::from_str(&links.join(", ")) {
+            headers.insert(header::LINK, value);
+        }
+
+        headers
+    }
+}
+
+impl<T: Serialize> Page<T> {
+    /// Creates the response for this page: the items as a JSON array, with the pagination
+    /// headers described in [`Page::headers`].
+    ///
+    /// # Parameters
+    ///
+    /// * `uri`: The URI of the request, used as the base of the links.
+    pub fn response(self, uri: &Uri) -> Response {
+        (StatusCode::OK, self.headers(uri), Json(self.items)).into_response()
+    }
+}
+
+/// Rebuilds `uri` with its pagination parameters replaced by `cursor`.
+fn page_link(uri: &Uri, cursor: Option<(&str, &str)>) -> String {
+    let mut params: Vec<String> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !param.is_empty() && !matches!(name, "page" | "after" | "before")
+        })
+        .map(str::to_string)
+        .collect();
+    if let Some((name, value)) = cursor {
+        params.push(format!("{name}={value}"));
+    }
+
+    if params.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{}", uri.path(), params.join("&"))
+    }
+}
+
+/// Most tags a single filter may name
+const MAX_FILTER_TAGS: usize = 20;
+
+/// How the `tags` of a `QuestionFilter` are matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    /// The question has at least one of the tags
+    #[default]
+    Any,
+    /// The question has every one of the tags
+    All,
+}
+
+/// Orderings of the question list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionSort {
+    Newest,
+    #[default]
+    Oldest,
+    /// Most answers first
+    Answers,
+    /// Alphabetical by title, in byte order
+    Title,
+    /// Highest score first
+    Score,
+    /// Most recently edited first
+    Updated,
+    /// Most recent activity first: the question or one of its answers was written to
+    Activity,
+}
+
+/// Orderings of a question's answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerSort {
+    /// The accepted answer first, then the rest in the order they were added
+    #[default]
+    Accepted,
+    /// Highest score first
+    Score,
+    /// Most recently written first
+    Newest,
+    /// Most recently edited first
+    Updated,
+}
+
+/// Ordering of a question's answers
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnswerFilter {
+    /// Order of the answers
+    #[param(inline)]
+    #[serde(default)]
+    pub sort: AnswerSort,
+}
+
+/// Orderings of the tag list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TagSort {
+    /// Most used first
+    #[default]
+    Popular,
+    /// Alphabetical by name, in byte order
+    Name,
+    /// Most recently created first
+    Newest,
+}
+
+/// Ordering of the tag list
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TagFilter {
+    /// Order of the tags
+    #[param(inline)]
+    #[serde(default)]
+    pub sort: TagSort,
+}
+
+/// Filters and ordering for the question list
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuestionFilter {
+    /// Comma separated tags the questions must have
+    #[param(value_type = Option<String>, example = "rust,math")]
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub tags: Vec<String>,
+    /// Whether questions need `any` or `all` of `tags`
+    #[param(inline)]
+    #[serde(default)]
+    pub tag_match: TagMatch,
+    /// Comma separated tags the questions must not have
+    #[param(value_type = Option<String>, example = "history")]
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub exclude_tags: Vec<String>,
+    /// Only answered (`true`) or unanswered (`false`) questions
+    pub answered: Option<bool>,
+    /// Only closed (`true`) or open (`false`) questions
+    pub closed: Option<bool>,
+    /// Only questions from this time on, in RFC 3339. Bounds the time of the last edit when
+    /// sorting by `updated`, of the last activity when sorting by `activity` and the creation
+    /// time otherwise
+    #[param(value_type = Option<String>, example = "2024-05-01T00:00:00Z")]
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub since: Option<OffsetDateTime>,
+    /// Only questions before this time, bounding the same time as `since`
+    #[param(value_type = Option<String>, example = "2024-06-01T00:00:00Z")]
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    /// Order of the results
+    #[param(inline)]
+    #[serde(default)]
+    pub sort: QuestionSort,
+}
+
+/// Splits a comma separated list of tags into its normalized, non-empty and
+/// deduplicated values.
+fn comma_separated<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<String> = Option::deserialize(deserializer)?;
+    let mut values: Vec<String> = Vec::new();
+    for item in value.iter().flat_map(|value| value.split(',')) {
+        let item = normalize_tag(item);
+        if !item.is_empty() && !values.contains(&item) {
+            values.push(item);
+        }
+    }
+    Ok(values)
+}
+
+impl QuestionFilter {
+    /// Checks the combination of filters makes sense.
+    ///
+    /// # Returns
+    ///
+    /// The filter, or a `QuestionBankErr` error if too many tags are named or a tag is both
+    /// included and excluded.
+    pub fn validate(self) -> Result<Self, QuestionBankErr> {
+        if self.tags.len() + self.exclude_tags.len() > MAX_FILTER_TAGS {
+            return Err(QuestionBankErr::InvalidInput(format!(
+                "at most {MAX_FILTER_TAGS} tags can be filtered on"
+            )));
+        }
+        if let Some(tag) = self.tags.iter().find(|tag| self.exclude_tags.contains(tag)) {
+            return Err(QuestionBankErr::InvalidInput(format!(
+                "tag {tag} is both included and excluded"
+            )));
+        }
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since > until {
+                return Err(QuestionBankErr::InvalidInput(
+                    "since must not be after until".to_string(),
+                ));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Checks the filter like [`QuestionFilter::validate`], with its tags resolved through
+    /// their synonyms: questions are stored with the tag a synonym stands for.
+    ///
+    /// # Parameters
+    ///
+    /// * `tags`: The tag repository holding the synonyms.
+    ///
+    /// # Returns
+    ///
+    /// The filter with canonical tags, or the status and error to respond with: 400 if the
+    /// filter is invalid and 500 if the synonyms can't be read.
+    pub async fn canonical(
+        self,
+        tags: &dyn TagRepository,
+    ) -> Result<Self, (StatusCode, Box<dyn Error>)> {
+        let invalid = |e: QuestionBankErr| (StatusCode::BAD_REQUEST, Box::new(e) as Box<dyn Error>);
+        let mut filter = self.validate().map_err(invalid)?;
+        for names in [&mut filter.tags, &mut filter.exclude_tags] {
+            *names = tags
+                .canonical(Some(std::mem::take(names)))
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+                .unwrap_or_default();
+        }
+        filter.validate().map_err(invalid)
+    }
+
+    /// The time of `question` bounded by `since` and `until`.
+    pub fn timestamp(&self, question: &Question) -> Option<OffsetDateTime> {
+        match self.sort {
+            QuestionSort::Updated => question.updated_at,
+            QuestionSort::Activity => question.last_activity_at,
+            _ => question.created_at,
+        }
+    }
+
+    /// Whether `question`, with `answer_count` answers, passes the filter.
+    pub fn matches(&self, question: &Question, answer_count: usize) -> bool {
+        let tags = question.tags.as_deref().unwrap_or_default();
+        let has = |tag: &String| tags.contains(tag);
+        let included = match self.tag_match {
+            _ if self.tags.is_empty() => true,
+            TagMatch::Any => self.tags.iter().any(has),
+            TagMatch::All => self.tags.iter().all(has),
+        };
+        let excluded = self.exclude_tags.iter().any(has);
+        let answered = match self.answered {
+            Some(answered) => answered == (answer_count > 0),
+            None => true,
+        };
+
+        let closed = match self.closed {
+            Some(closed) => closed == question.closed.is_some(),
+            None => true,
+        };
+
+        let time = self.timestamp(question);
+        let since = match (self.since, time) {
+            (Some(since), Some(time)) => time >= since,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let until = match (self.until, time) {
+            (Some(until), Some(time)) => time < until,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        included && !excluded && answered && closed && since && until
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::{
+        lib::MemoryRepo, memory_repo::tests::add_question, question_repo::QuestionRepository,
+    };
+
+    fn order(key_desc: bool, id_desc: bool) -> Order {
+        Order {
+            name: "test",
+            key: Some("key"),
+            key_desc,
+            id: "id",
+            id_desc,
+        }
+    }
+
+    /// Items with tied keys, sorted in `order`.
+    fn sorted(order: &Order) -> Vec<Keyed<i32>> {
+        let mut items: Vec<Keyed<i32>> = [3, 1, 2, 3, 1, 5, 2, 3, 4, 1]
+            .iter()
+            .zip(1..)
+            .map(|(&key, id)| Keyed {
+                key: SortKey::Int(key),
+                id,
+                item: id,
+            })
+            .collect();
+        items.sort_by(|a, b| order.compare((&a.key, a.id), (&b.key, b.id)));
+        items
+    }
+
+    fn query(position: Position) -> PageQuery {
+        PageQuery { position, limit: 3 }
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let order = order(false, false);
+        for key in [
+            SortKey::Null,
+            SortKey::Bool(true),
+            SortKey::Int(-42),
+            SortKey::Float(0.5),
+            SortKey::Text("a \"quoted\" title".to_string()),
+        ] {
+            let cursor = Cursor {
+                order: order.name.to_string(),
+                key,
+                id: 7,
+            };
+            assert_eq!(Cursor::decode(&cursor.encode(), &order).ok(), Some(cursor));
+        }
+    }
+
+    #[test]
+    fn cursors_of_other_orderings_are_rejected() {
+        let cursor = Cursor {
+            order: "other".to_string(),
+            key: SortKey::Int(1),
+            id: 1,
+        };
+        assert!(Cursor::decode(&cursor.encode(), &order(false, false)).is_err());
+        assert!(Cursor::decode("not a cursor", &order(false, false)).is_err());
+    }
+
+    #[test]
+    fn pagination_is_validated() {
+        let order = order(false, false);
+        let params = |page, limit, after: Option<&str>, before: Option<&str>| Pagination {
+            page,
+            limit,
+            after: after.map(str::to_string),
+            before: before.map(str::to_string),
+        };
+        let cursor = Cursor {
+            order: order.name.to_string(),
+            key: SortKey::Int(1),
+            id: 1,
+        }
+        .encode();
+
+        assert_eq!(
+            params(Some(3), 10, None, None).validate(&order).ok(),
+            Some(PageQuery {
+                position: Position::Offset(20),
+                limit: 10,
+            })
+        );
+        assert!(params(None, 0, None, None).validate(&order).is_err());
+        assert!(params(None, MAX_LIMIT + 1, None, None)
+            .validate(&order)
+            .is_err());
+        assert!(params(Some(0), 10, None, None).validate(&order).is_err());
+        assert!(params(Some(1), 10, Some(&cursor), None)
+            .validate(&order)
+            .is_err());
+        assert!(params(None, 10, Some(&cursor), Some(&cursor))
+            .validate(&order)
+            .is_err());
+        assert!(params(None, 10, None, Some(&cursor))
+            .validate(&order)
+            .is_ok());
+    }
+
+    #[test]
+    fn cursors_walk_the_whole_list_both_ways() {
+        for (key_desc, id_desc) in [(false, false), (true, false), (false, true), (true, true)] {
+            let order = order(key_desc, id_desc);
+            let all: Vec<i32> = sorted(&order).into_iter().map(|item| item.id).collect();
+            let read = |page_query: PageQuery| {
+                let rows = page_query.slice(&order, sorted(&order));
+                page_query.page(&order, rows, all.len() as i64)
+            };
+
+            let page = read(query(Position::Offset(0)));
+            let mut forward = page.items;
+            let mut next = page.next;
+            while let Some(cursor) = next {
+                let page = read(query(Position::After(cursor)));
+                forward.extend(page.items);
+                next = page.next;
+            }
+            assert_eq!(forward, all);
+
+            let page = read(query(Position::Offset(9)));
+            let mut backward = page.items;
+            let mut prev = page.prev;
+            while let Some(cursor) = prev {
+                let page = read(query(Position::Before(cursor)));
+                backward.splice(0..0, page.items);
+                prev = page.prev;
+            }
+            assert_eq!(backward, all);
+        }
+    }
+
+    #[test]
+    fn offset_pages_know_their_neighbours() {
+        let order = order(false, false);
+        let page_at = |start| {
+            let page_query = query(Position::Offset(start));
+            page_query.page(&order, page_query.slice(&order, sorted(&order)), 10)
+        };
+
+        let first = page_at(0);
+        assert!(first.prev.is_none() && first.next.is_some());
+        let middle = page_at(3);
+        assert!(middle.prev.is_some() && middle.next.is_some());
+        let last = page_at(9);
+        assert_eq!(last.items.len(), 1);
+        assert!(last.prev.is_some() && last.next.is_none());
+    }
+
+    #[test]
+    fn text_keys_sort_by_bytes() {
+        let key = |text: &str| SortKey::Text(text.to_string());
+        assert_eq!(key("Zebra").compare(&key("apple")), Ordering::Less);
+        assert_eq!(key("apple").compare(&key("Äpfel")), Ordering::Less);
+    }
+
+    #[test]
+    fn page_links_replace_the_position() {
+        let uri: Uri = "/questions?sort=title&page=2&limit=5".parse().unwrap();
+        assert_eq!(page_link(&uri, None), "/questions?sort=title&limit=5");
+        assert_eq!(
+            page_link(&uri, Some(("after", "abc"))),
+            "/questions?sort=title&limit=5&after=abc"
+        );
+        let uri: Uri = "/questions?before=abc".parse().unwrap();
+        assert_eq!(page_link(&uri, None), "/questions");
+    }
+
+    #[tokio::test]
+    async fn filters_resolve_synonyms_to_their_tag() {
+        let repo = MemoryRepo::new();
+        let id = add_question(&repo, "What is a closure?", &["javascript"]).await;
+        repo.add_synonym("javascript", "js").await.unwrap();
+
+        let filter = QuestionFilter {
+            tags: vec!["js".to_string()],
+            ..QuestionFilter::default()
+        };
+        let filter = filter.canonical(&repo).await.unwrap();
+        assert_eq!(filter.tags, ["javascript"]);
+        let page = repo
+            .paginated_get(&query(Position::Offset(0)), &filter)
+            .await
+            .unwrap();
+        let ids: Vec<_> = page.items.iter().map(|question| question.id).collect();
+        assert_eq!(ids, [Some(id)]);
+
+        let conflicting = QuestionFilter {
+            tags: vec!["js".to_string()],
+            exclude_tags: vec!["javascript".to_string()],
+            ..QuestionFilter::default()
+        };
+        let (status, _) = conflicting.canonical(&repo).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
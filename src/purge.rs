@@ -0,0 +1,50 @@
+use crate::config::QuestionBank;
+use std::{error::Error, sync::Arc};
+use time::{Duration, OffsetDateTime};
+use tokio::sync::RwLock;
+
+/// Environment variable with the number of days deleted questions and answers stay in the trash.
+pub const RETENTION_VAR: &str = "TRASH_RETENTION_DAYS";
+
+/// How long deleted questions and answers stay in the trash when `TRASH_RETENTION_DAYS` is unset
+pub const DEFAULT_RETENTION: Duration = Duration::days(30);
+
+/// How often the trash is purged
+pub const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Reads the trash retention period from `TRASH_RETENTION_DAYS`, defaulting to
+/// [`DEFAULT_RETENTION`] when the variable is unset.
+///
+/// # Returns
+///
+/// The retention period, or an error if the value is not a whole number of days.
+pub fn retention_from_env() -> Result<Duration, Box<dyn Error>> {
+    match std::env::var(RETENTION_VAR) {
+        Ok(value) => match value.trim().parse::<u32>() {
+            Ok(days) => Ok(Duration::days(days.into())),
+            Err(_) => Err(format!("Invalid {RETENTION_VAR} value: {value}").into()),
+        },
+        Err(_) => Ok(DEFAULT_RETENTION),
+    }
+}
+
+/// Removes what has been in the trash for longer than `retention` for good, once at startup
+/// and then every [`PURGE_INTERVAL`]. Runs until the server stops.
+///
+/// # Parameters
+///
+/// * `bank`: The question bank to purge.
+/// * `retention`: How long deleted questions and answers are kept.
+pub async fn purge_periodically(bank: Arc<RwLock<QuestionBank>>, retention: Duration) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        let before = OffsetDateTime::now_utc() - retention;
+        let result = bank.write().await.trash.purge(before).await;
+        match result {
+            Ok(0) => {}
+            Ok(purged) => tracing::info!("Purged {} questions and answers from the trash", purged),
+            Err(e) => tracing::error!("Purging the trash failed: {}", e),
+        }
+    }
+}
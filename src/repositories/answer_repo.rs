@@ -1 +1,302 @@
-// use crate::*;
+use crate::{
+    entities::{answer::Answer, revision::Revision},
+    models::answer_model,
+    pagination::{AnswerSort, Keyed, Page, PageQuery, SortKey},
+    repositories::{
+        lib::*,
+        memory_repo::{self, MemoryTables},
+    },
+};
+use time::OffsetDateTime;
+
+/// Storage operations on answers, implemented by every repository backend.
+#[async_trait]
+pub trait AnswerRepository: Debug + Send + Sync {
+    /// Retrieves a page of the answers to a question, see [`answer_model::get_all`].
+    async fn get_all(
+        &self,
+        question_id: i32,
+        page: &PageQuery,
+        sort: AnswerSort,
+    ) -> Result<Page<Answer>, Box<dyn Error>>;
+
+    /// Checks whether a question has any answer, see [`answer_model::has_answers`].
+    async fn has_answers(&self, question_id: i32) -> Result<bool, Box<dyn Error>>;
+
+    /// Retrieves an answer by its ID, see [`answer_model::get`].
+    async fn get(&self, index: i32) -> Result<Answer, Box<dyn Error>>;
+
+    /// Adds a new answer, see [`answer_model::add`].
+    async fn add(&self, answer: Answer) -> Result<i32, Box<dyn Error>>;
+
+    /// Moves an answer to the trash, see [`answer_model::delete`].
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>>;
+
+    /// Updates an answer by its ID, see [`answer_model::update`].
+    async fn update(
+        &self,
+        index: i32,
+        answer: Answer,
+        editor_id: Option<i32>,
+    ) -> Result<Answer, Box<dyn Error>>;
+
+    /// Marks an answer as accepted, see [`answer_model::accept`].
+    async fn accept(&self, index: i32) -> Result<Answer, Box<dyn Error>>;
+
+    /// Removes the accepted mark from an answer, see [`answer_model::unaccept`].
+    async fn unaccept(&self, index: i32) -> Result<Answer, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl AnswerRepository for PostgresRepo {
+    async fn get_all(
+        &self,
+        question_id: i32,
+        page: &PageQuery,
+        sort: AnswerSort,
+    ) -> Result<Page<Answer>, Box<dyn Error>> {
+        answer_model::get_all(&self.pool, question_id, page, sort).await
+    }
+
+    async fn has_answers(&self, question_id: i32) -> Result<bool, Box<dyn Error>> {
+        answer_model::has_answers(&self.pool, question_id).await
+    }
+
+    async fn get(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        answer_model::get(&self.pool, index).await
+    }
+
+    async fn add(&self, answer: Answer) -> Result<i32, Box<dyn Error>> {
+        answer_model::add(&self.pool, answer).await
+    }
+
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>> {
+        answer_model::delete(&self.pool, index).await
+    }
+
+    async fn update(
+        &self,
+        index: i32,
+        answer: Answer,
+        editor_id: Option<i32>,
+    ) -> Result<Answer, Box<dyn Error>> {
+        answer_model::update(&self.pool, index, answer, editor_id).await
+    }
+
+    async fn accept(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        answer_model::accept(&self.pool, index).await
+    }
+
+    async fn unaccept(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        answer_model::unaccept(&self.pool, index).await
+    }
+}
+
+/// Moves the last activity time of a question to `time`.
+fn touch_question(tables: &mut MemoryTables, question_id: i32, time: OffsetDateTime) {
+    if let Some(question) = tables.questions.get_mut(&question_id) {
+        question.last_activity_at = Some(time);
+    }
+}
+
+#[async_trait]
+impl AnswerRepository for MemoryRepo {
+    async fn get_all(
+        &self,
+        question_id: i32,
+        page: &PageQuery,
+        sort: AnswerSort,
+    ) -> Result<Page<Answer>, Box<dyn Error>> {
+        let order = answer_model::order(sort);
+        let mut answers: Vec<Keyed<Answer>> = self
+            .read()
+            .answers
+            .values()
+            .filter(|answer| answer.question_id == Some(question_id))
+            .map(|answer| Keyed {
+                key: match sort {
+                    AnswerSort::Accepted => SortKey::Bool(answer.accepted),
+                    AnswerSort::Score => SortKey::Int(answer.score.into()),
+                    AnswerSort::Newest => {
+                        answer.created_at.map_or(SortKey::Null, SortKey::timestamp)
+                    }
+                    AnswerSort::Updated => {
+                        answer.updated_at.map_or(SortKey::Null, SortKey::timestamp)
+                    }
+                },
+                id: answer.id.unwrap_or_default(),
+                item: answer.clone(),
+            })
+            .collect();
+        answers.sort_by(|a, b| order.compare((&a.key, a.id), (&b.key, b.id)));
+
+        let total = answers.len() as i64;
+        Ok(page.page(&order, page.slice(&order, answers), total))
+    }
+
+    async fn has_answers(&self, question_id: i32) -> Result<bool, Box<dyn Error>> {
+        let tables = self.read();
+        Ok(tables
+            .answers
+            .values()
+            .chain(tables.trashed_answers.values())
+            .any(|answer| answer.question_id == Some(question_id)))
+    }
+
+    async fn get(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        self.read()
+            .answers
+            .get(&index)
+            .cloned()
+            .ok_or_else(|| QuestionBankErr::AnswerDoesNotExist(index.to_string()).into())
+    }
+
+    async fn add(&self, mut answer: Answer) -> Result<i32, Box<dyn Error>> {
+        let mut tables = self.write();
+        let question_id = answer
+            .question_id
+            .filter(|question_id| tables.questions.contains_key(question_id))
+            .ok_or_else(|| {
+                QuestionBankErr::DoesNotExist(
+                    answer
+                        .question_id
+                        .map(|id| id.to_string())
+                        .unwrap_or_default(),
+                )
+            })?;
+
+        let answer_id = tables.next_id("answers");
+        tracing::debug!("Added answer {} to question {}", answer_id, question_id);
+        answer.id = Some(answer_id);
+        answer.accepted = false;
+        answer.score = 0;
+        answer.my_vote = None;
+        answer.render();
+        let created_at = memory_repo::now();
+        answer.created_at = Some(created_at);
+        answer.updated_at = Some(created_at);
+        touch_question(&mut tables, question_id, created_at);
+        tables.record_revision(Revision::of_answer(answer_id, &answer));
+        tables.answers.insert(answer_id, answer);
+
+        Ok(answer_id)
+    }
+
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>> {
+        let mut tables = self.write();
+        match tables.answers.remove(&index) {
+            Some(mut answer) => {
+                answer.deleted_at = Some(memory_repo::now());
+                tables.trashed_answers.insert(index, answer);
+                Ok(())
+            }
+            None => Err(Box::new(QuestionBankErr::AnswerDoesNotExist(
+                index.to_string(),
+            ))),
+        }
+    }
+
+    async fn update(
+        &self,
+        index: i32,
+        answer: Answer,
+        editor_id: Option<i32>,
+    ) -> Result<Answer, Box<dyn Error>> {
+        let mut tables = self.write();
+        let answer_to_update = tables
+            .answers
+            .get_mut(&index)
+            .ok_or_else(|| QuestionBankErr::AnswerDoesNotExist(index.to_string()))?;
+        answer_to_update.answer = answer.answer;
+        answer_to_update.render();
+        if answer.key.is_some() {
+            answer_to_update.key = answer.key;
+        }
+        let updated_at = memory_repo::now();
+        answer_to_update.updated_at = Some(updated_at);
+        let answer_to_update = answer_to_update.clone();
+        if let Some(question_id) = answer_to_update.question_id {
+            touch_question(&mut tables, question_id, updated_at);
+        }
+
+        let mut revision = Revision::of_answer(index, &answer_to_update);
+        revision.author_id = editor_id;
+        tables.record_revision(revision);
+
+        Ok(answer_to_update)
+    }
+
+    async fn accept(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        let mut tables = self.write();
+        let question_id = tables
+            .answers
+            .get(&index)
+            .ok_or_else(|| QuestionBankErr::AnswerDoesNotExist(index.to_string()))?
+            .question_id;
+
+        for answer in tables.all_answers_mut() {
+            if answer.question_id == question_id {
+                answer.accepted = answer.id == Some(index);
+            }
+        }
+
+        Ok(tables.answers[&index].clone())
+    }
+
+    async fn unaccept(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        let mut tables = self.write();
+        let answer = tables
+            .answers
+            .get_mut(&index)
+            .ok_or_else(|| QuestionBankErr::AnswerDoesNotExist(index.to_string()))?;
+        answer.accepted = false;
+
+        Ok(answer.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pagination::Position, repositories::memory_repo::tests::add_question};
+
+    #[tokio::test]
+    async fn memory_answers_can_be_added_accepted_and_deleted() {
+        let repo = MemoryRepo::new();
+        let question_id = add_question(&repo, "What is a lifetime?", &[]).await;
+        assert!(repo
+            .add(Answer::new(None, "Nowhere", Some(question_id + 1)))
+            .await
+            .is_err());
+
+        let first = repo
+            .add(Answer::new(None, "A scope", Some(question_id)))
+            .await
+            .unwrap();
+        let second = repo
+            .add(Answer::new(None, "A region of code", Some(question_id)))
+            .await
+            .unwrap();
+        assert!(repo.has_answers(question_id).await.unwrap());
+        assert!(repo.accept(second).await.unwrap().accepted);
+
+        let first_page = PageQuery {
+            position: Position::Offset(0),
+            limit: 10,
+        };
+        let page = repo
+            .get_all(question_id, &first_page, AnswerSort::Accepted)
+            .await
+            .unwrap();
+        let ids: Vec<_> = page.items.iter().map(|answer| answer.id).collect();
+        assert_eq!(ids, [Some(second), Some(first)]);
+
+        let edit = Answer::new(None, "A span of code", Some(question_id));
+        assert_eq!(
+            repo.update(first, edit, None).await.unwrap().answer,
+            "A span of code"
+        );
+        repo.delete(first).await.unwrap();
+        assert!(repo.get(first).await.is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    entities::bulk::BulkQuestion,
+    models::bulk_model,
+    repositories::{answer_repo::AnswerRepository, lib::*, question_repo::QuestionRepository},
+};
+
+/// Storage operations on batches of questions, implemented by every repository backend.
+#[async_trait]
+pub trait BulkRepository: Debug + Send + Sync {
+    /// Adds a batch of questions with their answers, see [`bulk_model::insert`].
+    async fn insert(
+        &self,
+        author_id: i32,
+        batch: &[BulkQuestion],
+    ) -> Result<Vec<i32>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl BulkRepository for PostgresRepo {
+    async fn insert(
+        &self,
+        author_id: i32,
+        batch: &[BulkQuestion],
+    ) -> Result<Vec<i32>, Box<dyn Error>> {
+        bulk_model::insert(&self.pool, author_id, batch).await
+    }
+}
+
+#[async_trait]
+impl BulkRepository for MemoryRepo {
+    /// The questions are validated beforehand, so nothing can fail halfway through.
+    async fn insert(
+        &self,
+        author_id: i32,
+        batch: &[BulkQuestion],
+    ) -> Result<Vec<i32>, Box<dyn Error>> {
+        let mut question_ids = Vec::with_capacity(batch.len());
+        for bulk in batch {
+            let question_id = QuestionRepository::add(self, bulk.question(author_id)).await?;
+            question_ids.push(question_id);
+            for (answer, accepted) in bulk.answers(author_id, question_id) {
+                let answer_id = AnswerRepository::add(self, answer).await?;
+                if accepted {
+                    AnswerRepository::accept(self, answer_id).await?;
+                }
+            }
+        }
+
+        Ok(question_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::bulk::BulkAnswer;
+
+    #[tokio::test]
+    async fn memory_batches_keep_their_accepted_answers() {
+        let repo = MemoryRepo::new();
+        let bulk = |title: &str| BulkQuestion {
+            id: None,
+            title: title.to_string(),
+            content: "Which city?".to_string(),
+            tags: vec!["geo".to_string()],
+            kind: None,
+            answers: vec![
+                BulkAnswer {
+                    answer: "Lyon".to_string(),
+                    accepted: false,
+                    key: None,
+                },
+                BulkAnswer {
+                    answer: "Paris".to_string(),
+                    accepted: true,
+                    key: None,
+                },
+            ],
+        };
+
+        let ids = repo
+            .insert(7, &[bulk("Capital of France"), bulk("Largest French city")])
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+        let question = QuestionRepository::get(&repo, ids[1])
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(question.author_id, Some(7));
+        assert_eq!(
+            question.accepted_answer.map(|answer| answer.answer),
+            Some("Paris".to_string())
+        );
+    }
+}
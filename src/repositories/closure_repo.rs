@@ -0,0 +1,171 @@
+use crate::{
+    entities::{closure::*, question::Question},
+    models::closure_model,
+    repositories::{lib::*, memory_repo::now},
+};
+
+/// Closing and reopening questions, implemented by every repository backend.
+#[async_trait]
+pub trait ClosureRepository: Debug + Send + Sync {
+    /// Closes a question, see [`closure_model::close`].
+    async fn close(
+        &self,
+        index: i32,
+        request: &CloseRequest,
+        user_id: Option<i32>,
+    ) -> Result<Question, Box<dyn Error>>;
+
+    /// Reopens a question, see [`closure_model::reopen`].
+    async fn reopen(
+        &self,
+        index: i32,
+        note: Option<String>,
+        user_id: Option<i32>,
+    ) -> Result<Question, Box<dyn Error>>;
+
+    /// Retrieves a question's closes and reopens, see [`closure_model::get_history`].
+    async fn get_history(&self, index: i32) -> Result<Vec<ClosureEvent>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl ClosureRepository for PostgresRepo {
+    async fn close(
+        &self,
+        index: i32,
+        request: &CloseRequest,
+        user_id: Option<i32>,
+    ) -> Result<Question, Box<dyn Error>> {
+        closure_model::close(&self.pool, index, request, user_id).await
+    }
+
+    async fn reopen(
+        &self,
+        index: i32,
+        note: Option<String>,
+        user_id: Option<i32>,
+    ) -> Result<Question, Box<dyn Error>> {
+        closure_model::reopen(&self.pool, index, note, user_id).await
+    }
+
+    async fn get_history(&self, index: i32) -> Result<Vec<ClosureEvent>, Box<dyn Error>> {
+        closure_model::get_history(&self.pool, index).await
+    }
+}
+
+#[async_trait]
+impl ClosureRepository for MemoryRepo {
+    async fn close(
+        &self,
+        index: i32,
+        request: &CloseRequest,
+        user_id: Option<i32>,
+    ) -> Result<Question, Box<dyn Error>> {
+        let mut tables = self.write();
+        let question = tables
+            .questions
+            .get_mut(&index)
+            .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
+        if question.closed.is_some() {
+            return Err(Box::new(QuestionBankErr::Closed(format!(
+                "Question {index}"
+            ))));
+        }
+
+        let closed_at = now();
+        question.closed = Some(Closure {
+            reason: request.reason,
+            note: request.note.clone(),
+            duplicate_of: request.duplicate_of,
+            closed_by: user_id,
+            closed_at,
+        });
+        let question = question.clone();
+        tables.closures.push(ClosureEvent {
+            question_id: index,
+            action: ClosureAction::Close,
+            reason: Some(request.reason),
+            note: request.note.clone(),
+            duplicate_of: request.duplicate_of,
+            user_id,
+            created_at: closed_at,
+        });
+
+        Ok(tables.with_accepted_answer(&question))
+    }
+
+    async fn reopen(
+        &self,
+        index: i32,
+        note: Option<String>,
+        user_id: Option<i32>,
+    ) -> Result<Question, Box<dyn Error>> {
+        let mut tables = self.write();
+        let question = tables
+            .questions
+            .get_mut(&index)
+            .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
+        if question.closed.take().is_none() {
+            return Err(Box::new(QuestionBankErr::NotClosed(format!(
+                "Question {index}"
+            ))));
+        }
+
+        let question = question.clone();
+        tables.closures.push(ClosureEvent {
+            question_id: index,
+            action: ClosureAction::Reopen,
+            reason: None,
+            note,
+            duplicate_of: None,
+            user_id,
+            created_at: now(),
+        });
+
+        Ok(tables.with_accepted_answer(&question))
+    }
+
+    async fn get_history(&self, index: i32) -> Result<Vec<ClosureEvent>, Box<dyn Error>> {
+        Ok(self
+            .read()
+            .closures
+            .iter()
+            .filter(|event| event.question_id == index)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::memory_repo::tests::add_question;
+
+    #[tokio::test]
+    async fn memory_questions_can_be_closed_once_and_reopened() {
+        let repo = MemoryRepo::new();
+        let id = add_question(&repo, "Is this on topic?", &[]).await;
+        let request = CloseRequest {
+            reason: CloseReason::OffTopic,
+            note: Some("Not about programming".to_string()),
+            duplicate_of: None,
+        };
+
+        let closed = repo.close(id, &request, Some(1)).await.unwrap();
+        assert_eq!(
+            closed.closed.map(|closure| closure.closed_by),
+            Some(Some(1))
+        );
+        assert!(repo.close(id, &request, Some(1)).await.is_err());
+        assert!(repo
+            .reopen(id, None, Some(2))
+            .await
+            .unwrap()
+            .closed
+            .is_none());
+        assert!(repo.reopen(id, None, Some(2)).await.is_err());
+
+        let history = repo.get_history(id).await.unwrap();
+        let actions: Vec<_> = history.iter().map(|event| event.action).collect();
+        assert_eq!(actions, [ClosureAction::Close, ClosureAction::Reopen]);
+    }
+}
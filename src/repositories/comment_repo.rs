@@ -0,0 +1,329 @@
+use crate::{
+    entities::comment::*,
+    models::comment_model,
+    repositories::{lib::*, memory_repo::now},
+};
+use std::collections::HashMap;
+
+/// Storage operations on the comments of questions and answers, implemented by every
+/// repository backend.
+#[async_trait]
+pub trait CommentRepository: Debug + Send + Sync {
+    /// Retrieves the comments on an item, see [`comment_model::get_all`].
+    async fn get_all(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+    ) -> Result<Vec<Comment>, Box<dyn Error>>;
+
+    /// Retrieves the comments on several answers, see [`comment_model::get_for_answers`].
+    async fn get_for_answers(
+        &self,
+        answer_ids: &[i32],
+    ) -> Result<HashMap<i32, Vec<Comment>>, Box<dyn Error>>;
+
+    /// Retrieves a comment on an item, see [`comment_model::get`].
+    async fn get(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+    ) -> Result<Comment, Box<dyn Error>>;
+
+    /// Adds a comment to an item, see [`comment_model::add`].
+    async fn add(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        author_id: Option<i32>,
+        comment: &CommentRequest,
+    ) -> Result<Comment, Box<dyn Error>>;
+
+    /// Changes the text of a comment, see [`comment_model::update`].
+    async fn update(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+        body: &str,
+    ) -> Result<Comment, Box<dyn Error>>;
+
+    /// Deletes a comment and its replies, see [`comment_model::delete`].
+    async fn delete(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+#[async_trait]
+impl CommentRepository for PostgresRepo {
+    async fn get_all(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+    ) -> Result<Vec<Comment>, Box<dyn Error>> {
+        comment_model::get_all(&self.pool, target, target_id).await
+    }
+
+    async fn get_for_answers(
+        &self,
+        answer_ids: &[i32],
+    ) -> Result<HashMap<i32, Vec<Comment>>, Box<dyn Error>> {
+        comment_model::get_for_answers(&self.pool, answer_ids).await
+    }
+
+    async fn get(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+    ) -> Result<Comment, Box<dyn Error>> {
+        comment_model::get(&self.pool, target, target_id, index).await
+    }
+
+    async fn add(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        author_id: Option<i32>,
+        comment: &CommentRequest,
+    ) -> Result<Comment, Box<dyn Error>> {
+        comment_model::add(&self.pool, target, target_id, author_id, comment).await
+    }
+
+    async fn update(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+        body: &str,
+    ) -> Result<Comment, Box<dyn Error>> {
+        comment_model::update(&self.pool, target, target_id, index, body).await
+    }
+
+    async fn delete(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        comment_model::delete(&self.pool, target, target_id, index).await
+    }
+}
+
+/// Whether `comment` is on the question or answer `target_id`.
+fn is_on(comment: &Comment, target: CommentTarget, target_id: i32) -> bool {
+    match target {
+        CommentTarget::Question => comment.question_id == Some(target_id),
+        CommentTarget::Answer => comment.answer_id == Some(target_id),
+    }
+}
+
+#[async_trait]
+impl CommentRepository for MemoryRepo {
+    async fn get_all(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+    ) -> Result<Vec<Comment>, Box<dyn Error>> {
+        Ok(self
+            .read()
+            .comments
+            .values()
+            .filter(|comment| is_on(comment, target, target_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_for_answers(
+        &self,
+        answer_ids: &[i32],
+    ) -> Result<HashMap<i32, Vec<Comment>>, Box<dyn Error>> {
+        let mut by_answer: HashMap<i32, Vec<Comment>> = HashMap::new();
+        for comment in self.read().comments.values() {
+            if let Some(answer_id) = comment.answer_id.filter(|id| answer_ids.contains(id)) {
+                by_answer
+                    .entry(answer_id)
+                    .or_default()
+                    .push(comment.clone());
+            }
+        }
+
+        Ok(by_answer)
+    }
+
+    async fn get(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+    ) -> Result<Comment, Box<dyn Error>> {
+        self.read()
+            .comments
+            .get(&index)
+            .filter(|comment| is_on(comment, target, target_id))
+            .cloned()
+            .ok_or_else(|| QuestionBankErr::CommentDoesNotExist(index.to_string()).into())
+    }
+
+    async fn add(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        author_id: Option<i32>,
+        comment: &CommentRequest,
+    ) -> Result<Comment, Box<dyn Error>> {
+        let mut tables = self.write();
+        if let Some(parent_id) = comment.parent_id {
+            if !tables
+                .comments
+                .get(&parent_id)
+                .is_some_and(|parent| is_on(parent, target, target_id))
+            {
+                return Err(Box::new(QuestionBankErr::InvalidInput(format!(
+                    "comment {parent_id} isn't on this {}",
+                    target.name()
+                ))));
+            }
+        }
+
+        let id = tables.next_id("comments");
+        let created_at = now();
+        let (question_id, answer_id) = match target {
+            CommentTarget::Question => (Some(target_id), None),
+            CommentTarget::Answer => (None, Some(target_id)),
+        };
+        let comment = Comment {
+            id,
+            question_id,
+            answer_id,
+            parent_id: comment.parent_id,
+            author_id,
+            body: comment.body.trim().to_string(),
+            created_at,
+            updated_at: created_at,
+        };
+        tables.comments.insert(id, comment.clone());
+
+        Ok(comment)
+    }
+
+    async fn update(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+        body: &str,
+    ) -> Result<Comment, Box<dyn Error>> {
+        let mut tables = self.write();
+        let comment = tables
+            .comments
+            .get_mut(&index)
+            .filter(|comment| is_on(comment, target, target_id))
+            .ok_or_else(|| QuestionBankErr::CommentDoesNotExist(index.to_string()))?;
+        comment.body = body.trim().to_string();
+        comment.updated_at = now();
+
+        Ok(comment.clone())
+    }
+
+    async fn delete(
+        &self,
+        target: CommentTarget,
+        target_id: i32,
+        index: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut tables = self.write();
+        if !tables
+            .comments
+            .get(&index)
+            .is_some_and(|comment| is_on(comment, target, target_id))
+        {
+            return Err(Box::new(QuestionBankErr::CommentDoesNotExist(
+                index.to_string(),
+            )));
+        }
+
+        // Replies go with the comment they reply to, like the cascading foreign key
+        let mut removed = vec![index];
+        while let Some(id) = removed.pop() {
+            tables.comments.remove(&id);
+            removed.extend(
+                tables
+                    .comments
+                    .values()
+                    .filter(|comment| comment.parent_id == Some(id))
+                    .map(|comment| comment.id),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: &str, parent_id: Option<i32>) -> CommentRequest {
+        CommentRequest {
+            body: body.to_string(),
+            parent_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_replies_go_with_the_comment_they_reply_to() {
+        let repo = MemoryRepo::new();
+        let comment = repo
+            .add(
+                CommentTarget::Answer,
+                4,
+                Some(1),
+                &request(" Source? ", None),
+            )
+            .await
+            .unwrap();
+        assert_eq!(comment.body, "Source?");
+        let reply = repo
+            .add(
+                CommentTarget::Answer,
+                4,
+                Some(2),
+                &request("Here", Some(comment.id)),
+            )
+            .await
+            .unwrap();
+        assert!(repo
+            .add(
+                CommentTarget::Answer,
+                5,
+                None,
+                &request("Elsewhere", Some(comment.id))
+            )
+            .await
+            .is_err());
+
+        let edited = repo
+            .update(CommentTarget::Answer, 4, reply.id, "Here it is")
+            .await
+            .unwrap();
+        assert_eq!(edited.body, "Here it is");
+        assert_eq!(repo.get_for_answers(&[4, 5]).await.unwrap()[&4].len(), 2);
+        assert!(repo
+            .get(CommentTarget::Question, 4, comment.id)
+            .await
+            .is_err());
+
+        repo.delete(CommentTarget::Answer, 4, comment.id)
+            .await
+            .unwrap();
+        assert!(repo
+            .get_all(CommentTarget::Answer, 4)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,5 @@
+pub use crate::models::errors::*;
+pub use crate::repositories::{memory_repo::MemoryRepo, postgres_repo::PostgresRepo};
+pub use async_trait::async_trait;
+pub use std::error::Error;
+pub use std::fmt::Debug;
@@ -0,0 +1,259 @@
+use crate::entities::{
+    answer::Answer,
+    closure::ClosureEvent,
+    comment::Comment,
+    question::Question,
+    quiz::QuizSession,
+    review::Review,
+    revision::Revision,
+    tag::{normalize_tags, Tag},
+    token::ApiToken,
+    user::User,
+    vote::VoteTarget,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use time::OffsetDateTime;
+
+/// A row of the in-memory tag table
+#[derive(Debug, Clone)]
+pub struct MemoryTag {
+    pub id: i32,
+    pub description: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+/// The current time at the microsecond precision Postgres stores, so both backends sort
+/// and filter alike.
+pub fn now() -> OffsetDateTime {
+    let now = OffsetDateTime::now_utc();
+    now.replace_nanosecond(now.nanosecond() / 1000 * 1000)
+        .unwrap_or(now)
+}
+
+/// The "tables" of the in-memory backend.
+#[derive(Debug, Default)]
+pub struct MemoryTables {
+    pub questions: BTreeMap<i32, Question>,
+    pub answers: BTreeMap<i32, Answer>,
+    /// Questions in the trash, hidden from every other table
+    pub trashed_questions: BTreeMap<i32, Question>,
+    /// Answers in the trash, including those of trashed questions
+    pub trashed_answers: BTreeMap<i32, Answer>,
+    /// Users along with their password hash
+    pub users: BTreeMap<i32, (User, String)>,
+    /// Session token hash to user ID and expiry
+    pub sessions: HashMap<String, (i32, OffsetDateTime)>,
+    /// API tokens along with their hash
+    pub tokens: BTreeMap<i32, (ApiToken, String)>,
+    /// Votes by what was voted on, its ID and the voting user
+    pub votes: HashMap<(VoteTarget, i32, i32), i16>,
+    /// Revisions of every question and answer, oldest first
+    pub revisions: Vec<Revision>,
+    /// Every close and reopen of a question, oldest first
+    pub closures: Vec<ClosureEvent>,
+    /// Comments on every question and answer, trashed or not
+    pub comments: BTreeMap<i32, Comment>,
+    /// Quiz sessions, their score left at zero
+    pub quiz_sessions: BTreeMap<i32, QuizSession>,
+    /// Questions served by quiz sessions by session and question ID, along with whether the
+    /// answer was correct once graded
+    pub quiz_items: BTreeMap<(i32, i32), Option<bool>>,
+    /// Spaced repetition reviews by user and question ID
+    pub reviews: BTreeMap<(i32, i32), Review>,
+    /// Tags by name, kept while unused until the trash is purged
+    pub tags: BTreeMap<String, MemoryTag>,
+    /// Synonyms along with the name of their tag
+    pub synonyms: BTreeMap<String, String>,
+    sequences: HashMap<&'static str, i32>,
+}
+
+impl MemoryTables {
+    /// Returns the next identity value for `table`, starting at 1 like a
+    /// Postgres `GENERATED ALWAYS AS IDENTITY` column.
+    pub fn next_id(&mut self, table: &'static str) -> i32 {
+        let id = self.sequences.entry(table).or_insert(0);
+        *id += 1;
+        *id
+    }
+
+    /// Stores `revision` as the next revision of its question or answer,
+    /// numbering it like the Postgres backend does.
+    pub fn record_revision(&mut self, mut revision: Revision) {
+        revision.revision = self
+            .revisions
+            .iter()
+            .filter(|r| r.question_id == revision.question_id && r.answer_id == revision.answer_id)
+            .map(|r| r.revision)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.revisions.push(revision);
+    }
+
+    /// Every question, trashed or not.
+    pub fn all_questions_mut(&mut self) -> impl Iterator<Item = &mut Question> {
+        self.questions
+            .values_mut()
+            .chain(self.trashed_questions.values_mut())
+    }
+
+    /// Every answer, trashed or not.
+    pub fn all_answers_mut(&mut self) -> impl Iterator<Item = &mut Answer> {
+        self.answers
+            .values_mut()
+            .chain(self.trashed_answers.values_mut())
+    }
+
+    /// Normalizes the tags of a question and replaces synonyms by their tag.
+    pub fn canonical_tags(&self, tags: Option<Vec<String>>) -> Option<Vec<String>> {
+        let tags = normalize_tags(tags)?
+            .into_iter()
+            .map(|tag| self.synonyms.get(&tag).cloned().unwrap_or(tag))
+            .collect();
+        normalize_tags(Some(tags))
+    }
+
+    /// The tags of every question, trashed or not.
+    fn used_tags(&self) -> Vec<String> {
+        self.questions
+            .values()
+            .chain(self.trashed_questions.values())
+            .flat_map(|question| question.tags.iter().flatten())
+            .cloned()
+            .collect()
+    }
+
+    /// Adds the tags of the questions that aren't in the tag table yet.
+    pub fn add_tags(&mut self) {
+        for name in self.used_tags() {
+            if !self.tags.contains_key(&name) {
+                let id = self.next_id("tags");
+                let created_at = now();
+                self.tags.insert(
+                    name,
+                    MemoryTag {
+                        id,
+                        description: None,
+                        created_at,
+                        updated_at: created_at,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Removes the tags no question has anymore, along with their description and synonyms.
+    pub fn delete_orphan_tags(&mut self) {
+        let used = self.used_tags();
+        self.tags.retain(|name, _| used.contains(name));
+        let tags = &self.tags;
+        self.synonyms.retain(|_, tag| tags.contains_key(tag));
+    }
+
+    /// Removes the tag named `name` along with its synonyms, returning whether it existed.
+    pub fn remove_tag(&mut self, name: &str) -> bool {
+        self.synonyms.retain(|_, tag| tag != name);
+        self.tags.remove(name).is_some()
+    }
+
+    /// Returns the tag named `name` with its usage count and synonyms.
+    pub fn tag(&self, name: &str) -> Option<Tag> {
+        let tag = self.tags.get(name)?;
+        Some(Tag {
+            name: name.to_string(),
+            description: tag.description.clone(),
+            questions: self
+                .questions
+                .values()
+                .filter(|question| question.tags.iter().flatten().any(|t| t == name))
+                .count() as i64,
+            synonyms: self
+                .synonyms
+                .iter()
+                .filter(|(_, tag)| *tag == name)
+                .map(|(synonym, _)| synonym.clone())
+                .collect(),
+            created_at: tag.created_at,
+            updated_at: tag.updated_at,
+        })
+    }
+
+    /// Returns a copy of `question` with its accepted answer filled in, like
+    /// the join done by the Postgres queries. The accepted answer of a trashed
+    /// question is the one trashed along with it.
+    pub fn with_accepted_answer(&self, question: &Question) -> Question {
+        let mut question = question.clone();
+        let answers = match question.deleted_at {
+            Some(_) => &self.trashed_answers,
+            None => &self.answers,
+        };
+        question.accepted_answer = answers
+            .values()
+            .find(|answer| {
+                answer.accepted
+                    && answer.question_id == question.id
+                    && answer.deleted_at == question.deleted_at
+            })
+            .cloned();
+        question
+    }
+}
+
+/// Repository backend that keeps everything in process memory.
+///
+/// Nothing is persisted, which makes it handy for running the server and its
+/// handlers without a database.
+#[derive(Debug, Default)]
+pub struct MemoryRepo {
+    tables: RwLock<MemoryTables>,
+}
+
+impl MemoryRepo {
+    /// Creates a new, empty `MemoryRepo` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the tables for reading.
+    pub fn read(&self) -> RwLockReadGuard<'_, MemoryTables> {
+        self.tables.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Locks the tables for writing.
+    pub fn write(&self) -> RwLockWriteGuard<'_, MemoryTables> {
+        self.tables.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::repositories::{
+        answer_repo::AnswerRepository, question_repo::QuestionRepository, user_repo::UserRepository,
+    };
+
+    /// Adds a question for the tests of the other tables to refer to.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the question.
+    pub async fn add_question(repo: &MemoryRepo, title: &str, tags: &[&str]) -> i32 {
+        QuestionRepository::add(repo, Question::new(None, title, "Explain", tags))
+            .await
+            .unwrap()
+    }
+
+    /// Adds an answer to the question `question_id`, see [`add_question`].
+    pub async fn add_answer(repo: &MemoryRepo, question_id: i32, answer: &str) -> i32 {
+        AnswerRepository::add(repo, Answer::new(None, answer, Some(question_id)))
+            .await
+            .unwrap()
+    }
+
+    /// Adds a member for the tests of the other tables to refer to.
+    pub async fn add_user(repo: &MemoryRepo, username: &str) -> User {
+        repo.create(username, "hash").await.unwrap()
+    }
+}
@@ -1,2 +1,17 @@
-mod answer_repo;
-mod question_repo;
+pub mod answer_repo;
+pub mod bulk_repo;
+pub mod closure_repo;
+pub mod comment_repo;
+pub mod lib;
+pub mod memory_repo;
+pub mod postgres_repo;
+pub mod question_repo;
+pub mod quiz_repo;
+pub mod review_repo;
+pub mod revision_repo;
+pub mod search_repo;
+pub mod tag_repo;
+pub mod token_repo;
+pub mod trash_repo;
+pub mod user_repo;
+pub mod vote_repo;
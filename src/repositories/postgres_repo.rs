@@ -0,0 +1,25 @@
+use sqlx::{Pool, Postgres};
+
+/// Repository backend that stores everything in Postgres through sqlx.
+///
+/// The queries themselves live in the `models` modules, this type only
+/// carries the connection pool they run against.
+#[derive(Debug, Clone)]
+pub struct PostgresRepo {
+    pub pool: Pool<Postgres>,
+}
+
+impl PostgresRepo {
+    /// Creates a new `PostgresRepo` instance.
+    ///
+    /// # Parameters
+    ///
+    /// * `pool`: A connected (and migrated) Postgres connection pool.
+    ///
+    /// # Returns
+    ///
+    /// A new `PostgresRepo` instance.
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
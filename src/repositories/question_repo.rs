@@ -1 +1,240 @@
-// use crate::*;
+use crate::{
+    entities::{question::Question, revision::Revision},
+    models::question_model,
+    pagination::{Keyed, Page, PageQuery, QuestionFilter, QuestionSort, SortKey},
+    repositories::{
+        lib::*,
+        memory_repo::{self, MemoryTables},
+    },
+};
+
+/// Storage operations on questions, implemented by every repository backend.
+#[async_trait]
+pub trait QuestionRepository: Debug + Send + Sync {
+    /// Retrieves a page of questions, see [`question_model::paginated_get`].
+    async fn paginated_get(
+        &self,
+        page: &PageQuery,
+        filter: &QuestionFilter,
+    ) -> Result<Page<Question>, Box<dyn Error>>;
+
+    /// Retrieves a question by its ID, see [`question_model::get`].
+    async fn get(&self, index: i32) -> Result<Vec<Question>, Box<dyn Error>>;
+
+    /// Adds a new question, see [`question_model::add`].
+    async fn add(&self, question: Question) -> Result<i32, Box<dyn Error>>;
+
+    /// Moves a question and its answers to the trash, see [`question_model::delete`].
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>>;
+
+    /// Updates a question by its ID, see [`question_model::update`].
+    async fn update(
+        &self,
+        index: i32,
+        question: Question,
+        editor_id: Option<i32>,
+    ) -> Result<Vec<Question>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl QuestionRepository for PostgresRepo {
+    async fn paginated_get(
+        &self,
+        page: &PageQuery,
+        filter: &QuestionFilter,
+    ) -> Result<Page<Question>, Box<dyn Error>> {
+        question_model::paginated_get(&self.pool, page, filter).await
+    }
+
+    async fn get(&self, index: i32) -> Result<Vec<Question>, Box<dyn Error>> {
+        question_model::get(&self.pool, index).await
+    }
+
+    async fn add(&self, question: Question) -> Result<i32, Box<dyn Error>> {
+        question_model::add(&self.pool, question).await
+    }
+
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>> {
+        question_model::delete(&self.pool, index).await
+    }
+
+    async fn update(
+        &self,
+        index: i32,
+        question: Question,
+        editor_id: Option<i32>,
+    ) -> Result<Vec<Question>, Box<dyn Error>> {
+        question_model::update(&self.pool, index, question, editor_id).await
+    }
+}
+
+#[async_trait]
+impl QuestionRepository for MemoryRepo {
+    async fn paginated_get(
+        &self,
+        page: &PageQuery,
+        filter: &QuestionFilter,
+    ) -> Result<Page<Question>, Box<dyn Error>> {
+        let tables = self.read();
+        let order = question_model::order(filter.sort);
+        let answer_count = |question: &Question| {
+            tables
+                .answers
+                .values()
+                .filter(|answer| answer.question_id == question.id)
+                .count()
+        };
+
+        let mut questions: Vec<Keyed<Question>> = tables
+            .questions
+            .values()
+            .filter(|question| filter.matches(question, answer_count(question)))
+            .map(|question| Keyed {
+                key: match filter.sort {
+                    QuestionSort::Newest | QuestionSort::Oldest => SortKey::Null,
+                    QuestionSort::Answers => SortKey::Int(answer_count(question) as i64),
+                    QuestionSort::Title => SortKey::Text(question.title.clone()),
+                    QuestionSort::Score => SortKey::Int(question.score.into()),
+                    QuestionSort::Updated | QuestionSort::Activity => filter
+                        .timestamp(question)
+                        .map_or(SortKey::Null, SortKey::timestamp),
+                },
+                id: question.id.unwrap_or_default(),
+                item: tables.with_accepted_answer(question),
+            })
+            .collect();
+        questions.sort_by(|a, b| order.compare((&a.key, a.id), (&b.key, b.id)));
+
+        let total = questions.len() as i64;
+        Ok(page.page(&order, page.slice(&order, questions), total))
+    }
+
+    async fn get(&self, index: i32) -> Result<Vec<Question>, Box<dyn Error>> {
+        let tables = self.read();
+        match tables.questions.get(&index) {
+            Some(question) => Ok(vec![tables.with_accepted_answer(question)]),
+            None => Err(Box::new(QuestionBankErr::DoesNotExist(index.to_string()))),
+        }
+    }
+
+    async fn add(&self, mut question: Question) -> Result<i32, Box<dyn Error>> {
+        let mut tables = self.write();
+        let question_id = tables.next_id("questions");
+        question.id = Some(question_id);
+        question.tags = tables.canonical_tags(question.tags);
+        question.score = 0;
+        question.my_vote = None;
+        question.accepted_answer = None;
+        question.deleted_at = None;
+        question.closed = None;
+        question.kind = Some(question.kind.unwrap_or_default());
+        question.render();
+        let created_at = memory_repo::now();
+        question.created_at = Some(created_at);
+        question.updated_at = Some(created_at);
+        question.last_activity_at = Some(created_at);
+        tables.record_revision(Revision::of_question(question_id, &question));
+        tables.questions.insert(question_id, question);
+        tables.add_tags();
+
+        Ok(question_id)
+    }
+
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>> {
+        let mut tables = self.write();
+        let mut question = tables
+            .questions
+            .remove(&index)
+            .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
+        let deleted_at = Some(memory_repo::now());
+        question.deleted_at = deleted_at;
+        tables.trashed_questions.insert(index, question);
+
+        let MemoryTables {
+            answers,
+            trashed_answers,
+            ..
+        } = &mut *tables;
+        let (trashed, kept) = std::mem::take(answers)
+            .into_iter()
+            .partition(|(_, answer)| answer.question_id == Some(index));
+        *answers = kept;
+        for (answer_id, mut answer) in trashed {
+            answer.deleted_at = deleted_at;
+            trashed_answers.insert(answer_id, answer);
+        }
+
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        index: i32,
+        question: Question,
+        editor_id: Option<i32>,
+    ) -> Result<Vec<Question>, Box<dyn Error>> {
+        let mut tables = self.write();
+        let canonical_tags = tables.canonical_tags(question.tags);
+        let question_to_update = tables
+            .questions
+            .get_mut(&index)
+            .ok_or_else(|| QuestionBankErr::DoesNotExist(index.to_string()))?;
+        question_to_update.title = question.title;
+        question_to_update.content = question.content;
+        question_to_update.render();
+        question_to_update.tags = canonical_tags;
+        if question.kind.is_some() {
+            question_to_update.kind = question.kind;
+        }
+        question_to_update.updated_at = Some(memory_repo::now());
+        question_to_update.last_activity_at = question_to_update.updated_at;
+        let question_to_update = question_to_update.clone();
+        let mut revision = Revision::of_question(index, &question_to_update);
+        revision.author_id = editor_id;
+        tables.record_revision(revision);
+        tables.add_tags();
+
+        Ok(vec![tables.with_accepted_answer(&question_to_update)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagination::Position;
+
+    #[tokio::test]
+    async fn memory_questions_can_be_added_listed_updated_and_deleted() {
+        let repo = MemoryRepo::new();
+        let id = repo
+            .add(Question::new(
+                None,
+                "What is a borrow?",
+                "Explain",
+                &["Rust"],
+            ))
+            .await
+            .unwrap();
+        let question = repo.get(id).await.unwrap().remove(0);
+        assert_eq!(question.title, "What is a borrow?");
+        assert_eq!(question.tags, Some(vec!["rust".to_string()]));
+
+        let edit = Question::new(None, "What is a mutable borrow?", "Explain", &["rust"]);
+        let updated = repo.update(id, edit, Some(1)).await.unwrap();
+        assert_eq!(updated[0].title, "What is a mutable borrow?");
+
+        let first_page = PageQuery {
+            position: Position::Offset(0),
+            limit: 10,
+        };
+        let page = repo
+            .paginated_get(&first_page, &QuestionFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].id, Some(id));
+
+        repo.delete(id).await.unwrap();
+        assert!(repo.get(id).await.is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{
+    entities::{answer::Answer, quiz::*},
+    models::quiz_model,
+    repositories::{
+        lib::*,
+        memory_repo::{now, MemoryTables},
+    },
+};
+use std::{cmp::Reverse, collections::HashMap};
+
+/// Storage operations on quiz sessions, implemented by every repository backend.
+#[async_trait]
+pub trait QuizRepository: Debug + Send + Sync {
+    /// Starts a quiz session, see [`quiz_model::start`].
+    async fn start(&self, user_id: i32, tags: &[String]) -> Result<QuizSession, Box<dyn Error>>;
+
+    /// Retrieves a quiz session, see [`quiz_model::get`].
+    async fn get(&self, index: i32) -> Result<QuizSession, Box<dyn Error>>;
+
+    /// Lists the questions a session can still serve, see [`quiz_model::unseen`].
+    async fn unseen(&self, index: i32) -> Result<Vec<i32>, Box<dyn Error>>;
+
+    /// Records that a session served a question, see [`quiz_model::serve`].
+    async fn serve(&self, index: i32, question_id: i32) -> Result<(), Box<dyn Error>>;
+
+    /// Retrieves the answer submissions are graded against, see
+    /// [`quiz_model::correct_answer`].
+    async fn correct_answer(&self, question_id: i32) -> Result<Answer, Box<dyn Error>>;
+
+    /// Retrieves the answers submissions to several questions are graded against, see
+    /// [`quiz_model::correct_answers`].
+    async fn correct_answers(
+        &self,
+        question_ids: &[i32],
+    ) -> Result<HashMap<i32, Answer>, Box<dyn Error>>;
+
+    /// Records a graded answer, see [`quiz_model::grade`].
+    async fn grade(
+        &self,
+        index: i32,
+        question_id: i32,
+        answer: &str,
+        correct: bool,
+        similarity: f32,
+    ) -> Result<QuizSession, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl QuizRepository for PostgresRepo {
+    async fn start(&self, user_id: i32, tags: &[String]) -> Result<QuizSession, Box<dyn Error>> {
+        quiz_model::start(&self.pool, user_id, tags).await
+    }
+
+    async fn get(&self, index: i32) -> Result<QuizSession, Box<dyn Error>> {
+        quiz_model::get(&self.pool, index).await
+    }
+
+    async fn unseen(&self, index: i32) -> Result<Vec<i32>, Box<dyn Error>> {
+        quiz_model::unseen(&self.pool, index).await
+    }
+
+    async fn serve(&self, index: i32, question_id: i32) -> Result<(), Box<dyn Error>> {
+        quiz_model::serve(&self.pool, index, question_id).await
+    }
+
+    async fn correct_answer(&self, question_id: i32) -> Result<Answer, Box<dyn Error>> {
+        quiz_model::correct_answer(&self.pool, question_id).await
+    }
+
+    async fn correct_answers(
+        &self,
+        question_ids: &[i32],
+    ) -> Result<HashMap<i32, Answer>, Box<dyn Error>> {
+        quiz_model::correct_answers(&self.pool, question_ids).await
+    }
+
+    async fn grade(
+        &self,
+        index: i32,
+        question_id: i32,
+        answer: &str,
+        correct: bool,
+        similarity: f32,
+    ) -> Result<QuizSession, Box<dyn Error>> {
+        quiz_model::grade(&self.pool, index, question_id, answer, correct, similarity).await
+    }
+}
+
+/// The session `index` with its score counted from the served questions.
+fn scored(tables: &MemoryTables, index: i32) -> Result<QuizSession, Box<dyn Error>> {
+    let mut session = tables
+        .quiz_sessions
+        .get(&index)
+        .cloned()
+        .ok_or_else(|| QuestionBankErr::QuizSessionDoesNotExist(index.to_string()))?;
+    for correct in tables
+        .quiz_items
+        .range((index, i32::MIN)..=(index, i32::MAX))
+        .map(|(_, correct)| correct)
+    {
+        session.served += 1;
+        if let Some(correct) = correct {
+            session.answered += 1;
+            session.correct += i64::from(*correct);
+        }
+    }
+
+    Ok(session)
+}
+
+#[async_trait]
+impl QuizRepository for MemoryRepo {
+    async fn start(&self, user_id: i32, tags: &[String]) -> Result<QuizSession, Box<dyn Error>> {
+        let mut tables = self.write();
+        let id = tables.next_id("quiz_sessions");
+        let session = QuizSession {
+            id,
+            user_id,
+            tags: tags.to_vec(),
+            served: 0,
+            answered: 0,
+            correct: 0,
+            created_at: now(),
+        };
+        tables.quiz_sessions.insert(id, session.clone());
+
+        Ok(session)
+    }
+
+    async fn get(&self, index: i32) -> Result<QuizSession, Box<dyn Error>> {
+        scored(&self.read(), index)
+    }
+
+    async fn unseen(&self, index: i32) -> Result<Vec<i32>, Box<dyn Error>> {
+        let tables = self.read();
+        let session = tables
+            .quiz_sessions
+            .get(&index)
+            .ok_or_else(|| QuestionBankErr::QuizSessionDoesNotExist(index.to_string()))?;
+        Ok(tables
+            .questions
+            .values()
+            .filter_map(|question| question.id)
+            .filter(|id| {
+                tables
+                    .answers
+                    .values()
+                    .any(|answer| answer.question_id == Some(*id))
+            })
+            .filter(|id| {
+                session.tags.is_empty()
+                    || tables.questions[id]
+                        .tags
+                        .iter()
+                        .flatten()
+                        .any(|tag| session.tags.contains(tag))
+            })
+            .filter(|id| !tables.quiz_items.contains_key(&(index, *id)))
+            .collect())
+    }
+
+    async fn serve(&self, index: i32, question_id: i32) -> Result<(), Box<dyn Error>> {
+        self.write().quiz_items.insert((index, question_id), None);
+        Ok(())
+    }
+
+    async fn correct_answer(&self, question_id: i32) -> Result<Answer, Box<dyn Error>> {
+        self.read()
+            .answers
+            .values()
+            .filter(|answer| answer.question_id == Some(question_id))
+            .min_by_key(|answer| (Reverse(answer.accepted), Reverse(answer.score), answer.id))
+            .cloned()
+            .ok_or_else(|| {
+                QuestionBankErr::AnswerDoesNotExist(format!("for question {question_id}")).into()
+            })
+    }
+
+    async fn correct_answers(
+        &self,
+        question_ids: &[i32],
+    ) -> Result<HashMap<i32, Answer>, Box<dyn Error>> {
+        let tables = self.read();
+        let mut answers: HashMap<i32, Answer> = HashMap::new();
+        for answer in tables.answers.values() {
+            let Some(question_id) = answer.question_id.filter(|id| question_ids.contains(id))
+            else {
+                continue;
+            };
+            let rank =
+                |answer: &Answer| (Reverse(answer.accepted), Reverse(answer.score), answer.id);
+            match answers.get(&question_id) {
+                Some(best) if rank(best) <= rank(answer) => {}
+                _ => {
+                    answers.insert(question_id, answer.clone());
+                }
+            }
+        }
+
+        Ok(answers)
+    }
+
+    async fn grade(
+        &self,
+        index: i32,
+        question_id: i32,
+        _answer: &str,
+        correct: bool,
+        _similarity: f32,
+    ) -> Result<QuizSession, Box<dyn Error>> {
+        let mut tables = self.write();
+        let item = tables
+            .quiz_items
+            .get_mut(&(index, question_id))
+            .ok_or_else(|| {
+                QuestionBankErr::InvalidInput(format!(
+                    "question {question_id} wasn't served in this session"
+                ))
+            })?;
+        if item.is_some() {
+            return Err(Box::new(QuestionBankErr::AlreadyExists(format!(
+                "An answer to question {question_id}"
+            ))));
+        }
+        *item = Some(correct);
+
+        scored(&tables, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::memory_repo::tests::{add_answer, add_question};
+
+    #[tokio::test]
+    async fn memory_quizzes_serve_answered_questions_once() {
+        let repo = MemoryRepo::new();
+        let tags = ["geo".to_string()];
+        let capital = add_question(&repo, "Capital of France?", &["geo"]).await;
+        add_answer(&repo, capital, "Paris").await;
+        add_question(&repo, "Capital of Spain?", &["geo"]).await;
+        let other = add_question(&repo, "Two plus two?", &["math"]).await;
+        add_answer(&repo, other, "Four").await;
+
+        let session = repo.start(1, &tags).await.unwrap();
+        assert_eq!(repo.unseen(session.id).await.unwrap(), [capital]);
+        assert!(repo
+            .grade(session.id, capital, "Paris", true, 1.0)
+            .await
+            .is_err());
+        repo.serve(session.id, capital).await.unwrap();
+        assert!(repo.unseen(session.id).await.unwrap().is_empty());
+
+        let graded = repo
+            .grade(session.id, capital, "Paris", true, 1.0)
+            .await
+            .unwrap();
+        assert_eq!((graded.served, graded.answered, graded.correct), (1, 1, 1));
+        assert!(repo
+            .grade(session.id, capital, "Paris", true, 1.0)
+            .await
+            .is_err());
+        assert_eq!(repo.get(session.id).await.unwrap().correct, 1);
+
+        assert_eq!(repo.correct_answer(capital).await.unwrap().answer, "Paris");
+        let answers = repo.correct_answers(&[capital, other]).await.unwrap();
+        assert_eq!(answers[&other].answer, "Four");
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{
+    entities::review::{DueCard, Review},
+    models::review_model,
+    repositories::{lib::*, memory_repo::now},
+    scheduling::{self, Card},
+};
+
+/// Storage operations on spaced repetition reviews, implemented by every repository backend.
+#[async_trait]
+pub trait ReviewRepository: Debug + Send + Sync {
+    /// Retrieves where a user stands with a question, see [`review_model::get`].
+    async fn get(&self, user_id: i32, question_id: i32) -> Result<Option<Review>, Box<dyn Error>>;
+
+    /// Stores the schedule of a question reviewed now, see [`review_model::save`].
+    async fn save(
+        &self,
+        user_id: i32,
+        question_id: i32,
+        card: &Card,
+    ) -> Result<Review, Box<dyn Error>>;
+
+    /// Lists the due reviews of a user, see [`review_model::due`].
+    async fn due(
+        &self,
+        user_id: i32,
+        tag: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<DueCard>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl ReviewRepository for PostgresRepo {
+    async fn get(&self, user_id: i32, question_id: i32) -> Result<Option<Review>, Box<dyn Error>> {
+        review_model::get(&self.pool, user_id, question_id).await
+    }
+
+    async fn save(
+        &self,
+        user_id: i32,
+        question_id: i32,
+        card: &Card,
+    ) -> Result<Review, Box<dyn Error>> {
+        review_model::save(&self.pool, user_id, question_id, card).await
+    }
+
+    async fn due(
+        &self,
+        user_id: i32,
+        tag: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<DueCard>, Box<dyn Error>> {
+        review_model::due(&self.pool, user_id, tag, limit).await
+    }
+}
+
+#[async_trait]
+impl ReviewRepository for MemoryRepo {
+    async fn get(&self, user_id: i32, question_id: i32) -> Result<Option<Review>, Box<dyn Error>> {
+        Ok(self.read().reviews.get(&(user_id, question_id)).cloned())
+    }
+
+    async fn save(
+        &self,
+        user_id: i32,
+        question_id: i32,
+        card: &Card,
+    ) -> Result<Review, Box<dyn Error>> {
+        let reviewed_at = now();
+        let review = Review {
+            user_id,
+            question_id,
+            ease: card.ease,
+            interval: card.interval,
+            repetitions: card.repetitions,
+            reviewed_at,
+            due_at: scheduling::due_at(reviewed_at, card),
+        };
+        self.write()
+            .reviews
+            .insert((user_id, question_id), review.clone());
+
+        Ok(review)
+    }
+
+    async fn due(
+        &self,
+        user_id: i32,
+        tag: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<DueCard>, Box<dyn Error>> {
+        let tables = self.read();
+        let now = now();
+        let mut due: Vec<DueCard> = tables
+            .reviews
+            .range((user_id, i32::MIN)..=(user_id, i32::MAX))
+            .map(|(_, review)| review)
+            .filter(|review| review.due_at <= now)
+            .filter_map(|review| {
+                let question = tables.questions.get(&review.question_id)?;
+                let tagged = match tag {
+                    Some(tag) => question.tags.iter().flatten().any(|t| t == tag),
+                    None => true,
+                };
+                tagged.then(|| DueCard {
+                    question: tables.with_accepted_answer(question),
+                    review: review.clone(),
+                })
+            })
+            .collect();
+        due.sort_by_key(|card| (card.review.due_at, card.review.question_id));
+        due.truncate(usize::try_from(limit).unwrap_or(0));
+
+        Ok(due)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::memory_repo::tests::add_question;
+
+    #[tokio::test]
+    async fn memory_new_cards_are_due_right_away() {
+        let repo = MemoryRepo::new();
+        let rust = add_question(&repo, "What is a trait?", &["rust"]).await;
+        let math = add_question(&repo, "What is a group?", &["math"]).await;
+        assert!(repo.get(1, rust).await.unwrap().is_none());
+
+        repo.save(1, rust, &Card::default()).await.unwrap();
+        repo.save(1, math, &Card::default()).await.unwrap();
+        let learned = Card {
+            interval: 6,
+            repetitions: 2,
+            ..Card::default()
+        };
+        repo.save(2, rust, &learned).await.unwrap();
+        assert_eq!(repo.get(2, rust).await.unwrap().unwrap().interval, 6);
+
+        let due = repo.due(1, Some("rust"), 10).await.unwrap();
+        let ids: Vec<_> = due.iter().map(|card| card.question.id).collect();
+        assert_eq!(ids, [Some(rust)]);
+        assert_eq!(repo.due(1, None, 10).await.unwrap().len(), 2);
+        assert!(repo.due(2, None, 10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{entities::revision::Revision, models::revision_model, repositories::lib::*};
+
+/// Storage operations on the revisions of questions and answers, implemented
+/// by every repository backend.
+#[async_trait]
+pub trait RevisionRepository: Debug + Send + Sync {
+    /// Retrieves the revisions of a question, see [`revision_model::get_question_revisions`].
+    async fn get_question_revisions(
+        &self,
+        question_id: i32,
+    ) -> Result<Vec<Revision>, Box<dyn Error>>;
+
+    /// Retrieves the revisions of an answer, see [`revision_model::get_answer_revisions`].
+    async fn get_answer_revisions(&self, answer_id: i32) -> Result<Vec<Revision>, Box<dyn Error>>;
+
+    /// Retrieves a revision of a question, see [`revision_model::get_question_revision`].
+    async fn get_question_revision(
+        &self,
+        question_id: i32,
+        revision: i32,
+    ) -> Result<Revision, Box<dyn Error>>;
+
+    /// Retrieves a revision of an answer, see [`revision_model::get_answer_revision`].
+    async fn get_answer_revision(
+        &self,
+        answer_id: i32,
+        revision: i32,
+    ) -> Result<Revision, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl RevisionRepository for PostgresRepo {
+    async fn get_question_revisions(
+        &self,
+        question_id: i32,
+    ) -> Result<Vec<Revision>, Box<dyn Error>> {
+        revision_model::get_question_revisions(&self.pool, question_id).await
+    }
+
+    async fn get_answer_revisions(&self, answer_id: i32) -> Result<Vec<Revision>, Box<dyn Error>> {
+        revision_model::get_answer_revisions(&self.pool, answer_id).await
+    }
+
+    async fn get_question_revision(
+        &self,
+        question_id: i32,
+        revision: i32,
+    ) -> Result<Revision, Box<dyn Error>> {
+        revision_model::get_question_revision(&self.pool, question_id, revision).await
+    }
+
+    async fn get_answer_revision(
+        &self,
+        answer_id: i32,
+        revision: i32,
+    ) -> Result<Revision, Box<dyn Error>> {
+        revision_model::get_answer_revision(&self.pool, answer_id, revision).await
+    }
+}
+
+impl MemoryRepo {
+    /// Returns the revisions matching `question_id` and `answer_id`, oldest first.
+    fn revisions_of(&self, question_id: Option<i32>, answer_id: Option<i32>) -> Vec<Revision> {
+        self.read()
+            .revisions
+            .iter()
+            .filter(|r| r.question_id == question_id && r.answer_id == answer_id)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Picks `revision` out of `revisions`.
+fn find_revision(revisions: Vec<Revision>, revision: i32) -> Result<Revision, Box<dyn Error>> {
+    revisions
+        .into_iter()
+        .find(|r| r.revision == revision)
+        .ok_or_else(|| QuestionBankErr::RevisionDoesNotExist(revision.to_string()).into())
+}
+
+#[async_trait]
+impl RevisionRepository for MemoryRepo {
+    async fn get_question_revisions(
+        &self,
+        question_id: i32,
+    ) -> Result<Vec<Revision>, Box<dyn Error>> {
+        Ok(self.revisions_of(Some(question_id), None))
+    }
+
+    async fn get_answer_revisions(&self, answer_id: i32) -> Result<Vec<Revision>, Box<dyn Error>> {
+        Ok(self.revisions_of(None, Some(answer_id)))
+    }
+
+    async fn get_question_revision(
+        &self,
+        question_id: i32,
+        revision: i32,
+    ) -> Result<Revision, Box<dyn Error>> {
+        find_revision(self.revisions_of(Some(question_id), None), revision)
+    }
+
+    async fn get_answer_revision(
+        &self,
+        answer_id: i32,
+        revision: i32,
+    ) -> Result<Revision, Box<dyn Error>> {
+        find_revision(self.revisions_of(None, Some(answer_id)), revision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::question::Question, repositories::question_repo::QuestionRepository};
+
+    #[tokio::test]
+    async fn memory_edits_are_numbered_revisions() {
+        let repo = MemoryRepo::new();
+        let id = repo
+            .add(Question::new(None, "First title", "Explain", &[]))
+            .await
+            .unwrap();
+        let edit = Question::new(None, "Second title", "Explain", &[]);
+        repo.update(id, edit, Some(3)).await.unwrap();
+
+        let revisions = repo.get_question_revisions(id).await.unwrap();
+        let numbers: Vec<_> = revisions.iter().map(|r| r.revision).collect();
+        assert_eq!(numbers, [1, 2]);
+        let second = repo.get_question_revision(id, 2).await.unwrap();
+        assert_eq!(second.title.as_deref(), Some("Second title"));
+        assert_eq!(second.author_id, Some(3));
+        assert!(repo.get_question_revision(id, 3).await.is_err());
+        assert!(repo.get_answer_revisions(id).await.unwrap().is_empty());
+    }
+}
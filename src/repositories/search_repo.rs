@@ -0,0 +1,324 @@
+use crate::{
+    entities::{question::Question, search::*},
+    models::search_model,
+    pagination::{Keyed, Page, PageQuery, SortKey},
+    repositories::lib::*,
+};
+use std::collections::HashSet;
+
+/// Full-text search, implemented by every repository backend.
+#[async_trait]
+pub trait SearchRepository: Debug + Send + Sync {
+    /// Searches questions and answers, see [`search_model::search`].
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        page: &PageQuery,
+    ) -> Result<Page<SearchResult>, Box<dyn Error>>;
+
+    /// Finds questions with a title like `title`, see [`search_model::similar`].
+    async fn similar(
+        &self,
+        title: &str,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl SearchRepository for PostgresRepo {
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        page: &PageQuery,
+    ) -> Result<Page<SearchResult>, Box<dyn Error>> {
+        search_model::search(&self.pool, query, page).await
+    }
+
+    async fn similar(
+        &self,
+        title: &str,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, Box<dyn Error>> {
+        search_model::similar(&self.pool, title, limit).await
+    }
+}
+
+/// Most words kept in a snippet, like `ts_headline`'s default `MaxWords`
+const SNIPPET_WORDS: usize = 35;
+
+/// Splits `text` into lowercased search terms.
+fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty() && !term.eq_ignore_ascii_case("or"))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The trigrams of `text` the way `pg_trgm` takes them: each lowercased word is padded with
+/// two spaces in front and one behind.
+///
+/// Letters outside ASCII are part of words, as in a database with a UTF-8 `LC_CTYPE`; under
+/// the `C` locale Postgres splits words on them instead.
+fn trigrams(text: &str) -> HashSet<Vec<char>> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let padded: Vec<char> = format!("  {} ", word.to_lowercase()).chars().collect();
+            padded.windows(3).map(<[char]>::to_vec).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// How alike two texts are, like `pg_trgm`'s `similarity`: the shared trigrams over all the
+/// trigrams of both.
+fn similarity(a: &HashSet<Vec<char>>, b: &HashSet<Vec<char>>) -> f32 {
+    let shared = a.intersection(b).count();
+    let all = a.len() + b.len() - shared;
+    if all == 0 {
+        return 0.0;
+    }
+    shared as f32 / all as f32
+}
+
+/// Whether a word of the searched text matches one of the terms.
+///
+/// Prefix matching stands in for the stemming Postgres does, so "borrowing"
+/// is found when searching for "borrow".
+fn is_match(word: &str, terms: &[String]) -> bool {
+    let word = word.to_lowercase();
+    let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+    !word.is_empty() && terms.iter().any(|term| word.starts_with(term.as_str()))
+}
+
+/// Counts the words of `text` matching one of the terms, or `None` when a
+/// term does not appear at all.
+fn matches(text: &str, terms: &[String]) -> Option<usize> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let all_found = terms.iter().all(|term| {
+        words
+            .iter()
+            .any(|word| is_match(word, std::slice::from_ref(term)))
+    });
+
+    all_found.then(|| words.iter().filter(|word| is_match(word, terms)).count())
+}
+
+/// `text` with `&`, `<` and `>` escaped for HTML, like the Postgres snippets.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Returns a fragment of `text` around the first match with matching words
+/// wrapped in `<b>` tags, or all of `text` when `whole` is set. The text is
+/// escaped, so the `<b>` tags are its only markup.
+fn highlight(text: &str, terms: &[String], whole: bool) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let (start, end) = if whole {
+        (0, words.len())
+    } else {
+        let first = words
+            .iter()
+            .position(|word| is_match(word, terms))
+            .unwrap_or(0);
+        let start = first.saturating_sub(5);
+        (start, words.len().min(start + SNIPPET_WORDS))
+    };
+
+    words[start..end]
+        .iter()
+        .map(|word| {
+            if is_match(word, terms) {
+                format!("<b>{}</b>", escape_html(word))
+            } else {
+                escape_html(word)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+impl MemoryRepo {
+    /// Matches a question and its answers against the search terms.
+    fn search_question(&self, question: &Question, terms: &[String]) -> Option<SearchResult> {
+        // Title words are counted twice, like the heavier weight the title
+        // gets in the Postgres search vector.
+        let question_text = format!("{} {}", question.title, question.content);
+        let title_hits = question
+            .title
+            .split_whitespace()
+            .filter(|word| is_match(word, terms))
+            .count();
+        let question_hits = matches(&question_text, terms).map(|hits| hits + title_hits);
+
+        let best_answer = self
+            .read()
+            .answers
+            .values()
+            .filter(|answer| answer.question_id == question.id)
+            .filter_map(|answer| matches(&answer.answer, terms).map(|hits| (hits, answer.clone())))
+            .max_by_key(|(hits, _)| *hits);
+
+        if question_hits.is_none() && best_answer.is_none() {
+            return None;
+        }
+
+        let answer_hits = best_answer.as_ref().map_or(0, |(hits, _)| *hits);
+        let rank = (question_hits.unwrap_or(0) as f32 + answer_hits as f32 / 2.0) / 10.0;
+
+        Some(SearchResult {
+            question_id: question.id.unwrap_or_default(),
+            title: question.title.clone(),
+            tags: question.tags.clone(),
+            rank,
+            title_snippet: highlight(&question.title, terms, true),
+            content_snippet: highlight(&question.content, terms, false),
+            answer_snippet: best_answer.map(|(_, answer)| highlight(&answer.answer, terms, false)),
+        })
+    }
+}
+
+/// A simple fallback matcher: every term has to appear in the question or in
+/// one of its answers, ranked by the number of matching words.
+#[async_trait]
+impl SearchRepository for MemoryRepo {
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        page: &PageQuery,
+    ) -> Result<Page<SearchResult>, Box<dyn Error>> {
+        let order = search_model::ORDER;
+        let terms = terms(&query.q);
+
+        let questions: Vec<Question> = self
+            .read()
+            .questions
+            .values()
+            .filter(|question| match &query.tag {
+                Some(tag) => question.tags.iter().flatten().any(|t| t == tag),
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        let mut results: Vec<Keyed<SearchResult>> = questions
+            .iter()
+            .filter(|_| !terms.is_empty())
+            .filter_map(|question| self.search_question(question, &terms))
+            .map(|result| Keyed {
+                key: SortKey::Float(result.rank as f64),
+                id: result.question_id,
+                item: result,
+            })
+            .collect();
+        results.sort_by(|a, b| order.compare((&a.key, a.id), (&b.key, b.id)));
+
+        let total = results.len() as i64;
+        Ok(page.page(&order, page.slice(&order, results), total))
+    }
+
+    async fn similar(
+        &self,
+        title: &str,
+        limit: i64,
+    ) -> Result<Vec<SimilarQuestion>, Box<dyn Error>> {
+        let wanted = trigrams(title);
+        let mut similar: Vec<SimilarQuestion> = self
+            .read()
+            .questions
+            .values()
+            .map(|question| SimilarQuestion {
+                id: question.id.unwrap_or_default(),
+                title: question.title.clone(),
+                similarity: similarity(&wanted, &trigrams(&question.title)),
+            })
+            .filter(|question| question.similarity >= search_model::SIMILARITY_THRESHOLD)
+            .collect();
+        similar.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        similar.truncate(usize::try_from(limit).unwrap_or_default());
+
+        Ok(similar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pagination::Position, repositories::memory_repo::tests::add_question};
+
+    fn trigram_set(trigrams: &[&str]) -> HashSet<Vec<char>> {
+        trigrams
+            .iter()
+            .map(|trigram| trigram.chars().collect())
+            .collect()
+    }
+
+    #[test]
+    fn trigrams_pad_each_lowercased_word() {
+        assert_eq!(trigrams("Cat"), trigram_set(&["  c", " ca", "cat", "at "]));
+        assert_eq!(trigrams("a-B"), trigram_set(&["  a", " a ", "  b", " b "]));
+        assert_eq!(trigrams("cat cat, CAT!"), trigrams("cat"));
+        assert_eq!(trigrams("café").len(), 5);
+        assert!(trigrams(" ?! ").is_empty());
+    }
+
+    #[test]
+    fn similarity_is_shared_over_all_trigrams() {
+        // The values are the ones pg_trgm gives for the same texts
+        let word = similarity(&trigrams("word"), &trigrams("two words"));
+        assert!((word - 0.36363637).abs() < 1e-6);
+        let rust = similarity(&trigrams("rust"), &trigrams("trust"));
+        assert!((rust - 0.375).abs() < 1e-6);
+        let title = trigrams("How does the borrow checker work?");
+        let unrelated = similarity(&title, &trigrams("Never jump"));
+        assert!((unrelated - 0.023809524).abs() < 1e-6);
+
+        assert_eq!(similarity(&title, &title), 1.0);
+        assert_eq!(similarity(&trigrams(""), &trigrams("")), 0.0);
+        assert_eq!(
+            similarity(&trigrams("rust"), &trigrams("trust")),
+            similarity(&trigrams("trust"), &trigrams("rust"))
+        );
+    }
+
+    #[test]
+    fn highlight_escapes_the_text_around_its_tags() {
+        let terms = terms("script");
+        assert_eq!(
+            highlight("a <script>alert(1)</script> & more", &terms, true),
+            "a <b>&lt;script&gt;alert(1)&lt;/script&gt;</b> &amp; more"
+        );
+    }
+
+    #[tokio::test]
+    async fn memory_search_ranks_matching_questions() {
+        let repo = MemoryRepo::new();
+        let borrow = add_question(&repo, "How does the borrow checker work?", &["rust"]).await;
+        add_question(&repo, "What is a monad?", &["haskell"]).await;
+
+        let query = SearchQuery {
+            q: "borrow".to_string(),
+            tag: Some("rust".to_string()),
+        };
+        let first_page = PageQuery {
+            position: Position::Offset(0),
+            limit: 10,
+        };
+        let page = repo.search(&query, &first_page).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].question_id, borrow);
+        assert!(page.items[0].title_snippet.contains("<b>borrow</b>"));
+
+        let similar = repo
+            .similar("How does the borrow checker work", 5)
+            .await
+            .unwrap();
+        let ids: Vec<_> = similar.iter().map(|question| question.id).collect();
+        assert_eq!(ids, [borrow]);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{
+    entities::tag::Tag,
+    models::tag_model,
+    pagination::{Keyed, Page, PageQuery, SortKey, TagSort},
+    repositories::{
+        lib::*,
+        memory_repo::{self, MemoryTables},
+    },
+};
+
+/// Storage operations on tags, implemented by every repository backend.
+#[async_trait]
+pub trait TagRepository: Debug + Send + Sync {
+    /// Retrieves a page of tags, see [`tag_model::get_all`].
+    async fn get_all(&self, page: &PageQuery, sort: TagSort) -> Result<Page<Tag>, Box<dyn Error>>;
+
+    /// Retrieves a tag by its name, see [`tag_model::get`].
+    async fn get(&self, name: &str) -> Result<Tag, Box<dyn Error>>;
+
+    /// Sets or removes the description of a tag, see [`tag_model::set_description`].
+    async fn set_description(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Tag, Box<dyn Error>>;
+
+    /// Makes a name a synonym of a tag, see [`tag_model::add_synonym`].
+    async fn add_synonym(&self, name: &str, synonym: &str) -> Result<Tag, Box<dyn Error>>;
+
+    /// Removes a synonym of a tag, see [`tag_model::delete_synonym`].
+    async fn delete_synonym(&self, name: &str, synonym: &str) -> Result<Tag, Box<dyn Error>>;
+
+    /// Merges one tag into another, see [`tag_model::merge`].
+    async fn merge(&self, source: &str, into: &str) -> Result<Tag, Box<dyn Error>>;
+
+    /// Removes a tag from every question, see [`tag_model::delete`].
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Normalizes tags and replaces synonyms by their tag, see [`tag_model::canonical_tags`].
+    async fn canonical(
+        &self,
+        names: Option<Vec<String>>,
+    ) -> Result<Option<Vec<String>>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl TagRepository for PostgresRepo {
+    async fn get_all(&self, page: &PageQuery, sort: TagSort) -> Result<Page<Tag>, Box<dyn Error>> {
+        tag_model::get_all(&self.pool, page, sort).await
+    }
+
+    async fn get(&self, name: &str) -> Result<Tag, Box<dyn Error>> {
+        tag_model::get(&self.pool, name).await
+    }
+
+    async fn set_description(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Tag, Box<dyn Error>> {
+        tag_model::set_description(&self.pool, name, description).await
+    }
+
+    async fn add_synonym(&self, name: &str, synonym: &str) -> Result<Tag, Box<dyn Error>> {
+        tag_model::add_synonym(&self.pool, name, synonym).await
+    }
+
+    async fn delete_synonym(&self, name: &str, synonym: &str) -> Result<Tag, Box<dyn Error>> {
+        tag_model::delete_synonym(&self.pool, name, synonym).await
+    }
+
+    async fn merge(&self, source: &str, into: &str) -> Result<Tag, Box<dyn Error>> {
+        tag_model::merge(&self.pool, source, into).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        tag_model::delete(&self.pool, name).await
+    }
+
+    async fn canonical(
+        &self,
+        names: Option<Vec<String>>,
+    ) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+        tag_model::canonical_tags(&self.pool, names).await
+    }
+}
+
+/// Returns the tag named `name`, or a `QuestionBankErr` error if there is none.
+fn find_tag(tables: &MemoryTables, name: &str) -> Result<Tag, Box<dyn Error>> {
+    tables
+        .tag(name)
+        .ok_or_else(|| QuestionBankErr::TagDoesNotExist(name.to_string()).into())
+}
+
+/// Moves the last update time of the tag named `name` to now.
+fn touch_tag(tables: &mut MemoryTables, name: &str) {
+    if let Some(tag) = tables.tags.get_mut(name) {
+        tag.updated_at = memory_repo::now();
+    }
+}
+
+/// Gives every question with `source` the tag `into` instead, and keeps
+/// `source` as a synonym of `into`.
+fn merge_tags(tables: &mut MemoryTables, source: &str, into: &str) {
+    for question in tables.all_questions_mut() {
+        let Some(tags) = question.tags.as_mut() else {
+            continue;
+        };
+        if let Some(position) = tags.iter().position(|tag| tag == source) {
+            if tags.iter().any(|tag| tag == into) {
+                tags.remove(position);
+            } else {
+                tags[position] = into.to_string();
+            }
+        }
+    }
+    for tag in tables.synonyms.values_mut() {
+        if tag == source {
+            *tag = into.to_string();
+        }
+    }
+    tables.remove_tag(source);
+    tables.synonyms.insert(source.to_string(), into.to_string());
+    touch_tag(tables, into);
+}
+
+#[async_trait]
+impl TagRepository for MemoryRepo {
+    async fn get_all(&self, page: &PageQuery, sort: TagSort) -> Result<Page<Tag>, Box<dyn Error>> {
+        let tables = self.read();
+        let order = tag_model::order(sort);
+        let mut tags: Vec<Keyed<Tag>> = tables
+            .tags
+            .iter()
+            .filter_map(|(name, row)| {
+                let tag = tables.tag(name)?;
+                Some(Keyed {
+                    key: match sort {
+                        TagSort::Popular => SortKey::Int(tag.questions),
+                        TagSort::Name => SortKey::Text(tag.name.clone()),
+                        TagSort::Newest => SortKey::timestamp(tag.created_at),
+                    },
+                    id: row.id,
+                    item: tag,
+                })
+            })
+            .collect();
+        tags.sort_by(|a, b| order.compare((&a.key, a.id), (&b.key, b.id)));
+
+        let total = tags.len() as i64;
+        Ok(page.page(&order, page.slice(&order, tags), total))
+    }
+
+    async fn get(&self, name: &str) -> Result<Tag, Box<dyn Error>> {
+        find_tag(&self.read(), name)
+    }
+
+    async fn set_description(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Tag, Box<dyn Error>> {
+        let mut tables = self.write();
+        let tag = tables
+            .tags
+            .get_mut(name)
+            .ok_or_else(|| QuestionBankErr::TagDoesNotExist(name.to_string()))?;
+        tag.description = description.map(String::from);
+        tag.updated_at = memory_repo::now();
+
+        find_tag(&tables, name)
+    }
+
+    async fn add_synonym(&self, name: &str, synonym: &str) -> Result<Tag, Box<dyn Error>> {
+        if name == synonym {
+            return Err(Box::new(QuestionBankErr::InvalidInput(
+                "a tag can't be its own synonym".to_string(),
+            )));
+        }
+
+        let mut tables = self.write();
+        find_tag(&tables, name)?;
+        if tables.synonyms.contains_key(synonym) {
+            return Err(Box::new(QuestionBankErr::AlreadyExists(format!(
+                "Synonym {synonym}"
+            ))));
+        }
+
+        if tables.tags.contains_key(synonym) {
+            merge_tags(&mut tables, synonym, name);
+        } else {
+            tables
+                .synonyms
+                .insert(synonym.to_string(), name.to_string());
+            touch_tag(&mut tables, name);
+        }
+
+        find_tag(&tables, name)
+    }
+
+    async fn delete_synonym(&self, name: &str, synonym: &str) -> Result<Tag, Box<dyn Error>> {
+        let mut tables = self.write();
+        find_tag(&tables, name)?;
+        if tables.synonyms.get(synonym).map(String::as_str) != Some(name) {
+            return Err(Box::new(QuestionBankErr::SynonymDoesNotExist(
+                synonym.to_string(),
+            )));
+        }
+        tables.synonyms.remove(synonym);
+        touch_tag(&mut tables, name);
+
+        find_tag(&tables, name)
+    }
+
+    async fn merge(&self, source: &str, into: &str) -> Result<Tag, Box<dyn Error>> {
+        if source == into {
+            return Err(Box::new(QuestionBankErr::InvalidInput(
+                "can't merge a tag into itself".to_string(),
+            )));
+        }
+
+        let mut tables = self.write();
+        find_tag(&tables, source)?;
+        find_tag(&tables, into)?;
+        merge_tags(&mut tables, source, into);
+
+        find_tag(&tables, into)
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let mut tables = self.write();
+        if !tables.remove_tag(name) {
+            return Err(Box::new(QuestionBankErr::TagDoesNotExist(name.to_string())));
+        }
+        for question in tables.all_questions_mut() {
+            if let Some(tags) = question.tags.as_mut() {
+                tags.retain(|tag| tag != name);
+            }
+            question.tags = question.tags.take().filter(|tags| !tags.is_empty());
+        }
+
+        Ok(())
+    }
+
+    async fn canonical(
+        &self,
+        names: Option<Vec<String>>,
+    ) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+        Ok(self.read().canonical_tags(names))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pagination::Position, repositories::memory_repo::tests::add_question};
+
+    #[tokio::test]
+    async fn memory_synonyms_resolve_to_their_tag() {
+        let repo = MemoryRepo::new();
+        add_question(&repo, "What is ownership?", &["rust"]).await;
+        let id = add_question(&repo, "What is a crate?", &["rustlang", "cargo"]).await;
+
+        let tag = repo.add_synonym("rust", "rustlang").await.unwrap();
+        assert_eq!(tag.questions, 2);
+        assert!(repo.get("rustlang").await.is_err());
+        assert_eq!(
+            repo.canonical(Some(vec!["rustlang".to_string()]))
+                .await
+                .unwrap(),
+            Some(vec!["rust".to_string()])
+        );
+        let tag = repo
+            .set_description("rust", Some("The language"))
+            .await
+            .unwrap();
+        assert_eq!(tag.description.as_deref(), Some("The language"));
+
+        let first_page = PageQuery {
+            position: Position::Offset(0),
+            limit: 10,
+        };
+        let page = repo.get_all(&first_page, TagSort::Popular).await.unwrap();
+        let names: Vec<_> = page.items.iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, ["rust", "cargo"]);
+
+        repo.delete("cargo").await.unwrap();
+        let tags = repo.read().questions[&id].tags.clone();
+        assert_eq!(tags, Some(vec!["rust".to_string()]));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    entities::{token::*, user::User},
+    models::token_model,
+    repositories::lib::*,
+};
+use time::OffsetDateTime;
+
+/// Storage operations on personal API tokens, implemented by every repository backend.
+#[async_trait]
+pub trait TokenRepository: Debug + Send + Sync {
+    /// Stores a new API token, see [`token_model::create`].
+    async fn create(
+        &self,
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        scopes: &[Scope],
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<ApiToken, Box<dyn Error>>;
+
+    /// Retrieves a user's API tokens, see [`token_model::get_all`].
+    async fn get_all(&self, user_id: i32) -> Result<Vec<ApiToken>, Box<dyn Error>>;
+
+    /// Revokes one of a user's API tokens, see [`token_model::delete`].
+    async fn delete(&self, user_id: i32, index: i32) -> Result<(), Box<dyn Error>>;
+
+    /// Retrieves the user an API token acts as, see [`token_model::token_user`].
+    async fn token_user(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(User, Vec<Scope>)>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl TokenRepository for PostgresRepo {
+    async fn create(
+        &self,
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        scopes: &[Scope],
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<ApiToken, Box<dyn Error>> {
+        token_model::create(&self.pool, user_id, name, token_hash, scopes, expires_at).await
+    }
+
+    async fn get_all(&self, user_id: i32) -> Result<Vec<ApiToken>, Box<dyn Error>> {
+        token_model::get_all(&self.pool, user_id).await
+    }
+
+    async fn delete(&self, user_id: i32, index: i32) -> Result<(), Box<dyn Error>> {
+        token_model::delete(&self.pool, user_id, index).await
+    }
+
+    async fn token_user(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(User, Vec<Scope>)>, Box<dyn Error>> {
+        token_model::token_user(&self.pool, token_hash).await
+    }
+}
+
+#[async_trait]
+impl TokenRepository for MemoryRepo {
+    async fn create(
+        &self,
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        scopes: &[Scope],
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<ApiToken, Box<dyn Error>> {
+        let mut tables = self.write();
+        let token = ApiToken {
+            id: tables.next_id("api_tokens"),
+            user_id,
+            name: name.to_string(),
+            scopes: scopes.to_vec(),
+            created_at: OffsetDateTime::now_utc(),
+            expires_at,
+            last_used_at: None,
+        };
+        tables
+            .tokens
+            .insert(token.id, (token.clone(), token_hash.to_string()));
+
+        Ok(token)
+    }
+
+    async fn get_all(&self, user_id: i32) -> Result<Vec<ApiToken>, Box<dyn Error>> {
+        Ok(self
+            .read()
+            .tokens
+            .values()
+            .filter(|(token, _)| token.user_id == user_id)
+            .map(|(token, _)| token.clone())
+            .collect())
+    }
+
+    async fn delete(&self, user_id: i32, index: i32) -> Result<(), Box<dyn Error>> {
+        let mut tables = self.write();
+        match tables.tokens.get(&index) {
+            Some((token, _)) if token.user_id == user_id => {
+                tables.tokens.remove(&index);
+                Ok(())
+            }
+            _ => Err(Box::new(QuestionBankErr::TokenDoesNotExist(
+                index.to_string(),
+            ))),
+        }
+    }
+
+    async fn token_user(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<(User, Vec<Scope>)>, Box<dyn Error>> {
+        let mut tables = self.write();
+        let now = OffsetDateTime::now_utc();
+        let Some((token, _)) = tables.tokens.values_mut().find(|(token, hash)| {
+            hash == token_hash
+                && match token.expires_at {
+                    Some(expires_at) => expires_at > now,
+                    None => true,
+                }
+        }) else {
+            return Ok(None);
+        };
+        token.last_used_at = Some(now);
+        let (user_id, scopes) = (token.user_id, token.scopes.clone());
+
+        Ok(tables
+            .users
+            .get(&user_id)
+            .map(|(user, _)| (user.clone(), scopes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::memory_repo::tests::add_user;
+    use time::Duration;
+
+    #[tokio::test]
+    async fn memory_tokens_resolve_to_their_user_until_they_expire() {
+        let repo = MemoryRepo::new();
+        let user = add_user(&repo, "alice").await;
+        let token = repo
+            .create(user.id, "ci", "live", &[Scope::Read], None)
+            .await
+            .unwrap();
+        let expired = OffsetDateTime::now_utc() - Duration::hours(1);
+        repo.create(user.id, "old", "expired", &[Scope::Admin], Some(expired))
+            .await
+            .unwrap();
+
+        let (token_user, scopes) = repo.token_user("live").await.unwrap().unwrap();
+        assert_eq!((token_user.id, scopes), (user.id, vec![Scope::Read]));
+        assert!(repo.token_user("expired").await.unwrap().is_none());
+        assert_eq!(repo.get_all(user.id).await.unwrap().len(), 2);
+
+        assert!(repo.delete(user.id + 1, token.id).await.is_err());
+        repo.delete(user.id, token.id).await.unwrap();
+        assert!(repo.token_user("live").await.unwrap().is_none());
+    }
+}
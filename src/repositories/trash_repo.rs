@@ -0,0 +1,271 @@
+use crate::{
+    entities::{answer::Answer, question::Question, vote::VoteTarget},
+    models::trash_model,
+    pagination::{Keyed, Page, PageQuery, SortKey},
+    repositories::{lib::*, memory_repo::MemoryTables},
+};
+use std::collections::HashSet;
+use time::OffsetDateTime;
+
+/// Listing, restoring and purging deleted questions and answers, implemented by every
+/// repository backend.
+#[async_trait]
+pub trait TrashRepository: Debug + Send + Sync {
+    /// Retrieves a page of trashed questions, see [`trash_model::get_questions`].
+    async fn get_questions(&self, page: &PageQuery) -> Result<Page<Question>, Box<dyn Error>>;
+
+    /// Retrieves a page of trashed answers, see [`trash_model::get_answers`].
+    async fn get_answers(&self, page: &PageQuery) -> Result<Page<Answer>, Box<dyn Error>>;
+
+    /// Takes a question out of the trash, see [`trash_model::restore_question`].
+    async fn restore_question(&self, index: i32) -> Result<Question, Box<dyn Error>>;
+
+    /// Takes an answer out of the trash, see [`trash_model::restore_answer`].
+    async fn restore_answer(&self, index: i32) -> Result<Answer, Box<dyn Error>>;
+
+    /// Removes what was trashed before `before` for good, see [`trash_model::purge`].
+    async fn purge(&self, before: OffsetDateTime) -> Result<u64, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl TrashRepository for PostgresRepo {
+    async fn get_questions(&self, page: &PageQuery) -> Result<Page<Question>, Box<dyn Error>> {
+        trash_model::get_questions(&self.pool, page).await
+    }
+
+    async fn get_answers(&self, page: &PageQuery) -> Result<Page<Answer>, Box<dyn Error>> {
+        trash_model::get_answers(&self.pool, page).await
+    }
+
+    async fn restore_question(&self, index: i32) -> Result<Question, Box<dyn Error>> {
+        trash_model::restore_question(&self.pool, index).await
+    }
+
+    async fn restore_answer(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        trash_model::restore_answer(&self.pool, index).await
+    }
+
+    async fn purge(&self, before: OffsetDateTime) -> Result<u64, Box<dyn Error>> {
+        trash_model::purge(&self.pool, before).await
+    }
+}
+
+/// The sort key of something trashed at `deleted_at`, like [`trash_model::QUESTION_ORDER`].
+fn deleted_key(deleted_at: Option<OffsetDateTime>) -> SortKey {
+    deleted_at.map_or(SortKey::Null, SortKey::timestamp)
+}
+
+#[async_trait]
+impl TrashRepository for MemoryRepo {
+    async fn get_questions(&self, page: &PageQuery) -> Result<Page<Question>, Box<dyn Error>> {
+        let order = trash_model::QUESTION_ORDER;
+        let tables = self.read();
+        let mut questions: Vec<Keyed<Question>> = tables
+            .trashed_questions
+            .values()
+            .map(|question| Keyed {
+                key: deleted_key(question.deleted_at),
+                id: question.id.unwrap_or_default(),
+                item: tables.with_accepted_answer(question),
+            })
+            .collect();
+        questions.sort_by(|a, b| order.compare((&a.key, a.id), (&b.key, b.id)));
+
+        let total = questions.len() as i64;
+        Ok(page.page(&order, page.slice(&order, questions), total))
+    }
+
+    async fn get_answers(&self, page: &PageQuery) -> Result<Page<Answer>, Box<dyn Error>> {
+        let order = trash_model::ANSWER_ORDER;
+        let tables = self.read();
+        let mut answers: Vec<Keyed<Answer>> = tables
+            .trashed_answers
+            .values()
+            .filter(|answer| {
+                answer
+                    .question_id
+                    .is_some_and(|question_id| tables.questions.contains_key(&question_id))
+            })
+            .map(|answer| Keyed {
+                key: deleted_key(answer.deleted_at),
+                id: answer.id.unwrap_or_default(),
+                item: answer.clone(),
+            })
+            .collect();
+        answers.sort_by(|a, b| order.compare((&a.key, a.id), (&b.key, b.id)));
+
+        let total = answers.len() as i64;
+        Ok(page.page(&order, page.slice(&order, answers), total))
+    }
+
+    async fn restore_question(&self, index: i32) -> Result<Question, Box<dyn Error>> {
+        let mut tables = self.write();
+        let Some(mut question) = tables.trashed_questions.remove(&index) else {
+            return Err(if tables.questions.contains_key(&index) {
+                QuestionBankErr::NotInTrash(format!("Question {index}")).into()
+            } else {
+                QuestionBankErr::DoesNotExist(index.to_string()).into()
+            });
+        };
+        let deleted_at = question.deleted_at.take();
+
+        let MemoryTables {
+            answers,
+            trashed_answers,
+            ..
+        } = &mut *tables;
+        let (restored, kept) =
+            std::mem::take(trashed_answers)
+                .into_iter()
+                .partition(|(_, answer)| {
+                    answer.question_id == Some(index) && answer.deleted_at == deleted_at
+                });
+        *trashed_answers = kept;
+        for (answer_id, mut answer) in restored {
+            answer.deleted_at = None;
+            answers.insert(answer_id, answer);
+        }
+        tables.questions.insert(index, question);
+
+        Ok(tables.with_accepted_answer(&tables.questions[&index]))
+    }
+
+    async fn restore_answer(&self, index: i32) -> Result<Answer, Box<dyn Error>> {
+        let mut tables = self.write();
+        let Some(mut answer) = tables.trashed_answers.remove(&index) else {
+            return Err(if tables.answers.contains_key(&index) {
+                QuestionBankErr::NotInTrash(format!("Answer {index}")).into()
+            } else {
+                QuestionBankErr::AnswerDoesNotExist(index.to_string()).into()
+            });
+        };
+        let question_id = answer.question_id.unwrap_or_default();
+        if tables.trashed_questions.contains_key(&question_id) {
+            tables.trashed_answers.insert(index, answer);
+            return Err(Box::new(QuestionBankErr::InTrash(format!(
+                "Question {question_id}"
+            ))));
+        }
+
+        answer.deleted_at = None;
+        tables.answers.insert(index, answer.clone());
+
+        Ok(answer)
+    }
+
+    async fn purge(&self, before: OffsetDateTime) -> Result<u64, Box<dyn Error>> {
+        let mut tables = self.write();
+        let expired = |deleted_at: Option<OffsetDateTime>| match deleted_at {
+            Some(deleted_at) => deleted_at < before,
+            None => false,
+        };
+        let before_count = tables.trashed_questions.len() + tables.trashed_answers.len();
+        tables
+            .trashed_questions
+            .retain(|_, question| !expired(question.deleted_at));
+        tables
+            .trashed_answers
+            .retain(|_, answer| !expired(answer.deleted_at));
+
+        let MemoryTables {
+            questions,
+            answers,
+            trashed_questions,
+            trashed_answers,
+            revisions,
+            votes,
+            comments,
+            closures,
+            quiz_items,
+            reviews,
+            ..
+        } = &mut *tables;
+        let question_exists =
+            |id: &i32| questions.contains_key(id) || trashed_questions.contains_key(id);
+        let answer_exists = |id: &i32| answers.contains_key(id) || trashed_answers.contains_key(id);
+        revisions.retain(
+            |revision| match (revision.question_id, revision.answer_id) {
+                (_, Some(answer_id)) => answer_exists(&answer_id),
+                (Some(question_id), None) => question_exists(&question_id),
+                (None, None) => false,
+            },
+        );
+        votes.retain(|(target, id, _), _| match target {
+            VoteTarget::Question => question_exists(id),
+            VoteTarget::Answer => answer_exists(id),
+        });
+        comments.retain(
+            |_, comment| match (comment.question_id, comment.answer_id) {
+                (_, Some(answer_id)) => answer_exists(&answer_id),
+                (Some(question_id), None) => question_exists(&question_id),
+                (None, None) => false,
+            },
+        );
+        closures.retain(|event| question_exists(&event.question_id));
+        quiz_items.retain(|(_, question_id), _| question_exists(question_id));
+        reviews.retain(|(_, question_id), _| question_exists(question_id));
+        for event in closures.iter_mut() {
+            if event.duplicate_of.is_some_and(|id| !question_exists(&id)) {
+                event.duplicate_of = None;
+            }
+        }
+        let purged = before_count - trashed_questions.len() - trashed_answers.len();
+
+        // Like the foreign key, duplicates of purged questions no longer point anywhere
+        let kept: HashSet<i32> = questions
+            .keys()
+            .chain(trashed_questions.keys())
+            .copied()
+            .collect();
+        for question in tables.all_questions_mut() {
+            if let Some(closed) = question.closed.as_mut() {
+                if closed.duplicate_of.is_some_and(|id| !kept.contains(&id)) {
+                    closed.duplicate_of = None;
+                }
+            }
+        }
+        tables.delete_orphan_tags();
+
+        Ok(purged as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pagination::Position,
+        repositories::{
+            memory_repo::tests::{add_answer, add_question},
+            question_repo::QuestionRepository,
+        },
+    };
+    use time::Duration;
+
+    #[tokio::test]
+    async fn memory_trashed_questions_come_back_with_their_answers() {
+        let repo = MemoryRepo::new();
+        let id = add_question(&repo, "Where did it go?", &["trash"]).await;
+        let answer_id = add_answer(&repo, id, "In the trash").await;
+        repo.delete(id).await.unwrap();
+
+        let first_page = PageQuery {
+            position: Position::Offset(0),
+            limit: 10,
+        };
+        assert_eq!(repo.get_questions(&first_page).await.unwrap().total, 1);
+        // Answers trashed along with their question are listed under it
+        assert_eq!(repo.get_answers(&first_page).await.unwrap().total, 0);
+        assert!(repo.restore_answer(answer_id).await.is_err());
+        assert_eq!(repo.restore_question(id).await.unwrap().id, Some(id));
+        assert!(repo.read().answers.contains_key(&answer_id));
+        assert!(repo.restore_question(id).await.is_err());
+
+        repo.delete(id).await.unwrap();
+        let now = OffsetDateTime::now_utc();
+        assert_eq!(repo.purge(now - Duration::days(1)).await.unwrap(), 0);
+        assert_eq!(repo.purge(now + Duration::seconds(1)).await.unwrap(), 2);
+        assert!(repo.restore_question(id).await.is_err());
+        assert!(repo.read().tags.is_empty());
+    }
+}
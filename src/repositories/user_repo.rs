@@ -0,0 +1,328 @@
+use crate::{
+    entities::{
+        user::{Role, User},
+        vote::VoteTarget,
+    },
+    models::user_model,
+    pagination::{Keyed, Page, PageQuery, SortKey},
+    repositories::{lib::*, memory_repo::MemoryTables},
+};
+use time::OffsetDateTime;
+
+/// Storage operations on users and their login sessions, implemented by every
+/// repository backend.
+#[async_trait]
+pub trait UserRepository: Debug + Send + Sync {
+    /// Adds a new user, see [`user_model::create`].
+    async fn create(&self, username: &str, password_hash: &str) -> Result<User, Box<dyn Error>>;
+
+    /// Retrieves a user and their password hash, see [`user_model::get_credentials`].
+    async fn get_credentials(
+        &self,
+        username: &str,
+    ) -> Result<Option<(User, String)>, Box<dyn Error>>;
+
+    /// Stores a new login session, see [`user_model::create_session`].
+    async fn create_session(
+        &self,
+        user_id: i32,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Retrieves the user a session belongs to, see [`user_model::session_user`].
+    async fn session_user(&self, token_hash: &str) -> Result<Option<User>, Box<dyn Error>>;
+
+    /// Removes a login session, see [`user_model::delete_session`].
+    async fn delete_session(&self, token_hash: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Retrieves a page of users, see [`user_model::get_all`].
+    async fn get_all(&self, page: &PageQuery) -> Result<Page<User>, Box<dyn Error>>;
+
+    /// Changes the role of a user, see [`user_model::set_role`].
+    async fn set_role(&self, index: i32, role: Role) -> Result<User, Box<dyn Error>>;
+
+    /// Removes a user, see [`user_model::delete`].
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>>;
+}
+
+#[async_trait]
+impl UserRepository for PostgresRepo {
+    async fn create(&self, username: &str, password_hash: &str) -> Result<User, Box<dyn Error>> {
+        user_model::create(&self.pool, username, password_hash).await
+    }
+
+    async fn get_credentials(
+        &self,
+        username: &str,
+    ) -> Result<Option<(User, String)>, Box<dyn Error>> {
+        user_model::get_credentials(&self.pool, username).await
+    }
+
+    async fn create_session(
+        &self,
+        user_id: i32,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), Box<dyn Error>> {
+        user_model::create_session(&self.pool, user_id, token_hash, expires_at).await
+    }
+
+    async fn session_user(&self, token_hash: &str) -> Result<Option<User>, Box<dyn Error>> {
+        user_model::session_user(&self.pool, token_hash).await
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), Box<dyn Error>> {
+        user_model::delete_session(&self.pool, token_hash).await
+    }
+
+    async fn get_all(&self, page: &PageQuery) -> Result<Page<User>, Box<dyn Error>> {
+        user_model::get_all(&self.pool, page).await
+    }
+
+    async fn set_role(&self, index: i32, role: Role) -> Result<User, Box<dyn Error>> {
+        user_model::set_role(&self.pool, index, role).await
+    }
+
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>> {
+        user_model::delete(&self.pool, index).await
+    }
+}
+
+/// Fails if `index` is the only admin, like [`user_model::set_role`] and
+/// [`user_model::delete`] do.
+fn check_not_last_admin(tables: &MemoryTables, index: i32) -> Result<(), QuestionBankErr> {
+    let mut admins = tables
+        .users
+        .values()
+        .filter(|(user, _)| user.role == Role::Admin)
+        .map(|(user, _)| user.id);
+    if admins.next() == Some(index) && admins.next().is_none() {
+        return Err(QuestionBankErr::LastAdmin(index.to_string()));
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl UserRepository for MemoryRepo {
+    async fn create(&self, username: &str, password_hash: &str) -> Result<User, Box<dyn Error>> {
+        let mut tables = self.write();
+        if tables
+            .users
+            .values()
+            .any(|(user, _)| user.username == username)
+        {
+            return Err(Box::new(QuestionBankErr::AlreadyExists(format!(
+                "User {username}"
+            ))));
+        }
+
+        let user = User {
+            id: tables.next_id("users"),
+            username: username.to_string(),
+            role: Role::Member,
+        };
+        tables
+            .users
+            .insert(user.id, (user.clone(), password_hash.to_string()));
+
+        Ok(user)
+    }
+
+    async fn get_credentials(
+        &self,
+        username: &str,
+    ) -> Result<Option<(User, String)>, Box<dyn Error>> {
+        Ok(self
+            .read()
+            .users
+            .values()
+            .find(|(user, _)| user.username == username)
+            .cloned())
+    }
+
+    async fn create_session(
+        &self,
+        user_id: i32,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut tables = self.write();
+        let now = OffsetDateTime::now_utc();
+        tables
+            .sessions
+            .retain(|_, (session_user, expires_at)| *session_user != user_id || *expires_at > now);
+        tables
+            .sessions
+            .insert(token_hash.to_string(), (user_id, expires_at));
+
+        Ok(())
+    }
+
+    async fn session_user(&self, token_hash: &str) -> Result<Option<User>, Box<dyn Error>> {
+        let tables = self.read();
+        Ok(tables
+            .sessions
+            .get(token_hash)
+            .filter(|(_, expires_at)| *expires_at > OffsetDateTime::now_utc())
+            .and_then(|(user_id, _)| tables.users.get(user_id))
+            .map(|(user, _)| user.clone()))
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), Box<dyn Error>> {
+        self.write().sessions.remove(token_hash);
+
+        Ok(())
+    }
+
+    async fn get_all(&self, page: &PageQuery) -> Result<Page<User>, Box<dyn Error>> {
+        let order = user_model::ORDER;
+        let users: Vec<Keyed<User>> = self
+            .read()
+            .users
+            .values()
+            .map(|(user, _)| Keyed {
+                key: SortKey::Null,
+                id: user.id,
+                item: user.clone(),
+            })
+            .collect();
+
+        let total = users.len() as i64;
+        Ok(page.page(&order, page.slice(&order, users), total))
+    }
+
+    async fn set_role(&self, index: i32, role: Role) -> Result<User, Box<dyn Error>> {
+        let mut tables = self.write();
+        if role != Role::Admin {
+            check_not_last_admin(&tables, index)?;
+        }
+        let (user, _) = tables
+            .users
+            .get_mut(&index)
+            .ok_or_else(|| QuestionBankErr::UserDoesNotExist(index.to_string()))?;
+        user.role = role;
+
+        Ok(user.clone())
+    }
+
+    async fn delete(&self, index: i32) -> Result<(), Box<dyn Error>> {
+        let mut tables = self.write();
+        check_not_last_admin(&tables, index)?;
+        if tables.users.remove(&index).is_none() {
+            return Err(Box::new(QuestionBankErr::UserDoesNotExist(
+                index.to_string(),
+            )));
+        }
+
+        tables.sessions.retain(|_, (user_id, _)| *user_id != index);
+        tables.tokens.retain(|_, (token, _)| token.user_id != index);
+        let votes: Vec<_> = tables
+            .votes
+            .iter()
+            .filter(|((_, _, user_id), _)| *user_id == index)
+            .map(|(key, value)| (*key, *value))
+            .collect();
+        for ((target, id, user_id), value) in votes {
+            tables.votes.remove(&(target, id, user_id));
+            let score = match target {
+                VoteTarget::Question => tables
+                    .all_questions_mut()
+                    .find(|q| q.id == Some(id))
+                    .map(|q| &mut q.score),
+                VoteTarget::Answer => tables
+                    .all_answers_mut()
+                    .find(|a| a.id == Some(id))
+                    .map(|a| &mut a.score),
+            };
+            if let Some(score) = score {
+                *score -= i32::from(value);
+            }
+        }
+        for question in tables.all_questions_mut() {
+            if question.author_id == Some(index) {
+                question.author_id = None;
+            }
+            if let Some(closed) = question.closed.as_mut() {
+                if closed.closed_by == Some(index) {
+                    closed.closed_by = None;
+                }
+            }
+        }
+        for answer in tables.all_answers_mut() {
+            if answer.author_id == Some(index) {
+                answer.author_id = None;
+            }
+        }
+        for revision in tables.revisions.iter_mut() {
+            if revision.author_id == Some(index) {
+                revision.author_id = None;
+            }
+        }
+        for comment in tables.comments.values_mut() {
+            if comment.author_id == Some(index) {
+                comment.author_id = None;
+            }
+        }
+        for event in tables.closures.iter_mut() {
+            if event.user_id == Some(index) {
+                event.user_id = None;
+            }
+        }
+        let MemoryTables {
+            quiz_sessions,
+            quiz_items,
+            reviews,
+            ..
+        } = &mut *tables;
+        quiz_sessions.retain(|_, session| session.user_id != index);
+        quiz_items.retain(|(session_id, _), _| quiz_sessions.contains_key(session_id));
+        reviews.retain(|(user_id, _), _| *user_id != index);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagination::Position;
+    use time::Duration;
+
+    #[tokio::test]
+    async fn memory_users_keep_an_admin_and_their_sessions() {
+        let repo = MemoryRepo::new();
+        let alice = repo.create("alice", "hash").await.unwrap();
+        assert_eq!(alice.role, Role::Member);
+        assert!(repo.create("alice", "other hash").await.is_err());
+        let (user, hash) = repo.get_credentials("alice").await.unwrap().unwrap();
+        assert_eq!((user.id, hash.as_str()), (alice.id, "hash"));
+
+        let expires_at = OffsetDateTime::now_utc() + Duration::hours(1);
+        repo.create_session(alice.id, "session", expires_at)
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.session_user("session").await.unwrap().map(|u| u.id),
+            Some(alice.id)
+        );
+        repo.delete_session("session").await.unwrap();
+        assert!(repo.session_user("session").await.unwrap().is_none());
+
+        repo.set_role(alice.id, Role::Admin).await.unwrap();
+        assert!(repo.set_role(alice.id, Role::Member).await.is_err());
+        assert!(repo.delete(alice.id).await.is_err());
+        let bob = repo.create("bob", "hash").await.unwrap();
+        repo.set_role(bob.id, Role::Admin).await.unwrap();
+        repo.delete(alice.id).await.unwrap();
+
+        let first_page = PageQuery {
+            position: Position::Offset(0),
+            limit: 10,
+        };
+        let page = repo.get_all(&first_page).await.unwrap();
+        let ids: Vec<_> = page.items.iter().map(|user| user.id).collect();
+        assert_eq!(ids, [bob.id]);
+    }
+}
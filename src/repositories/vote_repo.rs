@@ -0,0 +1,139 @@
+use crate::{entities::vote::*, models::vote_model, repositories::lib::*};
+use std::collections::HashMap;
+
+/// Storage operations on votes, implemented by every repository backend.
+#[async_trait]
+pub trait VoteRepository: Debug + Send + Sync {
+    /// Sets or removes a user's vote, see [`vote_model::set_vote`].
+    async fn set_vote(
+        &self,
+        target: VoteTarget,
+        index: i32,
+        user_id: i32,
+        value: Option<i16>,
+    ) -> Result<Vote, Box<dyn Error>>;
+
+    /// Retrieves a user's votes, see [`vote_model::user_votes`].
+    async fn user_votes(
+        &self,
+        target: VoteTarget,
+        user_id: i32,
+        ids: &[i32],
+    ) -> Result<HashMap<i32, i16>, Box<dyn Error>>;
+}
+
+#[async_trait]
+impl VoteRepository for PostgresRepo {
+    async fn set_vote(
+        &self,
+        target: VoteTarget,
+        index: i32,
+        user_id: i32,
+        value: Option<i16>,
+    ) -> Result<Vote, Box<dyn Error>> {
+        vote_model::set_vote(&self.pool, target, index, user_id, value).await
+    }
+
+    async fn user_votes(
+        &self,
+        target: VoteTarget,
+        user_id: i32,
+        ids: &[i32],
+    ) -> Result<HashMap<i32, i16>, Box<dyn Error>> {
+        vote_model::user_votes(&self.pool, target, user_id, ids).await
+    }
+}
+
+#[async_trait]
+impl VoteRepository for MemoryRepo {
+    async fn set_vote(
+        &self,
+        target: VoteTarget,
+        index: i32,
+        user_id: i32,
+        value: Option<i16>,
+    ) -> Result<Vote, Box<dyn Error>> {
+        let mut tables = self.write();
+        let exists = match target {
+            VoteTarget::Question => tables.questions.contains_key(&index),
+            VoteTarget::Answer => tables.answers.contains_key(&index),
+        };
+        if !exists {
+            return Err(Box::new(match target {
+                VoteTarget::Question => QuestionBankErr::DoesNotExist(index.to_string()),
+                VoteTarget::Answer => QuestionBankErr::AnswerDoesNotExist(index.to_string()),
+            }));
+        }
+
+        let key = (target, index, user_id);
+        let previous = match value {
+            Some(value) => tables.votes.insert(key, value),
+            None => tables.votes.remove(&key),
+        };
+        let change = i32::from(value.unwrap_or(0)) - i32::from(previous.unwrap_or(0));
+        let score = match target {
+            VoteTarget::Question => tables.questions.get_mut(&index).map(|q| &mut q.score),
+            VoteTarget::Answer => tables.answers.get_mut(&index).map(|a| &mut a.score),
+        }
+        .map(|score| {
+            *score += change;
+            *score
+        })
+        .unwrap_or_default();
+
+        Ok(Vote {
+            score,
+            my_vote: value,
+        })
+    }
+
+    async fn user_votes(
+        &self,
+        target: VoteTarget,
+        user_id: i32,
+        ids: &[i32],
+    ) -> Result<HashMap<i32, i16>, Box<dyn Error>> {
+        let tables = self.read();
+        Ok(ids
+            .iter()
+            .filter_map(|id| {
+                tables
+                    .votes
+                    .get(&(target, *id, user_id))
+                    .map(|value| (*id, *value))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::memory_repo::tests::add_question;
+
+    #[tokio::test]
+    async fn memory_votes_change_the_score_once_per_user() {
+        let repo = MemoryRepo::new();
+        let id = add_question(&repo, "Is this useful?", &[]).await;
+
+        let up = |user_id| repo.set_vote(VoteTarget::Question, id, user_id, Some(1));
+        assert_eq!(up(1).await.unwrap().score, 1);
+        assert_eq!(up(1).await.unwrap().score, 1);
+        assert_eq!(up(2).await.unwrap().score, 2);
+        let removed = repo
+            .set_vote(VoteTarget::Question, id, 1, None)
+            .await
+            .unwrap();
+        assert_eq!((removed.score, removed.my_vote), (1, None));
+        assert!(repo
+            .set_vote(VoteTarget::Answer, id, 1, Some(1))
+            .await
+            .is_err());
+
+        let votes = repo
+            .user_votes(VoteTarget::Question, 2, &[id, id + 1])
+            .await
+            .unwrap();
+        assert_eq!(votes, HashMap::from([(id, 1)]));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::grading::Grade;
+use time::{Duration, OffsetDateTime};
+
+/// Ease of a card that was never reviewed
+pub const INITIAL_EASE: f32 = 2.5;
+
+/// Lowest ease a card can drop to, so hard cards still come back less and less often
+pub const MIN_EASE: f32 = 1.3;
+
+/// Lowest quality of a review that counts as remembering the answer
+pub const PASSING_QUALITY: u8 = 3;
+
+/// Best quality of a review
+pub const MAX_QUALITY: u8 = 5;
+
+/// Longest interval between two reviews, in days
+pub const MAX_INTERVAL: i32 = 36500;
+
+/// How well a user knows the answer to a question, after the SM-2 algorithm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Card {
+    /// How fast the interval grows with each successful review
+    pub ease: f32,
+    /// Days until the next review
+    pub interval: i32,
+    /// Successful reviews in a row
+    pub repetitions: i32,
+}
+
+impl Default for Card {
+    fn default() -> Self {
+        Self {
+            ease: INITIAL_EASE,
+            interval: 0,
+            repetitions: 0,
+        }
+    }
+}
+
+/// The SM-2 quality of a graded quiz answer, from 0 to [`MAX_QUALITY`]: 5 for the exact
+/// answer, 4 for a correct one, and 2 to 0 for a wrong one by how close it came.
+pub fn quality(grade: &Grade) -> u8 {
+    match (grade.correct, grade.similarity) {
+        (true, similarity) if similarity >= 1.0 => MAX_QUALITY,
+        (true, _) => 4,
+        (false, similarity) if similarity >= 0.5 => 2,
+        (false, similarity) if similarity > 0.0 => 1,
+        (false, _) => 0,
+    }
+}
+
+/// Schedules a card after a review.
+///
+/// # Parameters
+///
+/// * `card`: The card as it was before the review.
+/// * `quality`: How well the answer was remembered, from 0 to [`MAX_QUALITY`], higher being
+///   capped.
+///
+/// # Returns
+///
+/// The card after the review. A passing review takes the interval to 1 day, then 6, then
+/// the previous interval times the ease; a failing one starts over at 1 day. The ease moves
+/// with the quality but never drops below [`MIN_EASE`].
+pub fn review(card: Card, quality: u8) -> Card {
+    let quality = quality.min(MAX_QUALITY);
+    let (interval, repetitions) = if quality >= PASSING_QUALITY {
+        let interval = match card.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (card.interval as f32 * card.ease).round() as i32,
+        };
+        (interval.clamp(1, MAX_INTERVAL), card.repetitions + 1)
+    } else {
+        (1, 0)
+    };
+    let missed = f32::from(MAX_QUALITY - quality);
+    let ease = (card.ease + 0.1 - missed * (0.08 + missed * 0.02)).max(MIN_EASE);
+
+    Card {
+        ease,
+        interval,
+        repetitions,
+    }
+}
+
+/// When a card reviewed at `reviewed_at` is due again.
+pub fn due_at(reviewed_at: OffsetDateTime, card: &Card) -> OffsetDateTime {
+    reviewed_at + Duration::days(card.interval.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grade(correct: bool, similarity: f32) -> Grade {
+        Grade {
+            correct,
+            similarity,
+        }
+    }
+
+    #[test]
+    fn quality_follows_the_grade() {
+        assert_eq!(quality(&grade(true, 1.0)), 5);
+        assert_eq!(quality(&grade(true, 0.85)), 4);
+        assert_eq!(quality(&grade(false, 0.6)), 2);
+        assert_eq!(quality(&grade(false, 0.2)), 1);
+        assert_eq!(quality(&grade(false, 0.0)), 0);
+    }
+
+    #[test]
+    fn passing_reviews_grow_the_interval() {
+        let first = review(Card::default(), 4);
+        assert_eq!((first.interval, first.repetitions), (1, 1));
+        assert_eq!(first.ease, INITIAL_EASE);
+
+        let second = review(first, 4);
+        assert_eq!((second.interval, second.repetitions), (6, 2));
+
+        let third = review(second, 4);
+        assert_eq!((third.interval, third.repetitions), (15, 3));
+    }
+
+    #[test]
+    fn ease_moves_with_quality() {
+        let easy = review(Card::default(), 5);
+        assert!((easy.ease - 2.6).abs() < 1e-6);
+
+        let hard = review(Card::default(), 3);
+        assert!((hard.ease - 2.36).abs() < 1e-6);
+        assert_eq!(hard.repetitions, 1);
+    }
+
+    #[test]
+    fn failing_review_starts_over() {
+        let card = Card {
+            ease: 2.5,
+            interval: 15,
+            repetitions: 3,
+        };
+        let failed = review(card, 1);
+        assert_eq!((failed.interval, failed.repetitions), (1, 0));
+        assert!((failed.ease - 1.96).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ease_has_a_floor() {
+        let mut card = Card::default();
+        for _ in 0..10 {
+            card = review(card, 0);
+        }
+        assert_eq!(card.ease, MIN_EASE);
+        assert_eq!(card.interval, 1);
+    }
+
+    #[test]
+    fn quality_above_the_scale_counts_as_best() {
+        assert_eq!(review(Card::default(), 9), review(Card::default(), 5));
+    }
+
+    #[test]
+    fn interval_is_capped() {
+        let card = Card {
+            ease: 2.5,
+            interval: MAX_INTERVAL,
+            repetitions: 10,
+        };
+        assert_eq!(review(card, 5).interval, MAX_INTERVAL);
+    }
+
+    #[test]
+    fn due_after_the_interval() {
+        let reviewed_at = OffsetDateTime::UNIX_EPOCH;
+        let card = review(review(Card::default(), 4), 4);
+        assert_eq!(due_at(reviewed_at, &card), reviewed_at + Duration::days(6));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    auth::{Access, CurrentUser},
+    entities::{answer::Answer, comment::Thread, question::Question, tag::Tag, user::User},
+};
+use askama::Template;
+use axum::http::StatusCode;
+use serde::Deserialize;
+use std::{error::Error, ops::Deref};
+
+/// The logged in user, shown in the page header
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub user: User,
+    /// The token the page's forms post back, empty for API tokens which don't need it
+    pub csrf: String,
+}
+
+impl From<&CurrentUser> for Account {
+    fn from(CurrentUser(user, access): &CurrentUser) -> Self {
+        let csrf = match access {
+            Access::Session { csrf } => csrf.clone(),
+            Access::Token(_) => String::new(),
+        };
+        Self {
+            user: user.clone(),
+            csrf,
+        }
+    }
+}
+
+impl Deref for Account {
+    type Target = User;
+
+    fn deref(&self) -> &User {
+        &self.user
+    }
+}
+
+/// A page of the question list
+#[derive(Template)]
+#[template(path = "questions.html")]
+pub struct QuestionsPage {
+    pub user: Option<Account>,
+    pub title: String,
+    pub questions: Vec<Question>,
+    pub total: i64,
+    /// Link to the following page, if there is one
+    pub next: Option<String>,
+    /// Link to the preceding page, if there is one
+    pub prev: Option<String>,
+}
+
+/// A question with its answers
+#[derive(Template)]
+#[template(path = "question.html")]
+pub struct QuestionPage {
+    pub user: Option<Account>,
+    pub title: String,
+    pub question_id: i32,
+    pub question: Question,
+    /// The question's comments
+    pub comments: Thread,
+    /// A page of the answers, along with whether the user may edit them and their comment
+    /// thread
+    pub answers: Vec<(Answer, bool, Thread)>,
+    /// The number of answers on all pages
+    pub answer_total: i64,
+    /// Link to the following page of answers, if there is one
+    pub next: Option<String>,
+    /// Link to the preceding page of answers, if there is one
+    pub prev: Option<String>,
+    /// Whether the user may edit the question
+    pub can_edit: bool,
+    /// Whether the user may post answers and comments
+    pub can_answer: bool,
+}
+
+/// A page of the tag list
+#[derive(Template)]
+#[template(path = "tags.html")]
+pub struct TagsPage {
+    pub user: Option<Account>,
+    pub title: String,
+    pub tags: Vec<Tag>,
+    pub total: i64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+/// A tag with a page of its questions
+#[derive(Template)]
+#[template(path = "tag.html")]
+pub struct TagPage {
+    pub user: Option<Account>,
+    pub title: String,
+    pub tag: Tag,
+    pub questions: Vec<Question>,
+    pub total: i64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+/// The fields of the question form
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct QuestionForm {
+    pub title: String,
+    pub content: String,
+    /// Comma separated tags
+    #[serde(default)]
+    pub tags: String,
+    /// See [`Account::csrf`]
+    #[serde(default)]
+    pub csrf: String,
+}
+
+impl QuestionForm {
+    /// Fills the form in from an existing question.
+    pub fn from_question(question: &Question) -> Self {
+        Self {
+            title: question.title.clone(),
+            content: question.content.clone(),
+            tags: question.tags.as_deref().unwrap_or_default().join(", "),
+            csrf: String::new(),
+        }
+    }
+
+    /// Turns the submitted form into a question.
+    pub fn into_question(self) -> Question {
+        let tags: Vec<&str> = self.tags.split(',').collect();
+        Question::new(None, &self.title, &self.content, &tags)
+    }
+}
+
+/// The form to ask or edit a question
+#[derive(Template)]
+#[template(path = "question_form.html")]
+pub struct QuestionFormPage {
+    pub user: Option<Account>,
+    pub title: String,
+    /// Where the form is posted to
+    pub action: String,
+    pub form: QuestionForm,
+    pub error: Option<String>,
+}
+
+/// The fields of the answer form
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnswerForm {
+    pub answer: String,
+    /// See [`Account::csrf`]
+    #[serde(default)]
+    pub csrf: String,
+}
+
+/// The fields of the comment form
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CommentForm {
+    pub body: String,
+    /// The comment replied to
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+    /// See [`Account::csrf`]
+    #[serde(default)]
+    pub csrf: String,
+}
+
+/// The fields of the logout form
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LogoutForm {
+    /// See [`Account::csrf`]
+    #[serde(default)]
+    pub csrf: String,
+}
+
+/// The form to edit an answer
+#[derive(Template)]
+#[template(path = "answer_form.html")]
+pub struct AnswerFormPage {
+    pub user: Option<Account>,
+    pub title: String,
+    pub answer_id: i32,
+    pub question_id: i32,
+    pub form: AnswerForm,
+    pub error: Option<String>,
+}
+
+/// The login form
+#[derive(Template)]
+#[template(path = "login.html")]
+pub struct LoginPage {
+    pub user: Option<Account>,
+    pub title: String,
+    pub error: Option<String>,
+}
+
+/// Shown instead of a page that failed
+#[derive(Template)]
+#[template(path = "error.html")]
+pub struct ErrorPage {
+    pub user: Option<Account>,
+    pub title: String,
+    pub message: String,
+}
+
+impl ErrorPage {
+    /// Builds the page for an error. Server errors are logged and shown as a generic
+    /// message, so database details don't end up on the page.
+    pub fn new(status: StatusCode, user: Option<Account>, error: &dyn Error) -> Self {
+        let message = if status.is_server_error() {
+            tracing::error!("{error}");
+            "Something went wrong, please try again later".to_string()
+        } else {
+            error.to_string()
+        };
+        Self {
+            user,
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            message,
+        }
+    }
+}
@@ -1,17 +1,25 @@
 #!/bin/bash
+# Posting needs an API token with the write scope, create one with
+# POST /api/v1/tokens and pass it as QB_TOKEN=qb_... ./post.sh
+#
+# ./post.sh imports the seed questions in questions.json, ./post.sh 100
+# generates 100 questions instead and streams them as NDJSON.
 
-for i in {1..100}; do
-  curl -X 'POST' \
-  'http://localhost:3000/api/v1/questions/add' \
+url='http://localhost:3000/api/v1/import'
+
+if [ -z "$1" ]; then
+  curl -X 'POST' "$url" \
   -H 'accept: application/json' \
   -H 'Content-Type: application/json' \
-  -d "{
-  \"content\": \"Content$i\",
-  \"id\": $i,
-  \"tags\": [
-    \"history\",
-    \"math\"
-  ],
-  \"title\": \"Title$i\"
-}"
-done
+  -H "Authorization: Bearer $QB_TOKEN" \
+  --data-binary "@$(dirname "$0")/questions.json"
+else
+  for ((i = 1; i <= $1; i++)); do
+    echo "{\"title\": \"Title$i\", \"content\": \"Content$i\", \"tags\": [\"history\", \"math\"], \"answers\": [{\"answer\": \"Answer$i\", \"accepted\": true}]}"
+  done | curl -X 'POST' "$url" \
+  -H 'accept: application/json' \
+  -H 'Content-Type: application/x-ndjson' \
+  -H "Authorization: Bearer $QB_TOKEN" \
+  -T -
+fi
+echo